@@ -0,0 +1,787 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use serde::Deserialize;
+
+use crate::services::notification::NOTIFIERS;
+use crate::services::payment::{opay, paystack};
+use crate::services::storage::STORAGE_BACKENDS;
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+const MB: usize = 1024 * 1024;
+
+/// Collection keys (also the env vars overriding them) and their default names.
+/// The default name is the key used in the `[database.collections]` table of the config file.
+const COLLECTIONS: [(&str, &str); 22] = [
+    ("MONGO_PRODUCTS_COLLECTION", "products"),
+    ("MONGO_USERS_COLLECTION", "users"),
+    ("MONGO_ORDERS_COLLECTION", "orders"),
+    ("MONGO_CART_COLLECTION", "cart"),
+    ("MONGO_REVIEWS_COLLECTION", "reviews"),
+    ("MONGO_RETURNS_COLLECTION", "returns"),
+    ("MONGO_RETURN_POLICIES_COLLECTION", "return_policies"),
+    ("MONGO_COUPONS_COLLECTION", "coupons"),
+    ("MONGO_COUPON_REDEMPTIONS_COLLECTION", "coupon_redemptions"),
+    ("MONGO_CART_COUPONS_COLLECTION", "cart_coupons"),
+    ("MONGO_PROMOTIONS_COLLECTION", "promotions"),
+    ("MONGO_PRICE_CHANGES_COLLECTION", "price_changes"),
+    ("MONGO_PRICE_HISTORY_COLLECTION", "price_history"),
+    ("MONGO_SHIPPING_ZONES_COLLECTION", "shipping_zones"),
+    ("MONGO_ADDRESSES_COLLECTION", "addresses"),
+    ("MONGO_TAX_RULES_COLLECTION", "tax_rules"),
+    ("MONGO_NOTIFICATIONS_COLLECTION", "notifications"),
+    ("MONGO_CART_REMINDERS_COLLECTION", "cart_reminders"),
+    ("MONGO_WISHLISTS_COLLECTION", "wishlists"),
+    ("MONGO_PRODUCT_ALERTS_COLLECTION", "product_alerts"),
+    ("MONGO_UPLOADS_COLLECTION", "uploads"),
+    ("MONGO_MEDIA_COLLECTION", "media"),
+];
+
+/// Application settings, read once at startup from the environment and an optional
+/// TOML file (CONFIG_FILE, config.toml by default). Environment variables win.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub auth: AuthConfig,
+    pub storage: StorageConfig,
+    pub links: LinksConfig,
+    pub cors: CorsConfig,
+    pub limits: BodyLimits,
+    pub payments: PaymentsConfig,
+    pub orders: OrdersConfig,
+    pub returns: ReturnsConfig,
+    pub media: MediaConfig,
+    pub notifications: NotificationsConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub host: IpAddr,
+    pub port: u16,
+    pub tls: Option<TlsConfig>,  // plain HTTP when unset, e.g. behind a proxy
+    pub shutdown_timeout: Duration,  // how long in-flight requests and jobs get to finish
+}
+
+/// PEM files to terminate TLS with
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub name: String,
+    collections: HashMap<&'static str, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    pub jwt_secret: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct StorageConfig {
+    pub backend: String,  // "s3" or "local"
+    pub s3_bucket: String,  // only set for the s3 backend
+    pub aws_region: String,
+    pub local_dir: String,
+    pub local_public_url: String,
+    pub local_upload_url: String,
+    pub local_signing_key: String,
+}
+
+/// Base URLs of links handed out to customers
+#[derive(Debug, Clone)]
+pub struct LinksConfig {
+    pub order_lookup_url: String,
+    pub wishlist_share_url: String,
+}
+
+/// Cross-origin requests browsers may make. No origin is allowed unless configured.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<HeaderValue>,
+    pub allow_any_origin: bool,  // set by listing "*" as the only origin
+    pub allowed_methods: Vec<Method>,
+    pub allowed_headers: Vec<HeaderName>,
+    pub allow_credentials: bool,
+    pub max_age: Duration,
+}
+
+/// Max request body size, in bytes, of each route group
+#[derive(Debug, Clone)]
+pub struct BodyLimits {
+    pub public: usize,
+    pub shopping: usize,
+    pub uploads: usize,
+    pub customer: usize,  // payment receipts and return photos
+    pub admin: usize,
+}
+
+/// Payment providers and the bank account for transfers. A payment method is only
+/// offered when its section is configured.
+#[derive(Debug, Clone)]
+pub struct PaymentsConfig {
+    pub callback_url: Option<String>,  // where providers send the customer after paying
+    pub paystack: Option<PaystackConfig>,
+    pub opay: Option<OpayConfig>,
+    pub bank_transfer: Option<BankTransferConfig>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaystackConfig {
+    pub secret_key: String,
+    pub base_url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpayConfig {
+    pub merchant_id: String,
+    pub public_key: String,
+    pub secret_key: String,
+    pub base_url: String,
+}
+
+/// Account customers paying offline transfer to
+#[derive(Debug, Clone)]
+pub struct BankTransferConfig {
+    pub bank_name: String,
+    pub account_name: String,
+    pub account_number: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrdersConfig {
+    pub offline_payment_deadline_hours: i64,  // unpaid transfers are cancelled after this
+}
+
+#[derive(Debug, Clone)]
+pub struct ReturnsConfig {
+    pub window_days: i64,  // for categories without their own return policy
+}
+
+#[derive(Debug, Clone)]
+pub struct MediaConfig {
+    pub orphan_grace_hours: i64,  // how long unreferenced media are kept, at least an hour
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationsConfig {
+    pub notifier: String,  // "log" or "file"
+    pub file_path: String,  // only used by the file notifier
+    pub cart_reminder_idle_hours: i64,  // how long a cart sits before a reminder
+    pub cart_reminder_cooldown_days: i64,  // minimum time between reminders to a customer
+}
+
+/// Everything wrong with the configuration, reported at once
+#[derive(Debug, Default)]
+pub struct ConfigError {
+    pub missing: Vec<String>,
+    pub invalid: Vec<String>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration")?;
+        if !self.missing.is_empty() {
+            write!(f, "; missing: {}", self.missing.join(", "))?;
+        }
+        if !self.invalid.is_empty() {
+            write!(f, "; invalid: {}", self.invalid.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Layout of the optional config file, every value can be left out
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    server: FileServer,
+    database: FileDatabase,
+    auth: FileAuth,
+    storage: FileStorage,
+    links: FileLinks,
+    cors: FileCors,
+    limits: FileLimits,
+    payments: FilePayments,
+    orders: FileOrders,
+    returns: FileReturns,
+    media: FileMedia,
+    notifications: FileNotifications,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileServer {
+    host: Option<String>,
+    port: Option<u16>,
+    tls_cert: Option<String>,
+    tls_key: Option<String>,
+    shutdown_timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileDatabase {
+    url: Option<String>,
+    name: Option<String>,
+    collections: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileAuth {
+    jwt_secret: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileStorage {
+    backend: Option<String>,
+    s3_bucket: Option<String>,
+    aws_region: Option<String>,
+    local_dir: Option<String>,
+    local_public_url: Option<String>,
+    local_upload_url: Option<String>,
+    local_signing_key: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileLinks {
+    order_lookup_url: Option<String>,
+    wishlist_share_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileCors {
+    allowed_origins: Option<Vec<String>>,
+    allowed_methods: Option<Vec<String>>,
+    allowed_headers: Option<Vec<String>>,
+    allow_credentials: Option<bool>,
+    max_age_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileLimits {
+    public: Option<usize>,
+    shopping: Option<usize>,
+    uploads: Option<usize>,
+    customer: Option<usize>,
+    admin: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FilePayments {
+    callback_url: Option<String>,
+    paystack: FilePaystack,
+    opay: FileOpay,
+    bank_transfer: FileBankTransfer,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FilePaystack {
+    secret_key: Option<String>,
+    base_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileOpay {
+    merchant_id: Option<String>,
+    public_key: Option<String>,
+    secret_key: Option<String>,
+    base_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileBankTransfer {
+    bank_name: Option<String>,
+    account_name: Option<String>,
+    account_number: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileOrders {
+    offline_payment_deadline_hours: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileReturns {
+    window_days: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileMedia {
+    orphan_grace_hours: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileNotifications {
+    notifier: Option<String>,
+    file_path: Option<String>,
+    cart_reminder_idle_hours: Option<i64>,
+    cart_reminder_cooldown_days: Option<i64>,
+}
+
+impl Config {
+    // Load and validate the configuration, listing every missing or invalid value
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut errors = ConfigError::default();
+
+        let path = env::var("CONFIG_FILE").ok();
+        let file = match path.as_deref() {
+            Some(path) => read_file(path, true, &mut errors),
+            None => read_file(DEFAULT_CONFIG_FILE, false, &mut errors),
+        };
+
+        let mut values = Values { errors };
+        let config = values.build(file);
+
+        let errors = values.errors;
+        if errors.missing.is_empty() && errors.invalid.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn listen_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.server.host, self.server.port)
+    }
+}
+
+impl DatabaseConfig {
+    // Name of the collection behind a key like MONGO_PRODUCTS_COLLECTION
+    pub fn collection_name(&self, key: &str) -> &str {
+        self.collections.get(key).map(String::as_str).unwrap_or("default")
+    }
+}
+
+fn read_file(path: &str, required: bool, errors: &mut ConfigError) -> FileConfig {
+    if !required && !Path::new(path).exists() {
+        return FileConfig::default();
+    }
+
+    let parsed = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| toml::from_str(&contents).map_err(|e| e.to_string()));
+
+    parsed.unwrap_or_else(|e| {
+        errors.invalid.push(format!("config file {}: {}", path, e.trim()));
+        FileConfig::default()
+    })
+}
+
+// Resolves each value from the environment, then the file, then a default,
+// collecting what is missing or malformed
+struct Values {
+    errors: ConfigError,
+}
+
+impl Values {
+    fn build(&mut self, file: FileConfig) -> Config {
+        let tls = match (
+            self.lookup("TLS_CERT_PATH", file.server.tls_cert),
+            self.lookup("TLS_KEY_PATH", file.server.tls_key),
+        ) {
+            (Some(cert), Some(key)) => Some(TlsConfig {
+                cert_path: PathBuf::from(cert),
+                key_path: PathBuf::from(key),
+            }),
+            (None, None) => None,
+            (Some(_), None) => {
+                self.errors.missing.push("TLS_KEY_PATH".to_string());
+                None
+            }
+            (None, Some(_)) => {
+                self.errors.missing.push("TLS_CERT_PATH".to_string());
+                None
+            }
+        };
+        for path in tls.iter().flat_map(|tls| [&tls.cert_path, &tls.key_path]) {
+            if !path.is_file() {
+                self.errors.invalid.push(format!("TLS file {} not found", path.display()));
+            }
+        }
+
+        let server = ServerConfig {
+            host: self.parsed("HOST", file.server.host, IpAddr::from([0, 0, 0, 0])),
+            port: self.parsed("PORT", as_string(file.server.port), 3000),
+            tls,
+            shutdown_timeout: Duration::from_secs(self.parsed(
+                "SHUTDOWN_TIMEOUT_SECS",
+                as_string(file.server.shutdown_timeout_secs),
+                30,
+            )),
+        };
+
+        let collections = COLLECTIONS
+            .iter()
+            .map(|(key, default)| {
+                let name = env::var(key)
+                    .ok()
+                    .or_else(|| file.database.collections.get(*default).cloned())
+                    .unwrap_or_else(|| default.to_string());
+                (*key, name)
+            })
+            .collect();
+        for name in file.database.collections.keys() {
+            if !COLLECTIONS.iter().any(|(_, default)| default == name) {
+                self.errors.invalid.push(format!("unknown collection {}", name));
+            }
+        }
+
+        let database = DatabaseConfig {
+            url: self.required("DATABASE_URL", file.database.url),
+            name: self.or_default("MONGO_DATABASE", file.database.name, "ecommerce_db"),
+            collections,
+        };
+
+        let auth = AuthConfig {
+            jwt_secret: self.required("JWT_SECRET", file.auth.jwt_secret),
+        };
+
+        let backend = self.or_default("STORAGE_BACKEND", file.storage.backend, "s3");
+        let s3_bucket = if backend == "s3" {
+            self.required("AWS_S3_BUCKET_NAME", file.storage.s3_bucket)
+        } else {
+            String::new()
+        };
+        if !STORAGE_BACKENDS.contains(&backend.as_str()) {
+            self.errors.invalid.push(format!(
+                "STORAGE_BACKEND must be one of {}, got {}",
+                STORAGE_BACKENDS.join(", "),
+                backend
+            ));
+        }
+        let storage = StorageConfig {
+            s3_bucket,
+            aws_region: self.or_default("AWS_REGION", file.storage.aws_region, "us-east-1"),
+            local_dir: self.or_default("LOCAL_STORAGE_DIR", file.storage.local_dir, "uploads"),
+            local_public_url: self.or_default(
+                "LOCAL_STORAGE_PUBLIC_URL",
+                file.storage.local_public_url,
+                "/uploads",
+            ),
+            local_upload_url: self.or_default(
+                "LOCAL_STORAGE_UPLOAD_URL",
+                file.storage.local_upload_url,
+                "/api/upload/direct",
+            ),
+            // Signed upload URLs fall back to the JWT secret
+            local_signing_key: self.or_default(
+                "LOCAL_STORAGE_SIGNING_KEY",
+                file.storage.local_signing_key,
+                &auth.jwt_secret,
+            ),
+            backend,
+        };
+
+        let links = LinksConfig {
+            order_lookup_url: self.or_default(
+                "ORDER_LOOKUP_URL",
+                file.links.order_lookup_url,
+                "/api/guest/orders/lookup",
+            ),
+            wishlist_share_url: self.or_default(
+                "WISHLIST_SHARE_URL",
+                file.links.wishlist_share_url,
+                "/api/wishlists/shared",
+            ),
+        };
+
+        let cors = self.cors(file.cors);
+
+        let limits = BodyLimits {
+            public: self.parsed("BODY_LIMIT_PUBLIC", as_string(file.limits.public), MB),
+            shopping: self.parsed("BODY_LIMIT_SHOPPING", as_string(file.limits.shopping), MB),
+            uploads: self.parsed("BODY_LIMIT_UPLOADS", as_string(file.limits.uploads), 10 * MB),
+            customer: self.parsed("BODY_LIMIT_CUSTOMER", as_string(file.limits.customer), 10 * MB),
+            admin: self.parsed("BODY_LIMIT_ADMIN", as_string(file.limits.admin), 2 * MB),
+        };
+
+        let payments = self.payments(file.payments);
+
+        let orders = OrdersConfig {
+            offline_payment_deadline_hours: self.at_least(
+                "OFFLINE_PAYMENT_DEADLINE_HOURS",
+                as_string(file.orders.offline_payment_deadline_hours),
+                48,
+                1,
+            ),
+        };
+
+        let returns = ReturnsConfig {
+            window_days: self.at_least("RETURN_WINDOW_DAYS", as_string(file.returns.window_days), 14, 0),
+        };
+
+        let media = MediaConfig {
+            orphan_grace_hours: self.at_least(
+                "MEDIA_ORPHAN_GRACE_HOURS",
+                as_string(file.media.orphan_grace_hours),
+                48,
+                1,
+            ),
+        };
+
+        let notifications = self.notifications(file.notifications);
+
+        Config {
+            server,
+            database,
+            auth,
+            storage,
+            links,
+            cors,
+            limits,
+            payments,
+            orders,
+            returns,
+            media,
+            notifications,
+        }
+    }
+
+    fn payments(&mut self, file: FilePayments) -> PaymentsConfig {
+        let paystack = self
+            .group([("PAYSTACK_SECRET_KEY", file.paystack.secret_key)])
+            .map(|[secret_key]| PaystackConfig {
+                secret_key,
+                base_url: self.or_default(
+                    "PAYSTACK_BASE_URL",
+                    file.paystack.base_url,
+                    paystack::DEFAULT_BASE_URL,
+                ),
+            });
+
+        let opay = self
+            .group([
+                ("OPAY_MERCHANT_ID", file.opay.merchant_id),
+                ("OPAY_PUBLIC_KEY", file.opay.public_key),
+                ("OPAY_SECRET_KEY", file.opay.secret_key),
+            ])
+            .map(|[merchant_id, public_key, secret_key]| OpayConfig {
+                merchant_id,
+                public_key,
+                secret_key,
+                base_url: self.or_default(
+                    "OPAY_BASE_URL",
+                    file.opay.base_url,
+                    opay::DEFAULT_BASE_URL,
+                ),
+            });
+
+        let bank_transfer = self
+            .group([
+                ("OFFLINE_BANK_NAME", file.bank_transfer.bank_name),
+                ("OFFLINE_BANK_ACCOUNT_NAME", file.bank_transfer.account_name),
+                ("OFFLINE_BANK_ACCOUNT_NUMBER", file.bank_transfer.account_number),
+            ])
+            .map(|[bank_name, account_name, account_number]| BankTransferConfig {
+                bank_name,
+                account_name,
+                account_number,
+            });
+
+        PaymentsConfig {
+            callback_url: self.lookup("PAYMENT_CALLBACK_URL", file.callback_url),
+            paystack,
+            opay,
+            bank_transfer,
+        }
+    }
+
+    fn notifications(&mut self, file: FileNotifications) -> NotificationsConfig {
+        let notifier = self.or_default("NOTIFIER", file.notifier, "log");
+        if !NOTIFIERS.contains(&notifier.as_str()) {
+            self.errors.invalid.push(format!(
+                "NOTIFIER must be one of {}, got {}",
+                NOTIFIERS.join(", "),
+                notifier
+            ));
+        }
+
+        NotificationsConfig {
+            notifier,
+            file_path: self.or_default("NOTIFICATIONS_FILE", file.file_path, "notifications.log"),
+            cart_reminder_idle_hours: self.at_least(
+                "ABANDONED_CART_IDLE_HOURS",
+                as_string(file.cart_reminder_idle_hours),
+                24,
+                1,
+            ),
+            cart_reminder_cooldown_days: self.at_least(
+                "ABANDONED_CART_REMINDER_COOLDOWN_DAYS",
+                as_string(file.cart_reminder_cooldown_days),
+                7,
+                0,
+            ),
+        }
+    }
+
+    fn cors(&mut self, file: FileCors) -> CorsConfig {
+        let origins = self.list("CORS_ALLOWED_ORIGINS", file.allowed_origins, &[]);
+        let allow_any_origin = origins == ["*"];
+        let allowed_origins = if allow_any_origin {
+            Vec::new()
+        } else {
+            self.each("CORS_ALLOWED_ORIGINS", origins, |origin| HeaderValue::from_str(origin).ok())
+        };
+
+        let methods = self.list(
+            "CORS_ALLOWED_METHODS",
+            file.allowed_methods,
+            &["GET", "POST", "PUT", "DELETE"],
+        );
+        let allowed_methods = self.each("CORS_ALLOWED_METHODS", methods, |method| {
+            Method::from_bytes(method.to_uppercase().as_bytes()).ok()
+        });
+
+        let headers = self.list(
+            "CORS_ALLOWED_HEADERS",
+            file.allowed_headers,
+            &["authorization", "content-type", "x-guest-token"],
+        );
+        let allowed_headers =
+            self.each("CORS_ALLOWED_HEADERS", headers, |header| HeaderName::try_from(header).ok());
+
+        let allow_credentials = self.parsed(
+            "CORS_ALLOW_CREDENTIALS",
+            as_string(file.allow_credentials),
+            false,
+        );
+        // Browsers refuse credentials with a wildcard origin
+        if allow_credentials && allow_any_origin {
+            self.errors
+                .invalid
+                .push("CORS_ALLOW_CREDENTIALS can't be used with any origin (*)".to_string());
+        }
+
+        CorsConfig {
+            allowed_origins,
+            allow_any_origin,
+            allowed_methods,
+            allowed_headers,
+            allow_credentials,
+            max_age: Duration::from_secs(self.parsed(
+                "CORS_MAX_AGE_SECS",
+                as_string(file.max_age_secs),
+                3600,
+            )),
+        }
+    }
+
+    fn lookup(&self, key: &str, file: Option<String>) -> Option<String> {
+        env::var(key)
+            .ok()
+            .or(file)
+            .filter(|value| !value.trim().is_empty())
+    }
+
+    fn required(&mut self, key: &str, file: Option<String>) -> String {
+        self.lookup(key, file).unwrap_or_else(|| {
+            self.errors.missing.push(key.to_string());
+            String::new()
+        })
+    }
+
+    fn or_default(&self, key: &str, file: Option<String>, default: &str) -> String {
+        self.lookup(key, file).unwrap_or_else(|| default.to_string())
+    }
+
+    // Comma separated in the environment, an array in the file
+    fn list(&self, key: &str, file: Option<Vec<String>>, default: &[&str]) -> Vec<String> {
+        let values = match env::var(key) {
+            Ok(value) => value.split(',').map(str::to_string).collect(),
+            Err(_) => file.unwrap_or_else(|| default.iter().map(|v| v.to_string()).collect()),
+        };
+
+        values
+            .into_iter()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+
+    // Parse every value of a list, "*" is only allowed as the whole list of origins
+    fn each<T>(
+        &mut self,
+        key: &str,
+        values: Vec<String>,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> Vec<T> {
+        values
+            .into_iter()
+            .filter_map(|value| {
+                let parsed = if value == "*" { None } else { parse(&value) };
+                if parsed.is_none() {
+                    self.errors.invalid.push(format!("{} can't contain {}", key, value));
+                }
+                parsed
+            })
+            .collect()
+    }
+
+    // Values that only make sense together: all of them, or none and the feature is off
+    fn group<const N: usize>(
+        &mut self,
+        keys: [(&str, Option<String>); N],
+    ) -> Option<[String; N]> {
+        let values = keys.map(|(key, file)| (key, self.lookup(key, file)));
+        if values.iter().all(|(_, value)| value.is_none()) {
+            return None;
+        }
+
+        for (key, value) in &values {
+            if value.is_none() {
+                self.errors.missing.push(key.to_string());
+            }
+        }
+        let values = values.map(|(_, value)| value);
+        values.iter().all(Option::is_some).then(|| values.map(Option::unwrap_or_default))
+    }
+
+    fn at_least(&mut self, key: &str, file: Option<String>, default: i64, min: i64) -> i64 {
+        let value = self.parsed(key, file, default);
+        if value < min {
+            self.errors.invalid.push(format!("{} must be at least {}, got {}", key, min, value));
+            return default;
+        }
+        value
+    }
+
+    fn parsed<T: std::str::FromStr>(&mut self, key: &str, file: Option<String>, default: T) -> T {
+        match self.lookup(key, file) {
+            None => default,
+            Some(value) => value.parse().unwrap_or_else(|_| {
+                self.errors.invalid.push(format!("{} can't be {}", key, value));
+                default
+            }),
+        }
+    }
+}
+
+fn as_string<T: ToString>(value: Option<T>) -> Option<String> {
+    value.map(|value| value.to_string())
+}
@@ -1 +1,2 @@
+pub mod app;
 pub mod database;
\ No newline at end of file
@@ -1,23 +1,38 @@
+use std::sync::Arc;
+
 use mongodb::{Collection, Database};
+use tokio_util::sync::CancellationToken;
 
+use crate::config::app::Config;
 use crate::config::database::MongoDB;
+use crate::services::storage::ObjectStorage;
 
 
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct AppState {
-    pub db: Database
+    pub db: Database,
+    pub config: Arc<Config>,
+    pub storage: Arc<dyn ObjectStorage>,
+    pub shutdown: CancellationToken,  // cancelled once the server starts shutting down
 }
 
 impl AppState {
-    pub async fn init() -> Result<Self, mongodb::error::Error> {
-        let mongodb = MongoDB::init().await?;
+    pub async fn init(
+        config: Config,
+        storage: Box<dyn ObjectStorage>,
+    ) -> Result<Self, mongodb::error::Error> {
+        let mongodb = MongoDB::init(&config.database).await?;
         Ok(AppState {
-            db: mongodb.db
+            db: mongodb.db,
+            config: Arc::new(config),
+            storage: storage.into(),
+            shutdown: CancellationToken::new(),
         })
     }
 
-    pub fn collection<T: Send + Sync>(&self, collection_name: &str) -> Collection<T> {
-         self.db.collection(collection_name)
+    // Collection behind a key like MONGO_PRODUCTS_COLLECTION, named as configured
+    pub fn collection<T: Send + Sync>(&self, key: &str) -> Collection<T> {
+         self.db.collection(self.config.database.collection_name(key))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,32 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::AdminUser;
+use crate::models::cart_reminder::RecoveryStatsQuery;
+use crate::services::abandoned_cart::AbandonedCartService;
+use crate::utils::error::{AppError, Result};
+use crate::utils::response::ApiResponse;
+use axum::extract::{Query, State};
+use std::sync::Arc;
+
+// GET /admin/abandoned-carts/stats?days=30 (requires admin)
+pub async fn recovery_stats(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RecoveryStatsQuery>,
+) -> Result<impl IntoResponse> {
+    let days = query.days.unwrap_or(30);
+    if !(1..=365).contains(&days) {
+        return Err(AppError::ValidationError(
+            "Days must be between 1 and 365".to_string(),
+        ));
+    }
+
+    let reminders = state.collection("MONGO_CART_REMINDERS_COLLECTION");
+    let stats = AbandonedCartService::recovery_stats(&reminders, days).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": stats
+    }));
+
+    Ok(response)
+}
@@ -0,0 +1,115 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::AuthUser;
+use crate::models::address::SaveAddressRequest;
+use crate::services::address::AddressService;
+use crate::utils::error::Result;
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+// POST /me/addresses (requires authentication)
+pub async fn create_address(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<SaveAddressRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ADDRESSES_COLLECTION");
+
+    let address = AddressService::create_address(&collection, &auth.claims.sub, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": address
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /me/addresses (requires authentication)
+pub async fn list_addresses(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ADDRESSES_COLLECTION");
+
+    let addresses = AddressService::get_addresses(&collection, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": addresses.len(),
+        "data": addresses
+    }));
+
+    Ok(response)
+}
+
+// GET /me/addresses/:id (requires authentication)
+pub async fn get_address(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ADDRESSES_COLLECTION");
+
+    let address = AddressService::get_address(&collection, &id, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": address
+    }));
+
+    Ok(response)
+}
+
+// PUT /me/addresses/:id (requires authentication)
+pub async fn update_address(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<SaveAddressRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ADDRESSES_COLLECTION");
+
+    let address =
+        AddressService::update_address(&collection, &id, &auth.claims.sub, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": address
+    }));
+
+    Ok(response)
+}
+
+// PUT /me/addresses/:id/default (requires authentication)
+pub async fn set_default_address(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ADDRESSES_COLLECTION");
+
+    let address = AddressService::set_default(&collection, &id, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": address
+    }));
+
+    Ok(response)
+}
+
+// DELETE /me/addresses/:id (requires authentication)
+pub async fn delete_address(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ADDRESSES_COLLECTION");
+
+    AddressService::delete_address(&collection, &id, &auth.claims.sub).await?;
+
+    let response = ApiResponse::with_message(serde_json::json!({}), "Address deleted successfully");
+
+    Ok(response)
+}
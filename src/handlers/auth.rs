@@ -1,32 +1,35 @@
 use axum::response::IntoResponse;
-use crate::config::database::MongoDB;
 use crate::db::AppState;
+use crate::middleware::auth::GuestToken;
+use crate::models::cart::CartMergeReport;
 use crate::models::user::{AuthResponse, LoginRequest, RegisterRequest};
 use crate::services::auth::AuthService;
+use crate::services::cart::CartService;
+use crate::services::coupon::CouponService;
 use crate::utils::error::Result;
 use crate::utils::response::ApiResponse;
 use axum::{extract::State, http::StatusCode, Json};
-use std::env;
 use std::sync::Arc;
 
 // POST /auth/register
 pub async fn register(
+    guest: GuestToken,
     State(state): State<Arc<AppState>>,
     Json(req): Json<RegisterRequest>,
 ) -> Result<impl IntoResponse> {
-    let collection_name = MongoDB::get_collection_name("MONGO_USERS_COLLECTION");
-    let collection = state.collection(&collection_name);
+    let collection = state.collection("MONGO_USERS_COLLECTION");
 
     let user = AuthService::register(&collection, req).await?;
-    
-    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-    let token = AuthService::generate_jwt(&user, &jwt_secret)?;
+
+    let token = AuthService::generate_jwt(&user, &state.config.auth.jwt_secret)?;
     
     let user_response = AuthService::user_to_response(&user);
+    let cart_merge = merge_guest_cart(&state, &guest, &user_response.id).await?;
 
     let response = ApiResponse::success(AuthResponse {
         token,
         user: user_response,
+        cart_merge,
     });
 
     Ok((StatusCode::CREATED, response))
@@ -34,23 +37,48 @@ pub async fn register(
 
 // POST /auth/login
 pub async fn login(
+    guest: GuestToken,
     State(state): State<Arc<AppState>>,
     Json(req): Json<LoginRequest>,
 ) -> Result<impl IntoResponse> {
-    let collection_name = MongoDB::get_collection_name("MONGO_USERS_COLLECTION");
-    let collection = state.collection(&collection_name);
+    let collection = state.collection("MONGO_USERS_COLLECTION");
 
     let user = AuthService::login(&collection, req).await?;
     
-    let jwt_secret = env::var("JWT_SECRET").expect("JWT_SECRET must be set");
-    let token = AuthService::generate_jwt(&user, &jwt_secret)?;
+    let token = AuthService::generate_jwt(&user, &state.config.auth.jwt_secret)?;
     
     let user_response = AuthService::user_to_response(&user);
+    let cart_merge = merge_guest_cart(&state, &guest, &user_response.id).await?;
 
     let response = ApiResponse::success(AuthResponse {
         token,
         user: user_response,
+        cart_merge,
     });
 
     Ok(response)
 }
+
+// Move the cart a client built as a guest into the account it signed in to
+async fn merge_guest_cart(
+    state: &AppState,
+    guest: &GuestToken,
+    user_id: &str,
+) -> Result<Option<CartMergeReport>> {
+    let Some(guest_key) = guest.cart_key() else {
+        return Ok(None);
+    };
+
+    let cart = state.collection("MONGO_CART_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let applied = state.collection("MONGO_CART_COUPONS_COLLECTION");
+
+    let lines = CartService::merge_carts(&cart, &products, &guest_key, user_id).await?;
+    let coupon_code = CouponService::move_applied(&applied, &guest_key, user_id).await?;
+
+    if lines.is_empty() && coupon_code.is_none() {
+        return Ok(None);
+    }
+
+    Ok(Some(CartMergeReport { lines, coupon_code }))
+}
@@ -0,0 +1,191 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::CartOwner;
+use crate::models::cart::{AddToCartRequest, CartResponse, UpdateCartItemRequest};
+use crate::models::coupon::ApplyCouponRequest;
+use crate::services::cart::CartService;
+use crate::services::coupon::{CouponService, DiscountableItem};
+use crate::services::promotion::PromotionService;
+use crate::services::tax::TaxService;
+use crate::utils::error::Result;
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use std::sync::Arc;
+
+// GET /cart (requires authentication or a guest token)
+pub async fn get_cart(
+    owner: CartOwner,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_CART_COLLECTION");
+
+    let cart = CartService::get_cart(&collection, &owner.key()).await?;
+    let cart = with_prices(&state, &owner.key(), cart).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": cart
+    }));
+
+    Ok(response)
+}
+
+// POST /cart (requires authentication or a guest token)
+pub async fn add_to_cart(
+    owner: CartOwner,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<AddToCartRequest>,
+) -> Result<impl IntoResponse> {
+    let cart = state.collection("MONGO_CART_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+    let active = PromotionService::active_promotions(&promotions).await?;
+
+    let cart = CartService::add_to_cart(&cart, &products, &active, &owner.key(), req).await?;
+    let cart = with_prices(&state, &owner.key(), cart).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": cart
+    }));
+
+    Ok(response)
+}
+
+// PUT /cart/:product_id (requires authentication or a guest token)
+pub async fn update_cart_item(
+    owner: CartOwner,
+    State(state): State<Arc<AppState>>,
+    Path(product_id): Path<String>,
+    Json(req): Json<UpdateCartItemRequest>,
+) -> Result<impl IntoResponse> {
+    let cart = state.collection("MONGO_CART_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+
+    let cart = CartService::update_quantity(
+        &cart,
+        &products,
+        &owner.key(),
+        &product_id,
+        req.quantity,
+    )
+    .await?;
+    let cart = with_prices(&state, &owner.key(), cart).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": cart
+    }));
+
+    Ok(response)
+}
+
+// DELETE /cart/:product_id (requires authentication or a guest token)
+pub async fn remove_cart_item(
+    owner: CartOwner,
+    State(state): State<Arc<AppState>>,
+    Path(product_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_CART_COLLECTION");
+
+    let cart = CartService::remove_item(&collection, &owner.key(), &product_id).await?;
+    let cart = with_prices(&state, &owner.key(), cart).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": cart
+    }));
+
+    Ok(response)
+}
+
+// POST /cart/coupon (requires authentication or a guest token)
+pub async fn apply_coupon(
+    owner: CartOwner,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ApplyCouponRequest>,
+) -> Result<impl IntoResponse> {
+    let cart = state.collection("MONGO_CART_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let coupons = state.collection("MONGO_COUPONS_COLLECTION");
+    let redemptions = state.collection("MONGO_COUPON_REDEMPTIONS_COLLECTION");
+    let applied = state.collection("MONGO_CART_COUPONS_COLLECTION");
+
+    // Reject codes that don't work on the current cart right away
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+    let mut cart = CartService::get_cart(&cart, &owner.key()).await?;
+    PromotionService::price_cart(&promotions, &products, &mut cart).await?;
+    let items: Vec<DiscountableItem> = cart
+        .items
+        .iter()
+        .map(|i| DiscountableItem {
+            product_id: i.product_id.clone(),
+            price: i.product_price,
+            quantity: i.quantity,
+        })
+        .collect();
+    CouponService::evaluate(&coupons, &redemptions, &products, &req.code, &owner.key(), &items)
+        .await?;
+
+    CouponService::apply_to_cart(&applied, &owner.key(), &req.code).await?;
+    let cart = with_prices(&state, &owner.key(), cart).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": cart
+    }));
+
+    Ok(response)
+}
+
+// DELETE /cart/coupon (requires authentication or a guest token)
+pub async fn remove_coupon(
+    owner: CartOwner,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let cart = state.collection("MONGO_CART_COLLECTION");
+    let applied = state.collection("MONGO_CART_COUPONS_COLLECTION");
+
+    CouponService::remove_from_cart(&applied, &owner.key()).await?;
+    let cart = CartService::get_cart(&cart, &owner.key()).await?;
+    let cart = with_prices(&state, &owner.key(), cart).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": cart
+    }));
+
+    Ok(response)
+}
+
+// Check the cart against the current products, price it at current promotion prices, then
+// add the discount of the applied coupon and the tax
+async fn with_prices(
+    state: &AppState,
+    user_id: &str,
+    mut cart: CartResponse,
+) -> Result<CartResponse> {
+    let cart_items = state.collection("MONGO_CART_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let coupons = state.collection("MONGO_COUPONS_COLLECTION");
+    let redemptions = state.collection("MONGO_COUPON_REDEMPTIONS_COLLECTION");
+    let applied = state.collection("MONGO_CART_COUPONS_COLLECTION");
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+    let tax_rules = state.collection("MONGO_TAX_RULES_COLLECTION");
+    let addresses = state.collection("MONGO_ADDRESSES_COLLECTION");
+
+    let active = PromotionService::active_promotions(&promotions).await?;
+    let warnings = CartService::revalidate(&cart_items, &products, &active, user_id).await?;
+    for item in cart.items.iter_mut() {
+        item.issue = warnings
+            .iter()
+            .find(|w| w.product_id == item.product_id)
+            .map(|w| w.issue.clone());
+    }
+    cart.warnings = warnings;
+
+    PromotionService::price_cart(&promotions, &products, &mut cart).await?;
+    CouponService::price_cart(&coupons, &redemptions, &applied, &products, user_id, &mut cart)
+        .await?;
+    TaxService::price_cart(&tax_rules, &products, &addresses, user_id, &mut cart).await?;
+
+    Ok(cart)
+}
@@ -0,0 +1,97 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::AdminUser;
+use crate::models::coupon::{CreateCouponRequest, UpdateCouponRequest};
+use crate::services::coupon::CouponService;
+use crate::utils::error::Result;
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+// POST /admin/coupons (requires admin)
+pub async fn create_coupon(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateCouponRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_COUPONS_COLLECTION");
+
+    let coupon = CouponService::create_coupon(&collection, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": coupon
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /admin/coupons (requires admin)
+pub async fn list_coupons(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_COUPONS_COLLECTION");
+
+    let coupons = CouponService::get_coupons(&collection).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": coupons.len(),
+        "data": coupons
+    }));
+
+    Ok(response)
+}
+
+// GET /admin/coupons/:code (requires admin)
+pub async fn get_coupon(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_COUPONS_COLLECTION");
+
+    let coupon = CouponService::get_coupon(&collection, &code).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": coupon
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/coupons/:code (requires admin)
+pub async fn update_coupon(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    Json(req): Json<UpdateCouponRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_COUPONS_COLLECTION");
+
+    let coupon = CouponService::update_coupon(&collection, &code, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": coupon
+    }));
+
+    Ok(response)
+}
+
+// DELETE /admin/coupons/:code (requires admin)
+pub async fn delete_coupon(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_COUPONS_COLLECTION");
+
+    CouponService::delete_coupon(&collection, &code).await?;
+
+    let response = ApiResponse::with_message(serde_json::json!({}), "Coupon deleted successfully");
+
+    Ok(response)
+}
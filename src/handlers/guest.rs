@@ -0,0 +1,115 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::handlers::order::{payment_instructions, store_payment_proof};
+use crate::middleware::auth::CartOwner;
+use crate::models::order::{CreateOrderRequest, GuestOrderRequest, OrderLookupQuery};
+use crate::services::order::{CheckoutCollections, OrderOwner, OrderService};
+use crate::services::payment;
+use crate::utils::error::{AppError, Result};
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Multipart, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+// POST /guest/session
+pub async fn create_session() -> Result<impl IntoResponse> {
+    // Sent back in the X-Guest-Token header to use the cart without an account
+    let response = ApiResponse::success(serde_json::json!({
+        "data": { "guest_token": Uuid::new_v4().to_string() }
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// POST /guest/orders (requires a guest token)
+pub async fn create_order(
+    owner: CartOwner,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<GuestOrderRequest>,
+) -> Result<impl IntoResponse> {
+    if let CartOwner::User(_) = owner {
+        return Err(AppError::ValidationError(
+            "Signed in customers check out with POST /orders".to_string(),
+        ));
+    }
+
+    let buyer = OrderService::guest_buyer(owner.key(), &req.email, &req.phone)?;
+    let collections = CheckoutCollections::from_state(&state);
+    let provider = payment::provider_for(&state.config.payments, &req.payment_method)?;
+
+    // The courier needs a number to call, default to the contact phone
+    let mut shipping_address = req.shipping_address;
+    if shipping_address.phone.as_deref().is_none_or(|p| p.trim().is_empty()) {
+        shipping_address.phone = buyer.phone.clone();
+    }
+
+    let order_request = CreateOrderRequest {
+        payment_method: req.payment_method,
+        address_id: None,
+        shipping_address: Some(shipping_address),
+        coupon_code: req.coupon_code,
+    };
+
+    let (order, payment) =
+        OrderService::create_order(&collections, &state.config, provider.as_deref(), &buyer, order_request)
+            .await?;
+
+    let token = OrderService::lookup_token(&order, &state.config.auth.jwt_secret)?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": order,
+        "payment": payment,
+        "payment_instructions": payment_instructions(&state.config.payments, &order),
+        "lookup_url": format!("{}?token={}", state.config.links.order_lookup_url, token)
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /guest/orders/lookup?token=
+pub async fn lookup_order(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<OrderLookupQuery>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ORDERS_COLLECTION");
+
+    let order = OrderService::get_order_by_lookup_token(
+        &collection,
+        &query.token,
+        &state.config.auth.jwt_secret,
+    )
+    .await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": order,
+        "payment_instructions": payment_instructions(&state.config.payments, &order)
+    }));
+
+    Ok(response)
+}
+
+// POST /guest/orders/payment-proof?token= (authorized by the order link, multipart)
+pub async fn upload_payment_proof(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<OrderLookupQuery>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse> {
+    let (id, email) =
+        OrderService::verify_lookup_token(&query.token, &state.config.auth.jwt_secret)?;
+    let owner = OrderOwner::Guest(email);
+
+    // Guests have no user id, the media library keeps the order as the uploader
+    let uploaded_by = format!("guest:{}", id);
+    let order = store_payment_proof(&state, &id, &owner, &uploaded_by, &mut multipart).await?;
+
+    let response = ApiResponse::with_message(
+        serde_json::json!({ "data": order }),
+        "Receipt received, your payment will be confirmed shortly",
+    );
+
+    Ok((StatusCode::CREATED, response))
+}
@@ -0,0 +1,72 @@
+use crate::config::database::MongoDB;
+use crate::db::AppState;
+use crate::utils::response::ApiResponse;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long each dependency gets to answer a readiness probe
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+// GET /health/live
+pub async fn live() -> impl IntoResponse {
+    ApiResponse::success(json!({ "status": "ok" }))
+}
+
+// GET /health/ready, fails while a dependency is down or the server is shutting down
+pub async fn ready(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    if state.shutdown.is_cancelled() {
+        return not_ready("Shutting down", None);
+    }
+
+    let (database, storage) = tokio::join!(
+        check("database", async { MongoDB::ping(&state.db).await.map_err(|e| e.to_string()) }),
+        check("storage", async { state.storage.check().await.map_err(|e| e.to_string()) }),
+    );
+
+    let status = |ok: bool| if ok { "ok" } else { "unavailable" };
+    let checks = json!({ "database": status(database), "storage": status(storage) });
+    if database && storage {
+        (StatusCode::OK, ApiResponse::success(json!({ "status": "ok", "checks": checks })))
+    } else {
+        not_ready("Not ready", Some(checks))
+    }
+}
+
+// GET /version
+pub async fn version() -> impl IntoResponse {
+    ApiResponse::success(json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_hash": env!("GIT_HASH"),
+    }))
+}
+
+// Whether a dependency answered in time
+async fn check(name: &str, probe: impl Future<Output = Result<(), String>>) -> bool {
+    match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok(Ok(())) => true,
+        Ok(Err(e)) => {
+            tracing::warn!("Readiness check {} failed: {}", name, e);
+            false
+        }
+        Err(_) => {
+            tracing::warn!("Readiness check {} timed out", name);
+            false
+        }
+    }
+}
+
+fn not_ready(message: &str, checks: Option<Value>) -> (StatusCode, ApiResponse<Value>) {
+    let response = ApiResponse {
+        status: "error".to_string(),
+        message: Some(message.to_string()),
+        data: checks.map(|checks| json!({ "checks": checks })),
+    };
+
+    (StatusCode::SERVICE_UNAVAILABLE, response)
+}
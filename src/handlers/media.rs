@@ -0,0 +1,27 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::AdminUser;
+use crate::models::media::MediaFilter;
+use crate::services::media::MediaService;
+use crate::utils::error::Result;
+use crate::utils::response::ApiResponse;
+use axum::extract::{Query, State};
+use std::sync::Arc;
+
+// GET /admin/media?unreferenced=true&owner_id= (requires admin)
+pub async fn list_media(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<MediaFilter>,
+) -> Result<impl IntoResponse> {
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
+
+    let items = MediaService::get_media(&media, filter).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": items.len(),
+        "data": items
+    }));
+
+    Ok(response)
+}
@@ -1,3 +1,19 @@
 pub mod product;
 pub mod auth;
 pub mod upload;
+pub mod cart;
+pub mod order;
+pub mod payment;
+pub mod returns;
+pub mod shipping;
+pub mod address;
+pub mod tax;
+pub mod coupon;
+pub mod promotion;
+pub mod guest;
+pub mod abandoned_cart;
+pub mod wishlist;
+pub mod product_alert;
+pub mod product_image;
+pub mod media;
+pub mod health;
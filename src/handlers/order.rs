@@ -0,0 +1,296 @@
+use axum::response::IntoResponse;
+use crate::config::app::PaymentsConfig;
+use crate::db::AppState;
+use crate::handlers::upload::read_image_files;
+use crate::middleware::auth::{AdminUser, AuthUser};
+use crate::models::media::MediaReference;
+use crate::models::order::{
+    ClaimOrderRequest, CreateOrderRequest, OrderFilter, OrderResponse, ReviewPaymentRequest,
+    UpdateOrderStatusRequest,
+};
+use crate::services::order::{Buyer, CheckoutCollections, OrderOwner, OrderService};
+use crate::services::image::ImageService;
+use crate::services::media::MediaService;
+use crate::services::payment;
+use crate::utils::error::{AppError, Result};
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+// POST /orders (requires authentication)
+pub async fn create_order(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateOrderRequest>,
+) -> Result<impl IntoResponse> {
+    let collections = CheckoutCollections::from_state(&state);
+    let provider = payment::provider_for(&state.config.payments, &req.payment_method)?;
+
+    let buyer = Buyer {
+        cart_key: auth.claims.sub.clone(),
+        user_id: Some(auth.claims.sub),
+        email: auth.claims.email,
+        phone: None,
+    };
+
+    let (order, payment) =
+        OrderService::create_order(&collections, &state.config, provider.as_deref(), &buyer, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": order,
+        "payment": payment,
+        "payment_instructions": payment_instructions(&state.config.payments, &order)
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /orders (requires authentication)
+pub async fn list_my_orders(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ORDERS_COLLECTION");
+
+    let orders = OrderService::get_user_orders(&collection, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": orders.len(),
+        "data": orders
+    }));
+
+    Ok(response)
+}
+
+// GET /orders/:id (requires authentication)
+pub async fn get_my_order(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ORDERS_COLLECTION");
+
+    let order = OrderService::get_user_order(&collection, &id, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": order,
+        "payment_instructions": payment_instructions(&state.config.payments, &order)
+    }));
+
+    Ok(response)
+}
+
+// GET /orders/:id/payment/verify (requires authentication)
+pub async fn verify_payment(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let orders = state.collection("MONGO_ORDERS_COLLECTION");
+
+    let order = OrderService::get_user_order(&orders, &id, &auth.claims.sub).await?;
+
+    let (Some(provider), Some(reference)) = (
+        payment::provider_for(&state.config.payments, &order.payment_method)?,
+        order.payment_reference.as_deref(),
+    ) else {
+        return Err(AppError::ValidationError(
+            "This order has no online payment to verify".to_string(),
+        ));
+    };
+
+    let verification = provider.verify(reference).await?;
+    let collections = CheckoutCollections::from_state(&state);
+    let order = OrderService::apply_payment_verification(&collections, &verification).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": order,
+        "payment_status": verification.status
+    }));
+
+    Ok(response)
+}
+
+// POST /orders/:id/payment-proof (requires authentication, multipart)
+pub async fn upload_payment_proof(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse> {
+    let owner = OrderOwner::User(auth.claims.sub.clone());
+    let order =
+        store_payment_proof(&state, &id, &owner, &auth.claims.sub, &mut multipart).await?;
+
+    let response = ApiResponse::with_message(
+        serde_json::json!({ "data": order }),
+        "Receipt received, your payment will be confirmed shortly",
+    );
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// POST /orders/claim (requires authentication)
+pub async fn claim_order(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ClaimOrderRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ORDERS_COLLECTION");
+
+    let order = OrderService::claim_guest_order(
+        &collection,
+        &req.token,
+        &state.config.auth.jwt_secret,
+        &auth.claims.sub,
+        &auth.claims.email,
+    )
+    .await?;
+
+    let response = ApiResponse::with_message(
+        serde_json::json!({ "data": order }),
+        "Order added to your account",
+    );
+
+    Ok(response)
+}
+
+// Store the single receipt image of a multipart upload and attach it to the order
+pub async fn store_payment_proof(
+    state: &AppState,
+    id: &str,
+    owner: &OrderOwner,
+    uploaded_by: &str,
+    multipart: &mut Multipart,
+) -> Result<OrderResponse> {
+    let collection = state.collection("MONGO_ORDERS_COLLECTION");
+
+    // Don't store receipts for orders that can no longer be paid
+    OrderService::ensure_awaiting_offline_payment(&collection, id, owner).await?;
+
+    let mut files = read_image_files(multipart).await?;
+    if files.len() != 1 {
+        return Err(AppError::ValidationError(
+            "Upload a single receipt image".to_string(),
+        ));
+    }
+    let (file_data, _) = files.remove(0);
+    let (file_data, content_type) = ImageService::sanitize(file_data).await?;
+
+    let url = state
+        .storage
+        .upload_image(file_data, &content_type, "payment-proofs")
+        .await?;
+
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
+    let reference = MediaReference::new("order", id);
+    MediaService::record(
+        &media,
+        uploaded_by,
+        &url,
+        vec![url.clone()],
+        Some(reference.clone()),
+    )
+    .await?;
+
+    let attached =
+        OrderService::attach_payment_proof(&collection, &media, id, owner, url.clone()).await;
+    if attached.is_err() {
+        // The order stopped accepting receipts meanwhile, let the sweeper delete this one
+        MediaService::release(&media, &[url], &reference).await?;
+    }
+    attached
+}
+
+// GET /admin/orders?payment_method=offline&payment_status=pending (requires admin)
+pub async fn list_orders(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<OrderFilter>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_ORDERS_COLLECTION");
+
+    let orders = OrderService::get_orders(&collection, filter).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": orders.len(),
+        "data": orders
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/orders/:id/status (requires admin)
+pub async fn update_order_status(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateOrderStatusRequest>,
+) -> Result<impl IntoResponse> {
+    let collections = CheckoutCollections::from_state(&state);
+    let order = OrderService::update_order_status(&collections, &id, &req.order_status).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": order
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/orders/:id/payment/confirm (requires admin)
+pub async fn confirm_payment(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<ReviewPaymentRequest>,
+) -> Result<impl IntoResponse> {
+    let collections = CheckoutCollections::from_state(&state);
+    let order = OrderService::confirm_offline_payment(&collections, &id, req.note).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": order
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/orders/:id/payment/reject (requires admin)
+pub async fn reject_payment(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<ReviewPaymentRequest>,
+) -> Result<impl IntoResponse> {
+    let collections = CheckoutCollections::from_state(&state);
+    let order = OrderService::reject_offline_payment(&collections, &id, req.note).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": order
+    }));
+
+    Ok(response)
+}
+
+// Bank details shown to customers who still have to pay by transfer
+pub fn payment_instructions(
+    config: &PaymentsConfig,
+    order: &OrderResponse,
+) -> Option<serde_json::Value> {
+    if order.payment_method != "offline" || order.payment_status != "pending" {
+        return None;
+    }
+    let bank = config.bank_transfer.as_ref()?;
+
+    Some(serde_json::json!({
+        "bank_name": bank.bank_name,
+        "account_name": bank.account_name,
+        "account_number": bank.account_number,
+        "amount": order.total_amount,
+        "reference": order.id,
+        "pay_before": order.payment_due_at,
+    }))
+}
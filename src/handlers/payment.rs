@@ -0,0 +1,39 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::services::order::{CheckoutCollections, OrderService};
+use crate::services::payment;
+use crate::utils::error::{AppError, Result};
+use crate::utils::response::ApiResponse;
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::HeaderMap,
+};
+use std::sync::Arc;
+
+// POST /payments/webhook/:provider (called by the payment provider)
+pub async fn payment_webhook(
+    State(state): State<Arc<AppState>>,
+    Path(provider_name): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse> {
+    let provider = payment::provider_for(&state.config.payments, &provider_name)?
+        .ok_or_else(|| AppError::NotFound("Unknown payment provider".to_string()))?;
+
+    let event = provider.parse_webhook(&headers, &body)?;
+    tracing::info!(
+        "{} webhook for {} reports {:?}",
+        provider.name(),
+        event.reference,
+        event.status
+    );
+
+    // Always confirm with the provider before touching the order
+    let verification = provider.verify(&event.reference).await?;
+
+    let collections = CheckoutCollections::from_state(&state);
+    OrderService::apply_payment_verification(&collections, &verification).await?;
+
+    Ok(ApiResponse::with_message(serde_json::json!({}), "Webhook processed"))
+}
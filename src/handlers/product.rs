@@ -1,11 +1,11 @@
 use axum::response::IntoResponse;
-use crate::config::database::MongoDB;
 use crate::db::AppState;
-use crate::middleware::auth::AuthUser;
+use crate::middleware::auth::{AdminUser, AuthUser};
 use crate::models::product::{
     CreateProductRequest, PaginationParams, ProductFilter, UpdateProductRequest,
 };
-use crate::services::product::ProductService;
+use crate::services::product::{ProductCollections, ProductService};
+use crate::services::promotion::PromotionService;
 use crate::utils::error::{ Result};
 use crate::utils::response::ApiResponse;
 use axum::{
@@ -16,6 +16,8 @@ use axum::{
 use serde::Deserialize;
 use std::sync::Arc;
 
+const LOWEST_PRICE_WINDOW_DAYS: i64 = 30;
+
 #[derive(Deserialize)]
 pub struct SearchQuery {
     pub q: String,
@@ -27,10 +29,10 @@ pub async fn list_products(
     Query(filter): Query<ProductFilter>,
     Query(pagination): Query<PaginationParams>,
 ) -> Result<impl IntoResponse> {
-    let collection_name = MongoDB::get_collection_name("MONGO_PRODUCTS_COLLECTION");
-    let collection = state.collection(&collection_name);
+    let collection = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
 
-    let products = ProductService::get_products(
+    let mut products = ProductService::get_products(
         &collection,
         Some(filter),
         None,
@@ -38,6 +40,7 @@ pub async fn list_products(
         pagination.limit,
     )
     .await?;
+    PromotionService::price_products(&promotions, &mut products).await?;
 
     let response = ApiResponse::success(serde_json::json!({
         "results": products.len(),
@@ -52,10 +55,11 @@ pub async fn search_products(
     State(state): State<Arc<AppState>>,
     Query(query): Query<SearchQuery>,
 ) -> Result<impl IntoResponse> {
-    let collection_name = MongoDB::get_collection_name("MONGO_PRODUCTS_COLLECTION");
-    let collection = state.collection(&collection_name);
+    let collection = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
 
-    let products = ProductService::search_products(&collection, &query.q).await?;
+    let mut products = ProductService::search_products(&collection, &query.q).await?;
+    PromotionService::price_products(&promotions, &mut products).await?;
 
     let response = ApiResponse::success(serde_json::json!({
         "results": products.len(),
@@ -70,10 +74,17 @@ pub async fn get_product(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let collection_name = MongoDB::get_collection_name("MONGO_PRODUCTS_COLLECTION");
-    let collection = state.collection(&collection_name);
+    let collection = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+    let history = state.collection("MONGO_PRICE_HISTORY_COLLECTION");
+
+    let mut product = ProductService::get_product_by_id(&collection, &id).await?;
+    PromotionService::price_products(&promotions, std::slice::from_mut(&mut product)).await?;
 
-    let product = ProductService::get_product_by_id(&collection, &id).await?;
+    // Reference price for sale claims, based on list prices only
+    let lowest =
+        ProductService::lowest_price_since(&history, &product, LOWEST_PRICE_WINDOW_DAYS).await?;
+    product.lowest_price_30d = Some(lowest);
 
     let response = ApiResponse::success(serde_json::json!({
         "data": product
@@ -88,10 +99,10 @@ pub async fn create_product(
     State(state): State<Arc<AppState>>,
     Json(req): Json<CreateProductRequest>,
 ) -> Result<impl IntoResponse> {
-    let collection_name = MongoDB::get_collection_name("MONGO_PRODUCTS_COLLECTION");
-    let collection = state.collection(&collection_name);
+    let collection = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
 
-    let product = ProductService::create_prouct(&collection, req).await?;
+    let product = ProductService::create_prouct(&collection, &media, req).await?;
 
     let response = ApiResponse::success(serde_json::json!({
         "data": product
@@ -102,15 +113,15 @@ pub async fn create_product(
 
 // PUT /admin/products/:id (requires authentication)
 pub async fn update_product(
-    _auth: AuthUser,
+    auth: AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
     Json(req): Json<UpdateProductRequest>,
 ) -> Result<impl IntoResponse> {
-    let collection_name = MongoDB::get_collection_name("MONGO_PRODUCTS_COLLECTION");
-    let collection = state.collection(&collection_name);
+    let collections = ProductCollections::from_state(&state);
 
-    let product = ProductService::update_product(&collection, &id, req).await?;
+    let product =
+        ProductService::update_product(&collections, &id, req, &auth.claims.sub).await?;
 
     let response = ApiResponse::success(serde_json::json!({
         "data": product
@@ -125,12 +136,31 @@ pub async fn delete_product(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
 ) -> Result<impl IntoResponse> {
-    let collection_name = MongoDB::get_collection_name("MONGO_PRODUCTS_COLLECTION");
-    let collection = state.collection(&collection_name);
+    let collection = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
 
-    ProductService::delete_product(&collection, &id).await?;
+    ProductService::delete_product(&collection, &media, &id).await?;
 
     let response = ApiResponse::with_message(serde_json::json!({}), "Product deleted successfully");
 
     Ok(response)
 }
+
+// GET /admin/products/:id/price-history (requires admin)
+pub async fn get_price_history(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let history = state.collection("MONGO_PRICE_HISTORY_COLLECTION");
+
+    let entries = ProductService::get_price_history(&collection, &history, &id).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": entries.len(),
+        "data": entries
+    }));
+
+    Ok(response)
+}
@@ -0,0 +1,74 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::AuthUser;
+use crate::models::product_alert::SubscribeAlertRequest;
+use crate::services::product_alert::ProductAlertService;
+use crate::utils::error::Result;
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+// POST /products/:id/alerts (requires authentication)
+pub async fn subscribe(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(product_id): Path<String>,
+    Json(req): Json<SubscribeAlertRequest>,
+) -> Result<impl IntoResponse> {
+    let alerts = state.collection("MONGO_PRODUCT_ALERTS_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+
+    let alert = ProductAlertService::subscribe(
+        &alerts,
+        &products,
+        &promotions,
+        &product_id,
+        &auth.claims.sub,
+        &auth.claims.email,
+        &req.kind,
+    )
+    .await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": alert
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /me/alerts (requires authentication)
+pub async fn list_alerts(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let alerts = state.collection("MONGO_PRODUCT_ALERTS_COLLECTION");
+
+    let alerts = ProductAlertService::get_user_alerts(&alerts, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": alerts.len(),
+        "data": alerts
+    }));
+
+    Ok(response)
+}
+
+// DELETE /me/alerts/:id (requires authentication)
+pub async fn delete_alert(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let alerts = state.collection("MONGO_PRODUCT_ALERTS_COLLECTION");
+
+    ProductAlertService::delete_alert(&alerts, &id, &auth.claims.sub).await?;
+
+    let response = ApiResponse::with_message(serde_json::json!({}), "Alert deleted successfully");
+
+    Ok(response)
+}
@@ -0,0 +1,103 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::AdminUser;
+use crate::models::product::{AttachImageRequest, ReorderImagesRequest, UpdateImageRequest};
+use crate::services::product_image::ProductImageService;
+use crate::utils::error::Result;
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+// POST /admin/products/:id/images (requires admin)
+pub async fn attach_image(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<AttachImageRequest>,
+) -> Result<impl IntoResponse> {
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
+
+    let product = ProductImageService::attach(&products, &media, &id, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": product
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// PUT /admin/products/:id/images/order (requires admin)
+pub async fn reorder_images(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<ReorderImagesRequest>,
+) -> Result<impl IntoResponse> {
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+
+    let product = ProductImageService::reorder(&products, &id, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": product
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/products/:id/images/:image_id (requires admin)
+pub async fn update_image(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, image_id)): Path<(String, String)>,
+    Json(req): Json<UpdateImageRequest>,
+) -> Result<impl IntoResponse> {
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+
+    let product = ProductImageService::update_image(&products, &id, &image_id, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": product
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/products/:id/images/:image_id/cover (requires admin)
+pub async fn set_cover_image(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, image_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+
+    let product = ProductImageService::set_cover(&products, &id, &image_id).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": product
+    }));
+
+    Ok(response)
+}
+
+// DELETE /admin/products/:id/images/:image_id (requires admin)
+pub async fn detach_image(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, image_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
+
+    let product = ProductImageService::detach(&products, &media, &id, &image_id).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": product
+    }));
+
+    Ok(response)
+}
@@ -0,0 +1,156 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::AdminUser;
+use crate::models::promotion::{
+    CreatePromotionRequest, PromotionFilter, SchedulePriceChangeRequest, UpdatePromotionRequest,
+};
+use crate::services::promotion::PromotionService;
+use crate::utils::error::Result;
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+// POST /admin/promotions (requires admin)
+pub async fn create_promotion(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreatePromotionRequest>,
+) -> Result<impl IntoResponse> {
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+
+    let promotion = PromotionService::create_promotion(&promotions, &products, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": promotion
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /admin/promotions?kind=flash_sale&active=true (requires admin)
+pub async fn list_promotions(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<PromotionFilter>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_PROMOTIONS_COLLECTION");
+
+    let promotions = PromotionService::get_promotions(&collection, filter).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": promotions.len(),
+        "data": promotions
+    }));
+
+    Ok(response)
+}
+
+// GET /admin/promotions/:id (requires admin)
+pub async fn get_promotion(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_PROMOTIONS_COLLECTION");
+
+    let promotion = PromotionService::get_promotion(&collection, &id).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": promotion
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/promotions/:id (requires admin)
+pub async fn update_promotion(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdatePromotionRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_PROMOTIONS_COLLECTION");
+
+    let promotion = PromotionService::update_promotion(&collection, &id, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": promotion
+    }));
+
+    Ok(response)
+}
+
+// DELETE /admin/promotions/:id (requires admin)
+pub async fn delete_promotion(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_PROMOTIONS_COLLECTION");
+
+    PromotionService::delete_promotion(&collection, &id).await?;
+
+    let response =
+        ApiResponse::with_message(serde_json::json!({}), "Promotion deleted successfully");
+
+    Ok(response)
+}
+
+// POST /admin/products/:id/price-changes (requires admin)
+pub async fn schedule_price_change(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(product_id): Path<String>,
+    Json(req): Json<SchedulePriceChangeRequest>,
+) -> Result<impl IntoResponse> {
+    let changes = state.collection("MONGO_PRICE_CHANGES_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+
+    let change =
+        PromotionService::schedule_price_change(&changes, &products, &product_id, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": change
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /admin/products/:id/price-changes (requires admin)
+pub async fn list_price_changes(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(product_id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_PRICE_CHANGES_COLLECTION");
+
+    let changes = PromotionService::get_price_changes(&collection, &product_id).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": changes.len(),
+        "data": changes
+    }));
+
+    Ok(response)
+}
+
+// DELETE /admin/price-changes/:id (requires admin)
+pub async fn cancel_price_change(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_PRICE_CHANGES_COLLECTION");
+
+    PromotionService::cancel_price_change(&collection, &id).await?;
+
+    let response =
+        ApiResponse::with_message(serde_json::json!({}), "Price change cancelled successfully");
+
+    Ok(response)
+}
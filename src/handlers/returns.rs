@@ -0,0 +1,283 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::handlers::upload::{read_image_files, upload_error};
+use crate::middleware::auth::{AdminUser, AuthUser};
+use crate::models::media::MediaReference;
+use crate::models::returns::{
+    CreateReturnRequest, ResolveReturnRequest, ReturnFilter, ReviewReturnRequest,
+    UpsertReturnPolicyRequest,
+};
+use crate::services::image::ImageService;
+use crate::services::media::MediaService;
+use crate::services::returns::{ReturnCollections, ReturnService};
+use crate::services::storage::UPLOAD_CONCURRENCY;
+use crate::utils::error::{AppError, Result};
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use futures_util::{stream, StreamExt};
+use std::sync::Arc;
+
+// POST /returns (requires authentication)
+pub async fn create_return(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<CreateReturnRequest>,
+) -> Result<impl IntoResponse> {
+    let collections = ReturnCollections::from_state(&state);
+
+    let return_request =
+        ReturnService::create_return(&collections, &state.config.returns, &auth.claims.sub, req)
+            .await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": return_request
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /returns (requires authentication)
+pub async fn list_my_returns(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_RETURNS_COLLECTION");
+
+    let returns = ReturnService::get_user_returns(&collection, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": returns.len(),
+        "data": returns
+    }));
+
+    Ok(response)
+}
+
+// GET /returns/:id (requires authentication)
+pub async fn get_my_return(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_RETURNS_COLLECTION");
+
+    let return_request = ReturnService::get_user_return(&collection, &id, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": return_request
+    }));
+
+    Ok(response)
+}
+
+// POST /returns/:id/photos (requires authentication, multipart)
+pub async fn upload_return_photos(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_RETURNS_COLLECTION");
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
+
+    // Make sure the return exists before uploading anything
+    ReturnService::get_user_return(&collection, &id, &auth.claims.sub).await?;
+
+    // Each photo is stored on its own, one that fails doesn't drop the others
+    let files = read_image_files(&mut multipart).await?;
+    let results: Vec<Result<String>> = stream::iter(files)
+        .map(|(file_data, _)| async {
+            let (file_data, content_type) = ImageService::sanitize(file_data).await?;
+            let url = state.storage.upload_image(file_data, &content_type, "returns").await?;
+            // Swept if the return can't take it
+            MediaService::record(&media, &auth.claims.sub, &url, vec![url.clone()], None).await?;
+            Ok(url)
+        })
+        .buffered(UPLOAD_CONCURRENCY)
+        .collect()
+        .await;
+
+    let urls: Vec<String> = results.iter().filter_map(|r| r.as_ref().ok().cloned()).collect();
+    if urls.is_empty() {
+        return Err(AppError::ValidationError(
+            "None of the photos could be uploaded".to_string(),
+        ));
+    }
+
+    let return_request =
+        ReturnService::add_photos(&collection, &id, &auth.claims.sub, urls.clone()).await?;
+    MediaService::reference(&media, &urls, &MediaReference::new("return", &id)).await?;
+
+    let photos: Vec<serde_json::Value> = results
+        .iter()
+        .enumerate()
+        .map(|(index, result)| match result {
+            Ok(url) => serde_json::json!({ "index": index, "status": "uploaded", "url": url }),
+            Err(e) => serde_json::json!({
+                "index": index,
+                "status": "failed",
+                "error": upload_error(e)
+            }),
+        })
+        .collect();
+    let status = if urls.len() == results.len() {
+        StatusCode::CREATED
+    } else {
+        StatusCode::MULTI_STATUS
+    };
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": return_request,
+        "photos": photos
+    }));
+
+    Ok((status, response))
+}
+
+// GET /admin/returns?status=requested (requires admin)
+pub async fn list_returns(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(filter): Query<ReturnFilter>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_RETURNS_COLLECTION");
+
+    let returns = ReturnService::get_returns(&collection, filter).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": returns.len(),
+        "data": returns
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/returns/:id/approve (requires admin)
+pub async fn approve_return(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<ReviewReturnRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_RETURNS_COLLECTION");
+
+    let return_request = ReturnService::approve_return(&collection, &id, req.note).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": return_request
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/returns/:id/reject (requires admin)
+pub async fn reject_return(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<ReviewReturnRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_RETURNS_COLLECTION");
+
+    let return_request = ReturnService::reject_return(&collection, &id, req.note).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": return_request
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/returns/:id/receive (requires admin)
+pub async fn receive_return(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<ReviewReturnRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_RETURNS_COLLECTION");
+
+    let return_request = ReturnService::mark_received(&collection, &id, req.note).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": return_request
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/returns/:id/resolve (requires admin)
+pub async fn resolve_return(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<ResolveReturnRequest>,
+) -> Result<impl IntoResponse> {
+    let returns = state.collection("MONGO_RETURNS_COLLECTION");
+    let orders = state.collection("MONGO_ORDERS_COLLECTION");
+    let users = state.collection("MONGO_USERS_COLLECTION");
+
+    let return_request = ReturnService::resolve_return(
+        &returns,
+        &orders,
+        &users,
+        &id,
+        &req.resolution,
+        req.note,
+    )
+    .await?;
+
+    // Refunds are only recorded, remind the admin to send the money
+    let response = if return_request.status == "refunded" {
+        ApiResponse::with_message(
+            serde_json::json!({ "data": return_request }),
+            &format!(
+                "Refund of {:.2} recorded, send it to the customer through the payment provider or by transfer",
+                return_request.refund_amount
+            ),
+        )
+    } else {
+        ApiResponse::success(serde_json::json!({ "data": return_request }))
+    };
+
+    Ok(response)
+}
+
+// GET /admin/return-policies (requires admin)
+pub async fn list_return_policies(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_RETURN_POLICIES_COLLECTION");
+
+    let policies = ReturnService::get_policies(&collection).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": policies.len(),
+        "data": policies
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/return-policies/:category (requires admin)
+pub async fn upsert_return_policy(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(category): Path<String>,
+    Json(req): Json<UpsertReturnPolicyRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_RETURN_POLICIES_COLLECTION");
+
+    let policy = ReturnService::upsert_policy(&collection, &category, req.window_days).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": policy
+    }));
+
+    Ok(response)
+}
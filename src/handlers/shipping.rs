@@ -0,0 +1,123 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::{AdminUser, CartOwner};
+use crate::models::shipping::{ShippingQuoteRequest, ShippingZoneRequest};
+use crate::services::address::AddressService;
+use crate::services::cart::CartService;
+use crate::services::promotion::PromotionService;
+use crate::services::shipping::ShippingService;
+use crate::utils::error::{AppError, Result};
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+// POST /shipping/quote (requires authentication or a guest token)
+pub async fn quote(
+    owner: CartOwner,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ShippingQuoteRequest>,
+) -> Result<impl IntoResponse> {
+    let cart = state.collection("MONGO_CART_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+    let zones = state.collection("MONGO_SHIPPING_ZONES_COLLECTION");
+    let addresses = state.collection("MONGO_ADDRESSES_COLLECTION");
+
+    let address =
+        AddressService::resolve_for_checkout(&addresses, &owner.key(), req.address_id, req.address)
+            .await?;
+    ShippingService::validate_address(&address)?;
+
+    let mut cart = CartService::get_cart(&cart, &owner.key()).await?;
+    if cart.items.is_empty() {
+        return Err(AppError::ValidationError("Cart is empty".to_string()));
+    }
+    PromotionService::price_cart(&promotions, &products, &mut cart).await?;
+
+    let lines: Vec<(String, i32)> = cart
+        .items
+        .iter()
+        .map(|i| (i.product_id.clone(), i.quantity))
+        .collect();
+    let weight_kg = ShippingService::total_weight(&products, &lines).await?;
+
+    let quote = ShippingService::quote(&zones, &address, weight_kg, cart.subtotal).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": quote
+    }));
+
+    Ok(response)
+}
+
+// POST /admin/shipping-zones (requires admin)
+pub async fn create_zone(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ShippingZoneRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_SHIPPING_ZONES_COLLECTION");
+
+    let zone = ShippingService::create_zone(&collection, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": zone
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /admin/shipping-zones (requires admin)
+pub async fn list_zones(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_SHIPPING_ZONES_COLLECTION");
+
+    let zones = ShippingService::get_zones(&collection).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": zones.len(),
+        "data": zones
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/shipping-zones/:id (requires admin)
+pub async fn update_zone(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<ShippingZoneRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_SHIPPING_ZONES_COLLECTION");
+
+    let zone = ShippingService::update_zone(&collection, &id, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": zone
+    }));
+
+    Ok(response)
+}
+
+// DELETE /admin/shipping-zones/:id (requires admin)
+pub async fn delete_zone(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_SHIPPING_ZONES_COLLECTION");
+
+    ShippingService::delete_zone(&collection, &id).await?;
+
+    let response =
+        ApiResponse::with_message(serde_json::json!({}), "Shipping zone deleted successfully");
+
+    Ok(response)
+}
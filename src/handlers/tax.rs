@@ -0,0 +1,80 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::AdminUser;
+use crate::models::tax::TaxRuleRequest;
+use crate::services::tax::TaxService;
+use crate::utils::error::Result;
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+// POST /admin/tax-rules (requires admin)
+pub async fn create_rule(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<TaxRuleRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_TAX_RULES_COLLECTION");
+
+    let rule = TaxService::create_rule(&collection, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": rule
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /admin/tax-rules (requires admin)
+pub async fn list_rules(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_TAX_RULES_COLLECTION");
+
+    let rules = TaxService::get_rules(&collection).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": rules.len(),
+        "data": rules
+    }));
+
+    Ok(response)
+}
+
+// PUT /admin/tax-rules/:id (requires admin)
+pub async fn update_rule(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<TaxRuleRequest>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_TAX_RULES_COLLECTION");
+
+    let rule = TaxService::update_rule(&collection, &id, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": rule
+    }));
+
+    Ok(response)
+}
+
+// DELETE /admin/tax-rules/:id (requires admin)
+pub async fn delete_rule(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let collection = state.collection("MONGO_TAX_RULES_COLLECTION");
+
+    TaxService::delete_rule(&collection, &id).await?;
+
+    let response = ApiResponse::with_message(serde_json::json!({}), "Tax rule deleted successfully");
+
+    Ok(response)
+}
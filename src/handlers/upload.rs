@@ -1,145 +1,360 @@
 use axum::{
-    extract::{Multipart,},
+    body::Body,
+    extract::{
+        multipart::{Field, MultipartError},
+        Multipart, Path, Query, State,
+    },
     http::StatusCode,
+    response::IntoResponse,
     Json,
 };
+use futures_util::TryStreamExt;
 use serde::Serialize;
-
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
 
 use crate::{
+    db::AppState,
     middleware::auth::AuthUser,
-    services::s3::S3Service,
+    models::product::MAX_PRODUCT_IMAGES,
+    models::upload::{ConfirmUploadRequest, DirectUploadQuery, PresignUploadRequest},
+    services::image::{ImageService, UploadedImage},
+    services::media::MediaService,
+    services::storage::{ByteChunks, UPLOAD_CONCURRENCY},
+    services::upload::UploadService,
     utils::error::{AppError, Result},
+    utils::response::ApiResponse,
 };
 
+/// Max size of each uploaded file
+const MAX_FILE_SIZE: usize = 5 * 1024 * 1024;
+/// Enough bytes to recognize every supported image format
+const SNIFF_LENGTH: usize = 16;
+
 #[derive(Serialize)]
 pub struct ImageUploadResponse {
     pub status: String,
     pub url: String,
+    pub image: UploadedImage,
 }
 
 #[derive(Serialize)]
 pub struct MultipleImageUploadResponse {
-    pub status: String,
+    pub status: String,  // "success", "partial" or "failed"
     pub urls: Vec<String>,
+    pub images: Vec<UploadedImage>,
+    pub results: Vec<ImageUploadResult>,
+}
+
+/// Outcome of one file of a multi-image upload, in form order
+#[derive(Serialize)]
+pub struct ImageUploadResult {
+    pub index: usize,
+    pub file_name: Option<String>,
+    pub status: String,  // "uploaded" or "failed"
+    pub image: Option<UploadedImage>,
+    pub error: Option<String>,
 }
 
 /// Upload a single product image
 /// POST /api/upload/image
 pub async fn upload_single_image(
-    _auth: AuthUser,  // Require authentication
+    auth: AuthUser,  // Require authentication
+    State(state): State<Arc<AppState>>,
     mut multipart: Multipart,
 ) -> Result<(StatusCode, Json<ImageUploadResponse>)> {
-    let s3_service = S3Service::new().await?;
-
     // Extract file from multipart form
-    if let Some(field) = multipart.next_field().await.map_err(|e| {
-        tracing::error!("Multipart error: {:?}", e);
-        AppError::ValidationError("Failed to read multipart data".to_string())
-    })? {
-        let content_type = field
-            .content_type()
-            .unwrap_or("image/jpeg")
-            .to_string();
-
-        // Validate content type
-        if !content_type.starts_with("image/") {
-            return Err(AppError::ValidationError(
-                "Only image files are allowed".to_string(),
-            ));
-        }
+    let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? else {
+        return Err(AppError::ValidationError("No file provided".to_string()));
+    };
+    let (data, _) = read_file(&mut field).await?;
 
-        // Read file data
-        let data = field.bytes().await.map_err(|e| {
-            tracing::error!("Failed to read file bytes: {:?}", e);
-            AppError::ValidationError("Failed to read file data".to_string())
-        })?;
+    // Resize to every variant and upload them to storage
+    let image = ImageService::upload_variants(state.storage.as_ref(), data, "products").await?;
 
-        // Check file size (max 5MB)
-        if data.len() > 5 * 1024 * 1024 {
-            return Err(AppError::ValidationError(
-                "File size must be less than 5MB".to_string(),
-            ));
-        }
+    // Swept unless a product starts using it
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
+    MediaService::record(&media, &auth.claims.sub, &image.url, image.files(), None).await?;
 
-        // Upload to S3
-        let url = s3_service
-            .upload_image(data.to_vec(), &content_type, "products")
-            .await?;
-
-        Ok((
-            StatusCode::CREATED,
-            Json(ImageUploadResponse {
-                status: "success".to_string(),
-                url,
-            }),
-        ))
-    } else {
-        Err(AppError::ValidationError("No file provided".to_string()))
-    }
+    Ok((
+        StatusCode::CREATED,
+        Json(ImageUploadResponse {
+            status: "success".to_string(),
+            url: image.url.clone(),
+            image,
+        }),
+    ))
 }
 
-/// Upload multiple product images
+/// Upload multiple product images. The form is read one file at a time and each file is
+/// processed as soon as it is read, a few at a time, so reading stops while uploads are busy.
+/// Each file gets its own result so one bad file doesn't fail the others.
 /// POST /api/upload/images
 pub async fn upload_multiple_images(
-    _auth: AuthUser,
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
     mut multipart: Multipart,
 ) -> Result<(StatusCode, Json<MultipleImageUploadResponse>)> {
-    let s3_service = S3Service::new().await?;
-    let mut files = Vec::new();
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
+    let slots = Arc::new(Semaphore::new(UPLOAD_CONCURRENCY));
+    let mut uploads = JoinSet::new();
+    let mut tasks = HashMap::new();  // file of each upload task, to report tasks that panic
+    let mut results = Vec::new();
+    let mut index = 0;
 
-    // Extract all files from multipart form
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
-        tracing::error!("Multipart error: {:?}", e);
-        AppError::ValidationError("Failed to read multipart data".to_string())
-    })? {
-        let content_type = field
-            .content_type()
-            .unwrap_or("image/jpeg")
-            .to_string();
-
-        // Validate content type
-        if !content_type.starts_with("image/") {
-            continue; // Skip non-image files
+    loop {
+        let mut field = match multipart.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                // The rest of the form can't be read, keep what was uploaded so far
+                results.push(failed(index, None, &multipart_error(e)));
+                break;
+            }
+        };
+
+        // Skip plain form fields
+        if field.file_name().is_none() && field.content_type().is_none() {
+            continue;
         }
 
-        // Read file data
-        let data = field.bytes().await.map_err(|e| {
-            tracing::error!("Failed to read file bytes: {:?}", e);
-            AppError::ValidationError("Failed to read file data".to_string())
-        })?;
+        let file_index = index;
+        let file_name = field.file_name().map(str::to_string);
+        index += 1;
 
-        // Check file size
-        if data.len() > 5 * 1024 * 1024 {
-            return Err(AppError::ValidationError(
-                "Each file must be less than 5MB".to_string(),
+        // Same limit as the images a product can have
+        if file_index >= MAX_PRODUCT_IMAGES {
+            let error = AppError::ValidationError(format!(
+                "Maximum {} images allowed",
+                MAX_PRODUCT_IMAGES
             ));
+            results.push(failed(file_index, file_name, &error));
+            continue;
         }
 
-        files.push((data.to_vec(), content_type));
+        let data = match read_file(&mut field).await {
+            Ok((data, _)) => data,
+            Err(e) => {
+                results.push(failed(file_index, file_name, &e));
+                continue;
+            }
+        };
+
+        // Wait for a free slot, which also stops reading the form while uploads are busy
+        let slot = slots.clone().acquire_owned().await.map_err(|_| AppError::InternalError)?;
+        let (state, media, owner_id) = (state.clone(), media.clone(), auth.claims.sub.clone());
+
+        let task_file = (file_index, file_name.clone());
+        let task = uploads.spawn(async move {
+            let _slot = slot;
+            let uploaded = async {
+                let image =
+                    ImageService::upload_variants(state.storage.as_ref(), data, "products").await?;
+                MediaService::record(&media, &owner_id, &image.url, image.files(), None).await?;
+                Ok::<_, AppError>(image)
+            };
+
+            match uploaded.await {
+                Ok(image) => ImageUploadResult {
+                    index: file_index,
+                    file_name,
+                    status: "uploaded".to_string(),
+                    image: Some(image),
+                    error: None,
+                },
+                Err(e) => failed(file_index, file_name, &e),
+            }
+        });
+        tasks.insert(task.id(), task_file);
     }
 
-    if files.is_empty() {
-        return Err(AppError::ValidationError("No files provided".to_string()));
+    while let Some(result) = uploads.join_next_with_id().await {
+        match result {
+            Ok((_, result)) => results.push(result),
+            Err(e) => {
+                tracing::error!("Image upload task failed: {:?}", e);
+                let (index, file_name) = tasks.remove(&e.id()).unwrap_or_default();
+                results.push(failed(index, file_name, &AppError::InternalError));
+            }
+        }
     }
 
-    // Limit to 5 images
-    if files.len() > 5 {
-        return Err(AppError::ValidationError(
-            "Maximum 5 images allowed".to_string(),
-        ));
+    if results.is_empty() {
+        return Err(AppError::ValidationError("No files provided".to_string()));
     }
+    results.sort_by_key(|result| result.index);
 
-    // Upload all files to S3
-    let urls = s3_service
-        .upload_multiple_images(files, "products")
-        .await?;
+    let images: Vec<UploadedImage> =
+        results.iter().filter_map(|result| result.image.clone()).collect();
+    let (status_code, status) = match images.len() {
+        n if n == results.len() => (StatusCode::CREATED, "success"),
+        0 => (StatusCode::BAD_REQUEST, "failed"),
+        _ => (StatusCode::MULTI_STATUS, "partial"),
+    };
 
     Ok((
-        StatusCode::CREATED,
+        status_code,
         Json(MultipleImageUploadResponse {
-            status: "success".to_string(),
-            urls,
+            status: status.to_string(),
+            urls: images.iter().map(|image| image.url.clone()).collect(),
+            images,
+            results,
         }),
     ))
 }
+
+fn failed(index: usize, file_name: Option<String>, error: &AppError) -> ImageUploadResult {
+    ImageUploadResult {
+        index,
+        file_name,
+        status: "failed".to_string(),
+        image: None,
+        error: Some(upload_error(error)),
+    }
+}
+
+/// What a client is told about a file that couldn't be uploaded
+pub fn upload_error(error: &AppError) -> String {
+    match error {
+        AppError::ValidationError(message) => message.clone(),
+        _ => "Upload failed".to_string(),
+    }
+}
+
+/// Get a presigned URL to upload a product image straight to storage
+/// POST /api/upload/presign
+pub async fn presign_upload(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<PresignUploadRequest>,
+) -> Result<impl IntoResponse> {
+    let uploads = state.collection("MONGO_UPLOADS_COLLECTION");
+
+    let presigned =
+        UploadService::presign(&uploads, state.storage.as_ref(), &auth.claims.sub, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": presigned
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+/// Register an image uploaded to a presigned URL
+/// POST /api/upload/confirm
+pub async fn confirm_upload(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ConfirmUploadRequest>,
+) -> Result<impl IntoResponse> {
+    let uploads = state.collection("MONGO_UPLOADS_COLLECTION");
+    let media = state.collection("MONGO_MEDIA_COLLECTION");
+
+    let confirmed = UploadService::confirm(
+        &uploads,
+        &media,
+        state.storage.as_ref(),
+        &auth.claims.sub,
+        req,
+    )
+    .await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": confirmed
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+/// Receive a presigned upload, for storage backends that can't take them directly. The
+/// file is checked once confirmed, so it is streamed to storage as it arrives.
+/// PUT /api/upload/direct/{key}
+pub async fn direct_upload(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    Query(query): Query<DirectUploadQuery>,
+    body: Body,
+) -> Result<StatusCode> {
+    state.storage.verify_presigned(
+        &key,
+        &query.content_type,
+        query.size,
+        query.expires,
+        &query.signature,
+    )?;
+
+    let chunks: ByteChunks = Box::pin(body.into_data_stream().map_err(|e| {
+        tracing::error!("Upload body error: {:?}", e);
+        AppError::ValidationError("Failed to read the upload".to_string())
+    }));
+    state
+        .storage
+        .put_stream(&key, chunks, query.size, &query.content_type)
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// Read every file of a multipart form (max 5 images of 5MB each)
+pub async fn read_image_files(multipart: &mut Multipart) -> Result<Vec<(Vec<u8>, String)>> {
+    let mut files = Vec::new();
+
+    // Extract all files from multipart form
+    while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+        // Skip plain form fields
+        if field.file_name().is_none() && field.content_type().is_none() {
+            continue;
+        }
+
+        // Same limit as the images a product can have
+        if files.len() == MAX_PRODUCT_IMAGES {
+            return Err(AppError::ValidationError(format!(
+                "Maximum {} images allowed",
+                MAX_PRODUCT_IMAGES
+            )));
+        }
+
+        let (data, content_type) = read_file(&mut field).await?;
+        files.push((data, content_type.to_string()));
+    }
+
+    if files.is_empty() {
+        return Err(AppError::ValidationError("No files provided".to_string()));
+    }
+
+    Ok(files)
+}
+
+/// Read a file into memory chunk by chunk, giving up as soon as it is too big or isn't an
+/// image. The file's magic bytes are trusted over the content type the client sent.
+async fn read_file(field: &mut Field<'_>) -> Result<(Vec<u8>, &'static str)> {
+    let mut data = Vec::new();
+    let mut content_type = None;
+
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        if data.len() + chunk.len() > MAX_FILE_SIZE {
+            return Err(AppError::ValidationError(
+                "Each file must be less than 5MB".to_string(),
+            ));
+        }
+        data.extend_from_slice(&chunk);
+
+        if content_type.is_none() && data.len() >= SNIFF_LENGTH {
+            content_type = Some(ImageService::sniff_content_type(&data)?);
+        }
+    }
+
+    let content_type = match content_type {
+        Some(content_type) => content_type,
+        None => ImageService::sniff_content_type(&data)?,
+    };
+
+    Ok((data, content_type))
+}
+
+fn multipart_error(e: MultipartError) -> AppError {
+    tracing::error!("Multipart error: {:?}", e);
+    AppError::ValidationError("Failed to read multipart data".to_string())
+}
@@ -0,0 +1,270 @@
+use axum::response::IntoResponse;
+use crate::db::AppState;
+use crate::middleware::auth::{AdminUser, AuthUser};
+use crate::models::wishlist::{
+    AddWishlistItemRequest, MoveToCartRequest, Wishlist, WishlistCountQuery, WishlistRequest,
+    WishlistResponse,
+};
+use crate::services::promotion::PromotionService;
+use crate::services::wishlist::WishlistService;
+use crate::utils::error::Result;
+use crate::utils::response::ApiResponse;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use std::sync::Arc;
+
+// POST /me/wishlists (requires authentication)
+pub async fn create_wishlist(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<WishlistRequest>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+
+    let wishlist = WishlistService::create_wishlist(&wishlists, &auth.claims.sub, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": with_products(&state, wishlist).await?
+    }));
+
+    Ok((StatusCode::CREATED, response))
+}
+
+// GET /me/wishlists (requires authentication)
+pub async fn list_wishlists(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+
+    let lists = WishlistService::get_wishlists(&wishlists, &auth.claims.sub).await?;
+    let lists = WishlistService::to_responses(&products, &promotions, lists).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": lists.len(),
+        "data": lists
+    }));
+
+    Ok(response)
+}
+
+// GET /me/wishlists/:id (requires authentication)
+pub async fn get_wishlist(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+
+    let wishlist = WishlistService::get_wishlist(&wishlists, &id, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": with_products(&state, wishlist).await?
+    }));
+
+    Ok(response)
+}
+
+// PUT /me/wishlists/:id (requires authentication)
+pub async fn rename_wishlist(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<WishlistRequest>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+
+    let wishlist =
+        WishlistService::rename_wishlist(&wishlists, &id, &auth.claims.sub, req).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": with_products(&state, wishlist).await?
+    }));
+
+    Ok(response)
+}
+
+// DELETE /me/wishlists/:id (requires authentication)
+pub async fn delete_wishlist(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+
+    WishlistService::delete_wishlist(&wishlists, &id, &auth.claims.sub).await?;
+
+    let response =
+        ApiResponse::with_message(serde_json::json!({}), "Wishlist deleted successfully");
+
+    Ok(response)
+}
+
+// POST /me/wishlists/:id/items (requires authentication)
+pub async fn add_item(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<AddWishlistItemRequest>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+
+    let wishlist = WishlistService::add_item(
+        &wishlists,
+        &products,
+        &id,
+        &auth.claims.sub,
+        &req.product_id,
+    )
+    .await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": with_products(&state, wishlist).await?
+    }));
+
+    Ok(response)
+}
+
+// DELETE /me/wishlists/:id/items/:product_id (requires authentication)
+pub async fn remove_item(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path((id, product_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+
+    let wishlist =
+        WishlistService::remove_item(&wishlists, &id, &auth.claims.sub, &product_id).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": with_products(&state, wishlist).await?
+    }));
+
+    Ok(response)
+}
+
+// POST /me/wishlists/:id/share (requires authentication)
+pub async fn share_wishlist(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+
+    let wishlist = WishlistService::share(&wishlists, &id, &auth.claims.sub).await?;
+    let share_url = wishlist.share_token.as_deref().map(|token| share_url(&state, token));
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": with_products(&state, wishlist).await?,
+        "share_url": share_url
+    }));
+
+    Ok(response)
+}
+
+// DELETE /me/wishlists/:id/share (requires authentication)
+pub async fn unshare_wishlist(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+
+    let wishlist = WishlistService::unshare(&wishlists, &id, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": with_products(&state, wishlist).await?
+    }));
+
+    Ok(response)
+}
+
+// POST /me/wishlists/:id/move-to-cart (requires authentication)
+pub async fn move_to_cart(
+    auth: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(req): Json<MoveToCartRequest>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+    let cart = state.collection("MONGO_CART_COLLECTION");
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+
+    let active = PromotionService::active_promotions(&promotions).await?;
+    let lines = WishlistService::move_to_cart(
+        &wishlists,
+        &cart,
+        &products,
+        &active,
+        &id,
+        &auth.claims.sub,
+        req.product_ids,
+    )
+    .await?;
+
+    let wishlist = WishlistService::get_wishlist(&wishlists, &id, &auth.claims.sub).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": with_products(&state, wishlist).await?,
+        "moved": lines
+    }));
+
+    Ok(response)
+}
+
+// GET /wishlists/shared/:token
+pub async fn get_shared_wishlist(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+
+    let wishlist = WishlistService::get_shared_wishlist(&wishlists, &token).await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "data": with_products(&state, wishlist).await?
+    }));
+
+    Ok(response)
+}
+
+// GET /admin/wishlist-counts?product_id=&limit= (requires admin)
+pub async fn product_counts(
+    _admin: AdminUser,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WishlistCountQuery>,
+) -> Result<impl IntoResponse> {
+    let wishlists = state.collection("MONGO_WISHLISTS_COLLECTION");
+
+    let counts =
+        WishlistService::product_counts(&wishlists, query.product_id.as_deref(), query.limit)
+            .await?;
+
+    let response = ApiResponse::success(serde_json::json!({
+        "results": counts.len(),
+        "data": counts
+    }));
+
+    Ok(response)
+}
+
+async fn with_products(state: &AppState, wishlist: Wishlist) -> Result<WishlistResponse> {
+    let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+    let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+
+    let mut responses = WishlistService::to_responses(&products, &promotions, vec![wishlist]).await?;
+    Ok(responses.remove(0))
+}
+
+// Public link to a shared wishlist
+fn share_url(state: &AppState, token: &str) -> String {
+    let base = &state.config.links.wishlist_share_url;
+    format!("{}/{}", base.trim_end_matches('/'), token)
+}
@@ -0,0 +1,38 @@
+use crate::db::AppState;
+use crate::jobs::next_tick;
+use crate::services::abandoned_cart::AbandonedCartService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Queue reminders for carts customers left without ordering
+pub fn spawn(state: Arc<AppState>, workers: &TaskTracker) {
+    workers.spawn(async move {
+        let mut interval = tokio::time::interval(SCAN_INTERVAL);
+
+        while next_tick(&mut interval, &state.shutdown).await {
+            let cart = state.collection("MONGO_CART_COLLECTION");
+            let orders = state.collection("MONGO_ORDERS_COLLECTION");
+            let users = state.collection("MONGO_USERS_COLLECTION");
+            let reminders = state.collection("MONGO_CART_REMINDERS_COLLECTION");
+            let notifications = state.collection("MONGO_NOTIFICATIONS_COLLECTION");
+
+            match AbandonedCartService::queue_reminders(
+                &cart,
+                &orders,
+                &users,
+                &reminders,
+                &notifications,
+                &state.config.notifications,
+            )
+            .await
+            {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Queued {} abandoned cart reminders", count),
+                Err(e) => tracing::error!("Abandoned cart scan failed: {:?}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,41 @@
+use crate::db::AppState;
+use crate::jobs::next_tick;
+use crate::services::media::MediaService;
+use crate::services::upload::UploadService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Delete uploaded files nothing uses anymore
+pub fn spawn(state: Arc<AppState>, workers: &TaskTracker) {
+    workers.spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+
+        while next_tick(&mut interval, &state.shutdown).await {
+            let media = state.collection("MONGO_MEDIA_COLLECTION");
+            let uploads = state.collection("MONGO_UPLOADS_COLLECTION");
+
+            let swept =
+                MediaService::sweep_orphans(&media, state.storage.as_ref(), &state.config.media);
+            match swept.await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Deleted {} unreferenced media", count),
+                Err(e) => tracing::error!("Media sweep failed: {:?}", e),
+            }
+
+            match UploadService::expire_unconfirmed(&uploads, state.storage.as_ref()).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Dropped {} unconfirmed uploads", count),
+                Err(e) => tracing::error!("Expiring unconfirmed uploads failed: {:?}", e),
+            }
+
+            match UploadService::purge_processed(&uploads, state.storage.as_ref()).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Purged raw files of {} processed uploads", count),
+                Err(e) => tracing::error!("Purging processed uploads failed: {:?}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,18 @@
+pub mod abandoned_carts;
+pub mod media_sweeper;
+pub mod notifications;
+pub mod offline_payments;
+pub mod price_alerts;
+pub mod price_changes;
+
+use tokio::time::Interval;
+use tokio_util::sync::CancellationToken;
+
+/// Wait for a job's next run, `false` once the server is shutting down.
+/// A run that already started is finished before the job stops.
+pub async fn next_tick(interval: &mut Interval, shutdown: &CancellationToken) -> bool {
+    tokio::select! {
+        _ = shutdown.cancelled() => false,
+        _ = interval.tick() => !shutdown.is_cancelled(),
+    }
+}
@@ -0,0 +1,33 @@
+use crate::db::AppState;
+use crate::jobs::next_tick;
+use crate::services::notification::{self, NotificationService};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
+
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Send queued notifications through the configured notifier
+pub fn spawn(state: Arc<AppState>, workers: &TaskTracker) {
+    let notifier = match notification::notifier_from_config(&state.config.notifications) {
+        Ok(notifier) => notifier,
+        Err(e) => {
+            tracing::error!("Notifications are disabled: {:?}", e);
+            return;
+        }
+    };
+
+    workers.spawn(async move {
+        let mut interval = tokio::time::interval(DISPATCH_INTERVAL);
+
+        while next_tick(&mut interval, &state.shutdown).await {
+            let notifications = state.collection("MONGO_NOTIFICATIONS_COLLECTION");
+
+            match NotificationService::dispatch_pending(&notifications, notifier.as_ref()).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Sent {} notifications via {}", count, notifier.name()),
+                Err(e) => tracing::error!("Notification dispatch failed: {:?}", e),
+            }
+        }
+    });
+}
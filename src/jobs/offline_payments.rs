@@ -0,0 +1,25 @@
+use crate::db::AppState;
+use crate::jobs::next_tick;
+use crate::services::order::{CheckoutCollections, OrderService};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Periodically cancel offline orders that were not paid before their deadline
+pub fn spawn(state: Arc<AppState>, workers: &TaskTracker) {
+    workers.spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+
+        while next_tick(&mut interval, &state.shutdown).await {
+            let collections = CheckoutCollections::from_state(&state);
+
+            match OrderService::cancel_expired_offline_orders(&collections).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Cancelled {} expired offline orders", count),
+                Err(e) => tracing::error!("Offline payment sweep failed: {:?}", e),
+            }
+        }
+    });
+}
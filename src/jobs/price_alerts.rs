@@ -0,0 +1,35 @@
+use crate::db::AppState;
+use crate::jobs::next_tick;
+use crate::services::product_alert::ProductAlertService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Tell subscribers about price drops from promotions that started since the last run
+pub fn spawn(state: Arc<AppState>, workers: &TaskTracker) {
+    workers.spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+        while next_tick(&mut interval, &state.shutdown).await {
+            let alerts = state.collection("MONGO_PRODUCT_ALERTS_COLLECTION");
+            let notifications = state.collection("MONGO_NOTIFICATIONS_COLLECTION");
+            let products = state.collection("MONGO_PRODUCTS_COLLECTION");
+            let promotions = state.collection("MONGO_PROMOTIONS_COLLECTION");
+
+            match ProductAlertService::notify_promotional_drops(
+                &alerts,
+                &notifications,
+                &products,
+                &promotions,
+            )
+            .await
+            {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Queued {} promotional price drop alerts", count),
+                Err(e) => tracing::error!("Checking promotional price drops failed: {:?}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,27 @@
+use crate::db::AppState;
+use crate::jobs::next_tick;
+use crate::services::product::ProductCollections;
+use crate::services::promotion::PromotionService;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
+
+const APPLY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Apply scheduled product price changes once they are due
+pub fn spawn(state: Arc<AppState>, workers: &TaskTracker) {
+    workers.spawn(async move {
+        let mut interval = tokio::time::interval(APPLY_INTERVAL);
+
+        while next_tick(&mut interval, &state.shutdown).await {
+            let changes = state.collection("MONGO_PRICE_CHANGES_COLLECTION");
+            let collections = ProductCollections::from_state(&state);
+
+            match PromotionService::apply_due_price_changes(&changes, &collections).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Applied {} scheduled price changes", count),
+                Err(e) => tracing::error!("Applying scheduled price changes failed: {:?}", e),
+            }
+        }
+    });
+}
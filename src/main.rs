@@ -1,6 +1,7 @@
 mod config;
 mod db;
 mod handlers;
+mod jobs;
 mod middleware;
 mod models;
 mod routes;
@@ -8,9 +9,11 @@ mod services;
 mod utils;
 
 use dotenv::dotenv;
-use std::net::SocketAddr;
+use std::future::IntoFuture;
+use std::io::Write;
 use std::sync::Arc;
-use tower_http::cors::{Any, CorsLayer};
+use axum_server::tls_rustls::RustlsConfig;
+use tokio_util::task::TaskTracker;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
@@ -27,25 +30,134 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // Load configuration, listing everything that is missing at once
+    let config = match config::app::Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("❌ {}", e);
+            return Err(e.into());
+        }
+    };
+
+    // Set up file storage
+    let storage = services::storage::storage_from_config(&config.storage).await?;
+    tracing::info!("✅ Using {} file storage", storage.name());
+
     // Connect to MongoDB
-    let app_state = Arc::new(db::AppState::init().await?);
+    let addr = config.listen_addr();
+    let tls = config.server.tls.clone();
+    let shutdown_timeout = config.server.shutdown_timeout;
+    let app_state = Arc::new(db::AppState::init(config, storage).await?);
     tracing::info!("✅ MongoDB connection established");
 
-    // Setup CORS
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    // Start background jobs
+    let workers = TaskTracker::new();
+    jobs::abandoned_carts::spawn(app_state.clone(), &workers);
+    jobs::media_sweeper::spawn(app_state.clone(), &workers);
+    jobs::notifications::spawn(app_state.clone(), &workers);
+    jobs::offline_payments::spawn(app_state.clone(), &workers);
+    jobs::price_alerts::spawn(app_state.clone(), &workers);
+    jobs::price_changes::spawn(app_state.clone(), &workers);
+    workers.close();
+
+    // On SIGTERM or Ctrl+C readiness starts failing, the jobs stop and the server drains
+    let shutdown = app_state.shutdown.clone();
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move {
+            shutdown_signal().await;
+            tracing::info!("🛑 Shutting down, finishing in-flight requests");
+            shutdown.cancel();
+        }
+    });
 
     // Build application with routes
-    let app = routes::api::create_routes(app_state).layer(cors);
+    let app = routes::api::create_routes(app_state.clone());
+
+    // Start server, terminating TLS ourselves when certificates are configured
+    match tls {
+        Some(tls) => {
+            // Several TLS backends get compiled in through dependencies, pick one
+            let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+            let rustls_config = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await?;
+
+            let handle = axum_server::Handle::new();
+            tokio::spawn({
+                let (handle, shutdown) = (handle.clone(), shutdown.clone());
+                async move {
+                    shutdown.cancelled().await;
+                    handle.graceful_shutdown(Some(shutdown_timeout));
+                }
+            });
+
+            tracing::info!("🚀 Server listening on {} (TLS)", addr);
+            axum_server::bind_rustls(addr, rustls_config)
+                .handle(handle)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        None => {
+            tracing::info!("🚀 Server listening on {}", addr);
+
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            let server = axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+                .into_future();
 
-    // Start server
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-    tracing::info!("🚀 Server listening on {}", addr);
+            // Don't wait forever on requests that won't finish
+            tokio::select! {
+                result = server => result?,
+                _ = async {
+                    shutdown.cancelled().await;
+                    tokio::time::sleep(shutdown_timeout).await;
+                } => tracing::warn!("⚠️  Dropped requests still running after {:?}", shutdown_timeout),
+            }
+        }
+    }
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    // Let the jobs finish the run they are in
+    shutdown.cancel();
+    let workers_done = tokio::time::timeout(shutdown_timeout, workers.wait()).await.is_ok();
+    if !workers_done {
+        tracing::warn!("⚠️  Background jobs still running after {:?}", shutdown_timeout);
+    }
+
+    // Close the MongoDB connections, without waiting on jobs we gave up on
+    app_state.db.client().clone().shutdown().immediate(!workers_done).await;
+
+    tracing::info!("👋 Shutdown complete");
+    std::io::stdout().flush()?;
 
     Ok(())
 }
+
+// Resolves on Ctrl+C, or on SIGTERM from the orchestrator
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Could not listen for Ctrl+C: {:?}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Could not listen for SIGTERM: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
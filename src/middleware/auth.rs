@@ -35,6 +35,27 @@ pub async fn auth_middleware(
     Ok(next.run(req).await)
 }
 
+// Like auth_middleware, but lets requests without an Authorization header through
+// so guests can use the route
+pub async fn optional_auth_middleware(
+    State(jwt_secret): State<Arc<String>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(auth_header) = req.headers().get("Authorization") {
+        let token = auth_header
+            .to_str()
+            .ok()
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or_else(|| AppError::AuthError("Invalid authorization format".to_string()))?;
+
+        let claims = AuthService::verify_jwt(token, &jwt_secret)?;
+        req.extensions_mut().insert(claims);
+    }
+
+    Ok(next.run(req).await)
+}
+
 // Extractor for getting authenticated user from request
 #[derive(Debug, Clone)]
 pub struct AuthUser {
@@ -61,3 +82,109 @@ where
         Ok(AuthUser { claims })
     }
 }
+
+// Extractor for routes restricted to admins
+#[derive(Debug, Clone)]
+pub struct AdminUser {
+    #[allow(dead_code)]
+    pub claims: Claims,
+}
+
+impl<S> FromRequestParts<S> for AdminUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let AuthUser { claims } = AuthUser::from_request_parts(parts, state).await?;
+
+        if claims.role != "admin" {
+            return Err(AppError::Forbidden("Admin access required".to_string()));
+        }
+
+        Ok(AdminUser { claims })
+    }
+}
+
+// Token of a client that shops without an account, sent in the X-Guest-Token header
+#[derive(Debug, Clone)]
+pub struct GuestToken(pub Option<String>);
+
+impl GuestToken {
+    // Key the guest's cart is stored under
+    pub fn cart_key(&self) -> Option<String> {
+        self.0.as_deref().map(guest_cart_key)
+    }
+}
+
+impl<S> FromRequestParts<S> for GuestToken
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(token) = parts.headers.get("X-Guest-Token") else {
+            return Ok(GuestToken(None));
+        };
+
+        // Guest tokens are random UUIDs handed out by POST /guest/session
+        let token = token
+            .to_str()
+            .ok()
+            .and_then(|t| uuid::Uuid::parse_str(t).ok())
+            .ok_or_else(|| AppError::AuthError("Invalid guest token".to_string()))?;
+
+        Ok(GuestToken(Some(token.to_string())))
+    }
+}
+
+// Owner of a cart: a signed-in user, or a guest identified by the X-Guest-Token header
+#[derive(Debug, Clone)]
+pub enum CartOwner {
+    User(Claims),
+    Guest(String),
+}
+
+impl CartOwner {
+    // Key the cart, applied coupon and address lookups are stored under
+    pub fn key(&self) -> String {
+        match self {
+            CartOwner::User(claims) => claims.sub.clone(),
+            CartOwner::Guest(token) => guest_cart_key(token),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for CartOwner
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        if let Some(claims) = parts.extensions.get::<Claims>() {
+            return Ok(CartOwner::User(claims.clone()));
+        }
+
+        let GuestToken(token) = GuestToken::from_request_parts(parts, state).await?;
+        let token =
+            token.ok_or_else(|| AppError::AuthError("Sign in or send a guest token".to_string()))?;
+
+        Ok(CartOwner::Guest(token))
+    }
+}
+
+fn guest_cart_key(token: &str) -> String {
+    format!("guest:{}", token)
+}
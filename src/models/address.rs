@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Serialize};
+
+use crate::models::shipping::Address;
+
+// Address saved in a customer's address book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedAddress {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: String,
+    pub label: Option<String>,  // e.g. "Home", "Office"
+    pub address: Address,
+    pub is_default: bool,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveAddressRequest {
+    pub label: Option<String>,
+    pub address: Address,
+    pub is_default: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SavedAddressResponse {
+    pub id: String,
+    pub label: Option<String>,
+    pub address: Address,
+    pub is_default: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SavedAddress {
+    // Convert SavedAddress to SavedAddressResponse
+    pub fn to_response(&self) -> SavedAddressResponse {
+        SavedAddressResponse {
+            id: self.id.unwrap().to_hex(),
+            label: self.label.clone(),
+            address: self.address.clone(),
+            is_default: self.is_default,
+            created_at: self.created_at,
+        }
+    }
+}
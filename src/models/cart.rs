@@ -1,6 +1,11 @@
+use chrono::{DateTime, Utc};
 use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
 use serde::{Deserialize, Serialize};
 
+use crate::models::coupon::DiscountLine;
+use crate::models::tax::TaxLine;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CartItem {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -8,8 +13,12 @@ pub struct CartItem {
     pub user_id: String,
     pub product_id: String,
     pub product_name: String,
-    pub product_price: f64,
+    pub product_price: f64,  // effective price the customer was last shown
     pub quantity: i32,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -17,3 +26,70 @@ pub struct AddToCartRequest {
     pub product_id: String,
     pub quantity: i32,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCartItemRequest {
+    pub quantity: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CartItemResponse {
+    pub product_id: String,
+    pub product_name: String,
+    pub product_price: f64,
+    pub quantity: i32,
+    pub line_total: f64,
+    pub issue: Option<String>,  // set when the line has a warning
+}
+
+// Something about a cart line the customer should look at before checking out
+#[derive(Debug, Clone, Serialize)]
+pub struct CartWarning {
+    pub product_id: String,
+    pub product_name: String,
+    pub issue: String,  // "price_changed", "out_of_stock", "low_stock", "removed"
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CartResponse {
+    pub items: Vec<CartItemResponse>,
+    pub item_count: i32,
+    pub subtotal: f64,
+    pub discount: Option<DiscountLine>,
+    pub coupon_message: Option<String>,  // why the applied coupon gives no discount
+    pub tax_amount: f64,                 // estimated for the default address
+    pub tax_lines: Vec<TaxLine>,
+    pub total: f64,
+    pub warnings: Vec<CartWarning>,
+}
+
+// What became of one guest cart line when it was merged into the customer's cart
+#[derive(Debug, Serialize)]
+pub struct CartMergeLine {
+    pub product_id: String,
+    pub product_name: String,
+    pub requested: i32,  // quantity in the guest cart
+    pub added: i32,
+    pub status: String,  // "added", "capped" (less stock than asked), "unavailable"
+}
+
+#[derive(Debug, Serialize)]
+pub struct CartMergeReport {
+    pub lines: Vec<CartMergeLine>,
+    pub coupon_code: Option<String>,  // coupon carried over from the guest cart
+}
+
+impl CartItem {
+    // Convert CartItem to CartItemResponse
+    pub fn to_response(&self) -> CartItemResponse {
+        CartItemResponse {
+            product_id: self.product_id.clone(),
+            product_name: self.product_name.clone(),
+            product_price: self.product_price,
+            quantity: self.quantity,
+            line_total: self.product_price * self.quantity as f64,
+            issue: None,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::{
+    chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional,
+};
+use serde::{Deserialize, Serialize};
+
+// Reminder sent about an abandoned cart, and whether an order followed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartReminder {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: String,
+    pub item_count: i32,
+    pub cart_value: f64,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub last_activity_at: DateTime<Utc>,  // last change to the cart before the reminder
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub sent_at: DateTime<Utc>,
+    pub recovered_order_id: Option<String>,
+    #[serde(default)]
+    pub recovered_amount: f64,
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub recovered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecoveryStatsQuery {
+    pub days: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoveryStats {
+    pub days: i64,
+    pub reminders_sent: u64,
+    pub carts_recovered: u64,
+    pub recovery_rate: f64,  // percent of reminders followed by an order
+    pub reminded_value: f64,
+    pub recovered_revenue: f64,
+}
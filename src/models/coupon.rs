@@ -0,0 +1,137 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::{
+    chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Coupon {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_type: String,  // "percentage" or "fixed"
+    pub value: f64,
+    pub min_order_value: Option<f64>,
+    pub usage_limit: Option<i32>,     // across all customers
+    pub per_user_limit: Option<i32>,
+    pub used_count: i32,
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub ends_at: Option<DateTime<Utc>>,
+    pub categories: Option<Vec<String>>,
+    pub product_ids: Option<Vec<String>>,
+    pub active: bool,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// One use of a coupon by a customer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CouponRedemption {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub code: String,
+    pub user_id: String,
+    pub order_id: Option<String>,
+    pub discount_amount: f64,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+// Coupon a customer applied to their cart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedCoupon {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: String,
+    pub code: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub applied_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCouponRequest {
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_type: String,
+    pub value: f64,
+    pub min_order_value: Option<f64>,
+    pub usage_limit: Option<i32>,
+    pub per_user_limit: Option<i32>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub categories: Option<Vec<String>>,
+    pub product_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCouponRequest {
+    pub description: Option<String>,
+    pub value: Option<f64>,
+    pub min_order_value: Option<f64>,
+    pub usage_limit: Option<i32>,
+    pub per_user_limit: Option<i32>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub categories: Option<Vec<String>>,
+    pub product_ids: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApplyCouponRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CouponResponse {
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_type: String,
+    pub value: f64,
+    pub min_order_value: Option<f64>,
+    pub usage_limit: Option<i32>,
+    pub per_user_limit: Option<i32>,
+    pub used_count: i32,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub categories: Option<Vec<String>>,
+    pub product_ids: Option<Vec<String>>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// Discount line shown on the cart
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscountLine {
+    pub code: String,
+    pub description: Option<String>,
+    pub amount: f64,
+}
+
+impl Coupon {
+    // Convert Coupon to CouponResponse
+    pub fn to_response(&self) -> CouponResponse {
+        CouponResponse {
+            code: self.code.clone(),
+            description: self.description.clone(),
+            discount_type: self.discount_type.clone(),
+            value: self.value,
+            min_order_value: self.min_order_value,
+            usage_limit: self.usage_limit,
+            per_user_limit: self.per_user_limit,
+            used_count: self.used_count,
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            categories: self.categories.clone(),
+            product_ids: self.product_ids.clone(),
+            active: self.active,
+            created_at: self.created_at,
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::{
+    chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional,
+};
+use serde::{Deserialize, Serialize};
+
+// An uploaded image and the stored files it is made of
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Media {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub owner_id: String,  // user who uploaded it
+    pub url: String,
+    pub files: Vec<String>,  // URLs of every stored file, variants included
+    pub references: Vec<MediaReference>,
+    // Set while nothing references the media, the sweeper deletes it after a grace period
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub unreferenced_since: Option<DateTime<Utc>>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+// Something that uses a media, e.g. the product it's the cover image of
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaReference {
+    pub kind: String,  // "product", "order", "return"
+    pub id: String,
+}
+
+impl MediaReference {
+    pub fn new(kind: &str, id: &str) -> Self {
+        MediaReference {
+            kind: kind.to_string(),
+            id: id.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MediaFilter {
+    pub owner_id: Option<String>,
+    pub unreferenced: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MediaResponse {
+    pub id: String,
+    pub owner_id: String,
+    pub url: String,
+    pub files: Vec<String>,
+    pub references: Vec<MediaReference>,
+    pub unreferenced_since: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Media {
+    // Convert Media to MediaResponse
+    pub fn to_response(&self) -> MediaResponse {
+        MediaResponse {
+            id: self.id.unwrap().to_hex(),
+            owner_id: self.owner_id.clone(),
+            url: self.url.clone(),
+            files: self.files.clone(),
+            references: self.references.clone(),
+            unreferenced_since: self.unreferenced_since,
+            created_at: self.created_at,
+        }
+    }
+}
@@ -1,4 +1,17 @@
 pub mod product;
+pub mod price_history;
 pub mod user;
 pub mod cart;
 pub mod order;
+pub mod returns;
+pub mod shipping;
+pub mod address;
+pub mod tax;
+pub mod coupon;
+pub mod promotion;
+pub mod cart_reminder;
+pub mod notification;
+pub mod wishlist;
+pub mod product_alert;
+pub mod upload;
+pub mod media;
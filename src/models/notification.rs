@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::{
+    chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional,
+};
+use serde::{Deserialize, Serialize};
+
+// Message waiting in the outbox until the dispatcher hands it to the notifier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub kind: String,  // e.g. "abandoned_cart"
+    pub user_id: Option<String>,
+    pub email: String,
+    pub subject: String,
+    pub body: String,
+    pub status: String,  // "pending", "sent", "failed"
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub sent_at: Option<DateTime<Utc>>,
+}
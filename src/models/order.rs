@@ -1,21 +1,61 @@
 use chrono::{DateTime, Utc};
-use mongodb::bson::{bson, oid::ObjectId};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::{
+    chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional,
+};
 use serde::{Deserialize, Serialize};
 
+use crate::models::shipping::{address_or_legacy, Address};
+use crate::models::tax::{ItemTax, TaxLine};
+
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
-    pub user_id: String,
+    pub user_id: Option<String>,  // None for guest orders not claimed by an account yet
+    #[serde(default)]
+    pub guest_email: Option<String>,
+    #[serde(default)]
+    pub guest_phone: Option<String>,
     pub items: Vec<OrderItem>,
-    pub total_amount: f64,
+    pub total_amount: f64,  // items - discount + tax not included in prices + shipping
+    #[serde(default)]
+    pub discount_amount: f64,
+    #[serde(default)]
+    pub tax_amount: f64,
+    #[serde(default)]
+    pub tax_lines: Vec<TaxLine>,
+    #[serde(default)]
+    pub shipping_fee: f64,
+    #[serde(default)]
+    pub coupon_code: Option<String>,
     pub payment_method: String,  // "paystack", "opay", "offline"
     pub payment_reference: Option<String>,
-    pub payment_status: String,  // "pending", "completed", "failed"
+    pub payment_status: String,  // "pending", "completed", "failed", "reversed"
     pub order_status: String,    // "pending", "processing", "shipped", "completed", "cancelled"
-    pub shipping_address: Option<String>,
+    #[serde(default, deserialize_with = "address_or_legacy")]
+    pub shipping_address: Option<Address>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
     pub created_at: DateTime<Utc>,
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub refunded_amount: f64,  // refunds recorded by admins, paid out outside the app
+    // Offline (bank transfer) payments
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub payment_due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub payment_proof: Option<PaymentProof>,
+    #[serde(default)]
+    pub payment_note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentProof {
+    pub url: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub uploaded_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,10 +64,125 @@ pub struct OrderItem {
     pub product_name: String,
     pub quantity: i32,
     pub price: f64,
+    #[serde(default)]
+    pub promotion_id: Option<String>,  // promotion that set the price
+    #[serde(default)]
+    pub tax: Option<ItemTax>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateOrderRequest {
     pub payment_method: String,
-    pub shipping_address: Option<String>,
+    pub address_id: Option<String>,          // saved address to ship to
+    pub shipping_address: Option<Address>,  // or a one-off address
+    pub coupon_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GuestOrderRequest {
+    pub email: String,
+    pub phone: String,
+    pub payment_method: String,
+    pub shipping_address: Address,
+    pub coupon_code: Option<String>,
+}
+
+// Claims of the signed link a guest uses to look up their order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderLookupClaims {
+    pub sub: String,  // Order ID
+    pub email: String,
+    pub purpose: String,
+    pub exp: usize,
+    pub iat: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderLookupQuery {
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimOrderRequest {
+    pub token: String,  // from the order link sent at checkout
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateOrderStatusRequest {
+    pub order_status: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewPaymentRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrderFilter {
+    pub order_status: Option<String>,
+    pub payment_status: Option<String>,
+    pub payment_method: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaymentProofResponse {
+    pub url: String,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrderResponse {
+    pub id: String,
+    pub user_id: Option<String>,
+    pub guest_email: Option<String>,
+    pub items: Vec<OrderItem>,
+    pub total_amount: f64,
+    pub discount_amount: f64,
+    pub tax_amount: f64,
+    pub tax_lines: Vec<TaxLine>,
+    pub shipping_fee: f64,
+    pub coupon_code: Option<String>,
+    pub payment_method: String,
+    pub payment_reference: Option<String>,
+    pub payment_status: String,
+    pub order_status: String,
+    pub shipping_address: Option<Address>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub refunded_amount: f64,
+    pub payment_due_at: Option<DateTime<Utc>>,
+    pub payment_proof: Option<PaymentProofResponse>,
+    pub payment_note: Option<String>,
+}
+
+impl Order {
+    // Convert Order to OrderResponse
+    pub fn to_response(&self) -> OrderResponse {
+        OrderResponse {
+            id: self.id.unwrap().to_hex(),
+            user_id: self.user_id.clone(),
+            guest_email: self.guest_email.clone(),
+            items: self.items.clone(),
+            total_amount: self.total_amount,
+            discount_amount: self.discount_amount,
+            tax_amount: self.tax_amount,
+            tax_lines: self.tax_lines.clone(),
+            shipping_fee: self.shipping_fee,
+            coupon_code: self.coupon_code.clone(),
+            payment_method: self.payment_method.clone(),
+            payment_reference: self.payment_reference.clone(),
+            payment_status: self.payment_status.clone(),
+            order_status: self.order_status.clone(),
+            shipping_address: self.shipping_address.clone(),
+            created_at: self.created_at,
+            completed_at: self.completed_at,
+            refunded_amount: self.refunded_amount,
+            payment_due_at: self.payment_due_at,
+            payment_proof: self.payment_proof.as_ref().map(|p| PaymentProofResponse {
+                url: p.url.clone(),
+                uploaded_at: p.uploaded_at,
+            }),
+            payment_note: self.payment_note.clone(),
+        }
+    }
 }
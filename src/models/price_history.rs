@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Serialize};
+
+// One change of a product's list price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceHistoryEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub product_id: String,
+    pub old_price: f64,
+    pub new_price: f64,
+    pub changed_by: String,  // user ID, or "scheduler" for scheduled price changes
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PriceHistoryResponse {
+    pub old_price: f64,
+    pub new_price: f64,
+    pub changed_by: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl PriceHistoryEntry {
+    // Convert PriceHistoryEntry to PriceHistoryResponse
+    pub fn to_response(&self) -> PriceHistoryResponse {
+        PriceHistoryResponse {
+            old_price: self.old_price,
+            new_price: self.new_price,
+            changed_by: self.changed_by.clone(),
+            changed_at: self.changed_at,
+        }
+    }
+}
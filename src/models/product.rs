@@ -2,6 +2,11 @@ use chrono::{DateTime, Utc};
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
+use crate::models::promotion::PromotionSummary;
+
+/// Most images a product can have, the cover included
+pub const MAX_PRODUCT_IMAGES: usize = 5;
+
 
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,8 +19,14 @@ pub struct Product {
     pub prodcut_type: String,
     pub price: f64,
     pub stock_quantity: i32,
+    #[serde(default)]
+    pub weight_kg: Option<f64>,
     pub cover_image:Option<String>,
     pub aditional_images:Option<Vec<String>>,
+    // Ordered images, the first one is the cover. cover_image and aditional_images
+    // mirror it for older clients.
+    #[serde(default)]
+    pub images: Vec<ProductImage>,
     pub label: Option<String>,
     pub average_rating: f64,
     pub rating_count: i32,
@@ -24,6 +35,29 @@ pub struct Product {
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductImage {
+    pub id: String,
+    pub url: String,
+    pub alt_text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttachImageRequest {
+    pub url: String,  // URL returned by an upload endpoint
+    pub alt_text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReorderImagesRequest {
+    pub image_ids: Vec<String>,  // every image of the product, cover first
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateImageRequest {
+    pub alt_text: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateProductRequest {
     pub name: String,
@@ -32,6 +66,7 @@ pub struct CreateProductRequest {
     pub product_type: String,
     pub price: f64,
     pub stock_quantity: i32,
+    pub weight_kg: Option<f64>,
     pub cover_image: Option<String>,
     pub additional_images: Option<Vec<String>>,
     pub tags: Option<Vec<String>>,
@@ -43,10 +78,11 @@ pub struct UpdateProductRequest {
     pub description: Option<String>,
     pub price: Option<f64>,
     pub stock_quantity: Option<i32>,
+    pub weight_kg: Option<f64>,
     pub tags: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ProductResponse {
     pub id: String,
     pub name: String,
@@ -54,9 +90,14 @@ pub struct ProductResponse {
     pub category: String,
     pub product_type: String,
     pub price: f64,
+    pub effective_price: f64,  // price after any running promotion
+    pub promotion: Option<PromotionSummary>,
+    pub lowest_price_30d: Option<f64>,  // only on the product detail
     pub stock_quantity: i32,
+    pub weight_kg: Option<f64>,
     pub cover_image: Option<String>,
     pub additional_images: Option<Vec<String>>,
+    pub images: Vec<ProductImage>,
     pub label: Option<String>,
     pub average_rating: f64,
     pub rating_count: i32,
@@ -74,9 +115,14 @@ impl Product {
             category: self.category.clone(),
             product_type: self.prodcut_type.clone(),
             price: self.price,
+            effective_price: self.price,
+            promotion: None,
+            lowest_price_30d: None,
             stock_quantity: self.stock_quantity,
+            weight_kg: self.weight_kg,
             cover_image: self.cover_image.clone(),
             additional_images: self.aditional_images.clone(),
+            images: self.image_list(),
             label: self.label.clone(),
             average_rating: self.average_rating,
             rating_count: self.rating_count,
@@ -86,6 +132,27 @@ impl Product {
     }
 }
 
+impl Product {
+    // Images in order, products created before images were managed get theirs
+    // from cover_image and aditional_images
+    pub fn image_list(&self) -> Vec<ProductImage> {
+        if !self.images.is_empty() {
+            return self.images.clone();
+        }
+
+        self.cover_image
+            .iter()
+            .chain(self.aditional_images.iter().flatten())
+            .enumerate()
+            .map(|(position, url)| ProductImage {
+                id: format!("legacy-{}", position),
+                url: url.clone(),
+                alt_text: None,
+            })
+            .collect()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ProductFilter {
     pub category: Option<String>,
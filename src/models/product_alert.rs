@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::{
+    chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional,
+};
+use serde::{Deserialize, Serialize};
+
+// A customer's request to hear about a product coming back in stock or getting cheaper
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductAlert {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: String,
+    pub email: String,
+    pub product_id: String,
+    pub kind: String,  // "back_in_stock" or "price_drop"
+    pub subscribed_price: f64,  // price drops are measured from here
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub delivered_at: Option<DateTime<Utc>>,  // each alert is sent once
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubscribeAlertRequest {
+    pub kind: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProductAlertResponse {
+    pub id: String,
+    pub product_id: String,
+    pub kind: String,
+    pub subscribed_price: f64,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl ProductAlert {
+    // Convert ProductAlert to ProductAlertResponse
+    pub fn to_response(&self) -> ProductAlertResponse {
+        ProductAlertResponse {
+            id: self.id.unwrap().to_hex(),
+            product_id: self.product_id.clone(),
+            kind: self.kind.clone(),
+            subscribed_price: self.subscribed_price,
+            created_at: self.created_at,
+            delivered_at: self.delivered_at,
+        }
+    }
+}
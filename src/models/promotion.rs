@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::{
+    chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Promotion {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    pub kind: String,  // "sale" or "flash_sale"
+    pub product_ids: Option<Vec<String>>,
+    pub category: Option<String>,
+    pub sale_price: Option<f64>,   // fixed price, only for product promotions
+    pub percent_off: Option<f64>,
+    pub quantity_cap: Option<i32>,  // units sold at the flash sale price
+    pub sold_count: i32,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub starts_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub ends_at: DateTime<Utc>,
+    pub active: bool,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+// Permanent change of a product's price at a given time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledPriceChange {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub product_id: String,
+    pub price: f64,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub effective_at: DateTime<Utc>,
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub applied_at: Option<DateTime<Utc>>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePromotionRequest {
+    pub name: String,
+    pub kind: String,
+    pub product_ids: Option<Vec<String>>,
+    pub category: Option<String>,
+    pub sale_price: Option<f64>,
+    pub percent_off: Option<f64>,
+    pub quantity_cap: Option<i32>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdatePromotionRequest {
+    pub name: Option<String>,
+    pub sale_price: Option<f64>,
+    pub percent_off: Option<f64>,
+    pub quantity_cap: Option<i32>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromotionFilter {
+    pub kind: Option<String>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SchedulePriceChangeRequest {
+    pub price: f64,
+    pub effective_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromotionResponse {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub product_ids: Option<Vec<String>>,
+    pub category: Option<String>,
+    pub sale_price: Option<f64>,
+    pub percent_off: Option<f64>,
+    pub quantity_cap: Option<i32>,
+    pub sold_count: i32,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// Promotion currently lowering a product's price
+#[derive(Debug, Clone, Serialize)]
+pub struct PromotionSummary {
+    pub name: String,
+    pub kind: String,
+    pub ends_at: DateTime<Utc>,
+    pub remaining: Option<i32>,  // flash sale units left
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduledPriceChangeResponse {
+    pub id: String,
+    pub product_id: String,
+    pub price: f64,
+    pub effective_at: DateTime<Utc>,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Promotion {
+    // Convert Promotion to PromotionResponse
+    pub fn to_response(&self) -> PromotionResponse {
+        PromotionResponse {
+            id: self.id.unwrap().to_hex(),
+            name: self.name.clone(),
+            kind: self.kind.clone(),
+            product_ids: self.product_ids.clone(),
+            category: self.category.clone(),
+            sale_price: self.sale_price,
+            percent_off: self.percent_off,
+            quantity_cap: self.quantity_cap,
+            sold_count: self.sold_count,
+            starts_at: self.starts_at,
+            ends_at: self.ends_at,
+            active: self.active,
+            created_at: self.created_at,
+        }
+    }
+
+    // Summary shown next to the discounted price
+    pub fn to_summary(&self) -> PromotionSummary {
+        PromotionSummary {
+            name: self.name.clone(),
+            kind: self.kind.clone(),
+            ends_at: self.ends_at,
+            remaining: self.quantity_cap.map(|cap| (cap - self.sold_count).max(0)),
+        }
+    }
+}
+
+impl ScheduledPriceChange {
+    // Convert ScheduledPriceChange to ScheduledPriceChangeResponse
+    pub fn to_response(&self) -> ScheduledPriceChangeResponse {
+        ScheduledPriceChangeResponse {
+            id: self.id.unwrap().to_hex(),
+            product_id: self.product_id.clone(),
+            price: self.price,
+            effective_at: self.effective_at,
+            applied_at: self.applied_at,
+            created_at: self.created_at,
+        }
+    }
+}
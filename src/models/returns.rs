@@ -0,0 +1,133 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReturnRequest {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub order_id: String,
+    pub user_id: String,
+    pub items: Vec<ReturnItem>,
+    pub reason: String,
+    pub photos: Vec<String>,
+    pub status: String,  // "requested", "approved", "rejected", "received", "refunded", "credited"
+    pub resolution: Option<String>,  // "refund" (recorded, paid out by hand) or "store_credit"
+    pub refund_amount: f64,
+    pub admin_note: Option<String>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReturnItem {
+    pub product_id: String,
+    pub product_name: String,
+    pub quantity: i32,
+    pub unit_price: f64,
+    #[serde(default)]
+    pub paid_unit_price: f64,  // after the coupon discount, plus tax not included in the price
+}
+
+// Returnable window for all products in a category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReturnPolicy {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub category: String,
+    pub window_days: i64,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateReturnRequest {
+    pub order_id: String,
+    pub items: Vec<ReturnItemRequest>,
+    pub reason: String,
+    pub photos: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReturnItemRequest {
+    pub product_id: String,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewReturnRequest {
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveReturnRequest {
+    pub resolution: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertReturnPolicyRequest {
+    pub window_days: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReturnFilter {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReturnResponse {
+    pub id: String,
+    pub order_id: String,
+    pub user_id: String,
+    pub items: Vec<ReturnItem>,
+    pub reason: String,
+    pub photos: Vec<String>,
+    pub status: String,
+    pub resolution: Option<String>,
+    pub refund_amount: f64,
+    pub admin_note: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReturnRequest {
+    // Convert ReturnRequest to ReturnResponse
+    pub fn to_response(&self) -> ReturnResponse {
+        ReturnResponse {
+            id: self.id.unwrap().to_hex(),
+            order_id: self.order_id.clone(),
+            user_id: self.user_id.clone(),
+            items: self.items.clone(),
+            reason: self.reason.clone(),
+            photos: self.photos.clone(),
+            status: self.status.clone(),
+            resolution: self.resolution.clone(),
+            refund_amount: self.refund_amount,
+            admin_note: self.admin_note.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReturnPolicyResponse {
+    pub category: String,
+    pub window_days: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ReturnPolicy {
+    // Convert ReturnPolicy to ReturnPolicyResponse
+    pub fn to_response(&self) -> ReturnPolicyResponse {
+        ReturnPolicyResponse {
+            category: self.category.clone(),
+            window_days: self.window_days,
+            updated_at: self.updated_at,
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Address {
+    pub recipient_name: String,
+    #[serde(default)]
+    pub phone: Option<String>,
+    pub line1: String,
+    pub line2: Option<String>,
+    pub city: String,
+    pub state: String,
+    pub lga: Option<String>,
+    pub postcode: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShippingZone {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    pub states: Vec<String>,
+    pub cities: Option<Vec<String>>,  // limits the zone to these cities of its states
+    pub rate_type: String,            // "flat" or "weight_based"
+    pub fee: f64,                     // flat fee, or base fee for weight based rates
+    pub per_kg: Option<f64>,
+    pub free_over: Option<f64>,       // items subtotal above which delivery is free
+    pub active: bool,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShippingZoneRequest {
+    pub name: String,
+    pub states: Vec<String>,
+    pub cities: Option<Vec<String>>,
+    pub rate_type: String,
+    pub fee: f64,
+    pub per_kg: Option<f64>,
+    pub free_over: Option<f64>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShippingQuoteRequest {
+    pub address_id: Option<String>,
+    pub address: Option<Address>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShippingZoneResponse {
+    pub id: String,
+    pub name: String,
+    pub states: Vec<String>,
+    pub cities: Option<Vec<String>>,
+    pub rate_type: String,
+    pub fee: f64,
+    pub per_kg: Option<f64>,
+    pub free_over: Option<f64>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShippingQuote {
+    pub zone: String,
+    pub weight_kg: f64,
+    pub items_subtotal: f64,
+    pub fee: f64,
+}
+
+impl ShippingZone {
+    // Convert ShippingZone to ShippingZoneResponse
+    pub fn to_response(&self) -> ShippingZoneResponse {
+        ShippingZoneResponse {
+            id: self.id.unwrap().to_hex(),
+            name: self.name.clone(),
+            states: self.states.clone(),
+            cities: self.cities.clone(),
+            rate_type: self.rate_type.clone(),
+            fee: self.fee,
+            per_kg: self.per_kg,
+            free_over: self.free_over,
+            active: self.active,
+            created_at: self.created_at,
+        }
+    }
+}
+
+// Orders placed before addresses were structured stored them as a single line
+pub fn address_or_legacy<'de, D>(deserializer: D) -> Result<Option<Address>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StoredAddress {
+        Structured(Address),
+        Legacy(String),
+    }
+
+    Ok(match Option::<StoredAddress>::deserialize(deserializer)? {
+        Some(StoredAddress::Structured(address)) => Some(address),
+        Some(StoredAddress::Legacy(line1)) => Some(Address {
+            line1,
+            ..Default::default()
+        }),
+        None => None,
+    })
+}
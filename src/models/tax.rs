@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxRule {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,                     // e.g. "VAT"
+    pub rate: f64,                        // percent
+    pub categories: Option<Vec<String>>,  // None applies to every category
+    pub regions: Option<Vec<String>>,     // destination states, None applies everywhere
+    pub inclusive: bool,                  // prices already include the tax
+    pub exempt: bool,                     // matching items are not taxed
+    pub active: bool,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaxRuleRequest {
+    pub name: String,
+    pub rate: f64,
+    pub categories: Option<Vec<String>>,
+    pub regions: Option<Vec<String>>,
+    pub inclusive: bool,
+    pub exempt: Option<bool>,
+    pub active: Option<bool>,
+}
+
+// Tax charged on one order item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemTax {
+    pub rule: String,
+    pub rate: f64,
+    pub inclusive: bool,
+    pub taxable_amount: f64,
+    pub amount: f64,
+}
+
+// Tax per rule over a whole cart or order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxLine {
+    pub rule: String,
+    pub rate: f64,
+    pub inclusive: bool,
+    pub taxable_amount: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaxRuleResponse {
+    pub id: String,
+    pub name: String,
+    pub rate: f64,
+    pub categories: Option<Vec<String>>,
+    pub regions: Option<Vec<String>>,
+    pub inclusive: bool,
+    pub exempt: bool,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TaxRule {
+    // Convert TaxRule to TaxRuleResponse
+    pub fn to_response(&self) -> TaxRuleResponse {
+        TaxRuleResponse {
+            id: self.id.unwrap().to_hex(),
+            name: self.name.clone(),
+            rate: self.rate,
+            categories: self.categories.clone(),
+            regions: self.regions.clone(),
+            inclusive: self.inclusive,
+            exempt: self.exempt,
+            active: self.active,
+            created_at: self.created_at,
+        }
+    }
+}
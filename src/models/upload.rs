@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::{
+    chrono_datetime_as_bson_datetime, chrono_datetime_as_bson_datetime_optional,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::services::image::UploadedImage;
+use crate::services::storage::PresignedUpload;
+
+// A file a client was allowed to upload straight to storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingUpload {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub key: String,  // where the client uploads the raw file
+    pub user_id: String,
+    pub content_type: String,
+    pub size: i64,
+    pub status: String,  // "pending", "processing", "confirmed", "rejected", "expired"
+    #[serde(default)]
+    pub url: Option<String>,  // large variant once confirmed
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default, with = "chrono_datetime_as_bson_datetime_optional")]
+    pub confirmed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub raw_purged: bool,  // raw file deleted after the upload URL expired
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PresignUploadRequest {
+    pub content_type: String,
+    pub size: u64,  // exact size in bytes of the file to upload
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmUploadRequest {
+    pub key: String,
+}
+
+// Query string of the presigned URLs the local storage hands out
+#[derive(Debug, Deserialize)]
+pub struct DirectUploadQuery {
+    pub content_type: String,
+    pub size: u64,
+    pub expires: i64,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PresignUploadResponse {
+    pub key: String,
+    pub upload: PresignedUpload,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfirmUploadResponse {
+    pub key: String,
+    pub image: UploadedImage,
+}
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
+use crate::models::cart::CartMergeReport;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -11,6 +13,8 @@ pub struct User {
     pub password_hash: String,
     pub full_name: Option<String>,
     pub role: String,  // "customer" or "admin"
+    #[serde(default)]
+    pub store_credit: f64,
     pub created_at: DateTime<Utc>,
 }
 
@@ -32,6 +36,8 @@ pub struct LoginRequest {
 pub struct AuthResponse {
     pub token: String,
     pub user: UserResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cart_merge: Option<CartMergeReport>,  // when a guest cart was merged in
 }
 
 #[derive(Debug, Serialize)]
@@ -40,6 +46,7 @@ pub struct UserResponse {
     pub email: String,
     pub full_name: Option<String>,
     pub role: String,
+    pub store_credit: f64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use serde::{Deserialize, Serialize};
+
+use crate::models::product::ProductResponse;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Wishlist {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: String,
+    pub name: String,
+    pub items: Vec<WishlistItem>,
+    pub share_token: Option<String>,  // set while the list is shared publicly
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WishlistItem {
+    pub product_id: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WishlistRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddWishlistItemRequest {
+    pub product_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveToCartRequest {
+    pub product_ids: Option<Vec<String>>,  // None moves the whole list
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WishlistCountQuery {
+    pub product_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WishlistItemResponse {
+    pub product_id: String,
+    pub added_at: DateTime<Utc>,
+    pub product: Option<ProductResponse>,  // None once the product is deleted
+}
+
+#[derive(Debug, Serialize)]
+pub struct WishlistResponse {
+    pub id: String,
+    pub name: String,
+    pub item_count: usize,
+    pub items: Vec<WishlistItemResponse>,
+    pub share_token: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// What became of one item when moving a wishlist to the cart
+#[derive(Debug, Serialize)]
+pub struct MoveToCartLine {
+    pub product_id: String,
+    pub status: String,  // "moved", "out_of_stock", "unavailable"
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WishlistCount {
+    #[serde(rename = "_id")]
+    pub product_id: String,
+    pub wishlist_count: i64,
+}
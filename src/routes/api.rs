@@ -1,17 +1,28 @@
+use crate::config::app::CorsConfig;
 use crate::db::AppState;
-use crate::handlers::{auth as auth_handlers, product as product_handlers, upload as upload_handlers};
-use crate::middleware::auth::auth_middleware; 
-use axum::extract::{DefaultBodyLimit, };
+use crate::handlers::{
+    abandoned_cart as abandoned_cart_handlers, address as address_handlers, auth as auth_handlers,
+    cart as cart_handlers, coupon as coupon_handlers, guest as guest_handlers,
+    health as health_handlers, media as media_handlers, order as order_handlers, payment as payment_handlers,
+    product as product_handlers, product_alert as product_alert_handlers,
+    product_image as product_image_handlers, promotion as promotion_handlers,
+    returns as return_handlers, shipping as shipping_handlers, tax as tax_handlers,
+    upload as upload_handlers, wishlist as wishlist_handlers,
+};
+use crate::middleware::auth::{auth_middleware, optional_auth_middleware};
+use axum::extract::DefaultBodyLimit;
 use axum::{
     middleware,
     routing::{delete, get, post, put},
     Router,
 };
-use std::env;
 use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::services::ServeDir;
 
 pub fn create_routes(state: Arc<AppState>) -> Router {
-    let jwt_secret = Arc::new(env::var("JWT_SECRET").expect("JWT_SECRET must be set"));
+    let jwt_secret = Arc::new(state.config.auth.jwt_secret.clone());
+    let limits = &state.config.limits;
 
     // Public routes (no authentication)
     let public_routes = Router::new()
@@ -19,27 +30,170 @@ pub fn create_routes(state: Arc<AppState>) -> Router {
         .route("/auth/login", post(auth_handlers::login))
         .route("/products", get(product_handlers::list_products))
         .route("/products/search", get(product_handlers::search_products))
-        .route("/products/{id}", get(product_handlers::get_product));
+        .route("/products/{id}", get(product_handlers::get_product))
+        .route("/payments/webhook/{provider}", post(payment_handlers::payment_webhook))
+        .route("/guest/session", post(guest_handlers::create_session))
+        .route("/guest/orders/lookup", get(guest_handlers::lookup_order))
+        .route("/wishlists/shared/{token}", get(wishlist_handlers::get_shared_wishlist))
+        .layer(DefaultBodyLimit::max(limits.public));
+
+    // Shopping routes (signed in customers, or guests with an X-Guest-Token header)
+    let shopping_routes = Router::new()
+        .route("/cart", get(cart_handlers::get_cart))
+        .route("/cart", post(cart_handlers::add_to_cart))
+        .route("/cart/{product_id}", put(cart_handlers::update_cart_item))
+        .route("/cart/{product_id}", delete(cart_handlers::remove_cart_item))
+        .route("/cart/coupon", post(cart_handlers::apply_coupon))
+        .route("/cart/coupon", delete(cart_handlers::remove_coupon))
+        .route("/shipping/quote", post(shipping_handlers::quote))
+        .route("/guest/orders", post(guest_handlers::create_order))
+        .layer(middleware::from_fn_with_state(jwt_secret.clone(), optional_auth_middleware))
+        .layer(DefaultBodyLimit::max(limits.shopping));
 
 
    // Upload routes (require authentication)
     let upload_routes: Router<Arc<AppState>> = Router::new()
         .route("/upload/image", post(upload_handlers::upload_single_image))
         .route("/upload/images", post(upload_handlers::upload_multiple_images))
+        .route("/upload/presign", post(upload_handlers::presign_upload))
+        .route("/upload/confirm", post(upload_handlers::confirm_upload))
         .layer(middleware::from_fn_with_state(jwt_secret.clone(), auth_middleware))
-        .layer(DefaultBodyLimit::max(10 * 1024 * 1024));  // 10MB max for uploads
+        .layer(DefaultBodyLimit::max(limits.uploads));
+
+    // Presigned uploads received by the API itself (authorized by the URL's signature)
+    let direct_upload_routes: Router<Arc<AppState>> = Router::new()
+        .route("/upload/direct/{*key}", put(upload_handlers::direct_upload))
+        .layer(DefaultBodyLimit::max(limits.uploads));
+
+    // Receipts of guest orders (authorized by the order link)
+    let guest_upload_routes: Router<Arc<AppState>> = Router::new()
+        .route("/guest/orders/payment-proof", post(guest_handlers::upload_payment_proof))
+        .layer(DefaultBodyLimit::max(limits.customer));
+
+    // Customer routes (require authentication)
+    let customer_routes = Router::new()
+        .route("/orders", post(order_handlers::create_order))
+        .route("/orders/claim", post(order_handlers::claim_order))
+        .route("/orders", get(order_handlers::list_my_orders))
+        .route("/orders/{id}", get(order_handlers::get_my_order))
+        .route("/orders/{id}/payment/verify", get(order_handlers::verify_payment))
+        .route("/orders/{id}/payment-proof", post(order_handlers::upload_payment_proof))
+        .route("/me/addresses", post(address_handlers::create_address))
+        .route("/me/addresses", get(address_handlers::list_addresses))
+        .route("/me/addresses/{id}", get(address_handlers::get_address))
+        .route("/me/addresses/{id}", put(address_handlers::update_address))
+        .route("/me/addresses/{id}", delete(address_handlers::delete_address))
+        .route("/me/addresses/{id}/default", put(address_handlers::set_default_address))
+        .route("/products/{id}/alerts", post(product_alert_handlers::subscribe))
+        .route("/me/alerts", get(product_alert_handlers::list_alerts))
+        .route("/me/alerts/{id}", delete(product_alert_handlers::delete_alert))
+        .route("/me/wishlists", post(wishlist_handlers::create_wishlist))
+        .route("/me/wishlists", get(wishlist_handlers::list_wishlists))
+        .route("/me/wishlists/{id}", get(wishlist_handlers::get_wishlist))
+        .route("/me/wishlists/{id}", put(wishlist_handlers::rename_wishlist))
+        .route("/me/wishlists/{id}", delete(wishlist_handlers::delete_wishlist))
+        .route("/me/wishlists/{id}/items", post(wishlist_handlers::add_item))
+        .route("/me/wishlists/{id}/items/{product_id}", delete(wishlist_handlers::remove_item))
+        .route("/me/wishlists/{id}/share", post(wishlist_handlers::share_wishlist))
+        .route("/me/wishlists/{id}/share", delete(wishlist_handlers::unshare_wishlist))
+        .route("/me/wishlists/{id}/move-to-cart", post(wishlist_handlers::move_to_cart))
+        .route("/returns", post(return_handlers::create_return))
+        .route("/returns", get(return_handlers::list_my_returns))
+        .route("/returns/{id}", get(return_handlers::get_my_return))
+        .route("/returns/{id}/photos", post(return_handlers::upload_return_photos))
+        .layer(middleware::from_fn_with_state(jwt_secret.clone(), auth_middleware))
+        .layer(DefaultBodyLimit::max(limits.customer));  // photos and receipts
 
     // Admin routes (require authentication)
     let admin_routes = Router::new()
         .route("/admin/products", post(product_handlers::create_product))
         .route("/admin/products/{id}", put(product_handlers::update_product))
         .route("/admin/products/{id}", delete(product_handlers::delete_product))
-        .layer(middleware::from_fn_with_state(jwt_secret.clone(), auth_middleware));
+        .route("/admin/products/{id}/images", post(product_image_handlers::attach_image))
+        .route("/admin/products/{id}/images/order", put(product_image_handlers::reorder_images))
+        .route("/admin/products/{id}/images/{image_id}", put(product_image_handlers::update_image))
+        .route("/admin/products/{id}/images/{image_id}", delete(product_image_handlers::detach_image))
+        .route(
+            "/admin/products/{id}/images/{image_id}/cover",
+            put(product_image_handlers::set_cover_image),
+        )
+        .route("/admin/orders", get(order_handlers::list_orders))
+        .route("/admin/orders/{id}/status", put(order_handlers::update_order_status))
+        .route("/admin/orders/{id}/payment/confirm", put(order_handlers::confirm_payment))
+        .route("/admin/orders/{id}/payment/reject", put(order_handlers::reject_payment))
+        .route("/admin/coupons", post(coupon_handlers::create_coupon))
+        .route("/admin/coupons", get(coupon_handlers::list_coupons))
+        .route("/admin/coupons/{code}", get(coupon_handlers::get_coupon))
+        .route("/admin/coupons/{code}", put(coupon_handlers::update_coupon))
+        .route("/admin/coupons/{code}", delete(coupon_handlers::delete_coupon))
+        .route("/admin/promotions", post(promotion_handlers::create_promotion))
+        .route("/admin/promotions", get(promotion_handlers::list_promotions))
+        .route("/admin/promotions/{id}", get(promotion_handlers::get_promotion))
+        .route("/admin/promotions/{id}", put(promotion_handlers::update_promotion))
+        .route("/admin/promotions/{id}", delete(promotion_handlers::delete_promotion))
+        .route("/admin/products/{id}/price-history", get(product_handlers::get_price_history))
+        .route("/admin/products/{id}/price-changes", post(promotion_handlers::schedule_price_change))
+        .route("/admin/products/{id}/price-changes", get(promotion_handlers::list_price_changes))
+        .route("/admin/price-changes/{id}", delete(promotion_handlers::cancel_price_change))
+        .route("/admin/shipping-zones", post(shipping_handlers::create_zone))
+        .route("/admin/shipping-zones", get(shipping_handlers::list_zones))
+        .route("/admin/shipping-zones/{id}", put(shipping_handlers::update_zone))
+        .route("/admin/shipping-zones/{id}", delete(shipping_handlers::delete_zone))
+        .route("/admin/tax-rules", post(tax_handlers::create_rule))
+        .route("/admin/tax-rules", get(tax_handlers::list_rules))
+        .route("/admin/tax-rules/{id}", put(tax_handlers::update_rule))
+        .route("/admin/tax-rules/{id}", delete(tax_handlers::delete_rule))
+        .route("/admin/abandoned-carts/stats", get(abandoned_cart_handlers::recovery_stats))
+        .route("/admin/wishlist-counts", get(wishlist_handlers::product_counts))
+        .route("/admin/media", get(media_handlers::list_media))
+        .route("/admin/returns", get(return_handlers::list_returns))
+        .route("/admin/returns/{id}/approve", put(return_handlers::approve_return))
+        .route("/admin/returns/{id}/reject", put(return_handlers::reject_return))
+        .route("/admin/returns/{id}/receive", put(return_handlers::receive_return))
+        .route("/admin/returns/{id}/resolve", put(return_handlers::resolve_return))
+        .route("/admin/return-policies", get(return_handlers::list_return_policies))
+        .route("/admin/return-policies/{category}", put(return_handlers::upsert_return_policy))
+        .layer(middleware::from_fn_with_state(jwt_secret.clone(), auth_middleware))
+        .layer(DefaultBodyLimit::max(limits.admin));
+
+    // Probes for the orchestrator, outside /api
+    let health_routes = Router::new()
+        .route("/health/live", get(health_handlers::live))
+        .route("/health/ready", get(health_handlers::ready))
+        .route("/version", get(health_handlers::version));
 
      // Combine routes
-    Router::new()
+    let mut app = Router::new()
+        .merge(health_routes)
         .nest("/api", public_routes)
+        .nest("/api", shopping_routes)
         .nest("/api", upload_routes)
-        .nest("/api", admin_routes)
-        .with_state(state)
+        .nest("/api", direct_upload_routes)
+        .nest("/api", guest_upload_routes)
+        .nest("/api", customer_routes)
+        .nest("/api", admin_routes);
+
+    // Serve uploaded files when they are kept on local disk
+    if let Some(dir) = state.storage.public_dir() {
+        app = app.nest_service("/uploads", ServeDir::new(dir));
+    }
+
+    let cors = cors_layer(&state.config.cors);
+    app.layer(cors).with_state(state)
+}
+
+// Which cross-origin requests browsers may make
+fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let origins = if config.allow_any_origin {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(config.allowed_origins.clone())
+    };
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(config.allowed_methods.clone())
+        .allow_headers(config.allowed_headers.clone())
+        .allow_credentials(config.allow_credentials)
+        .max_age(config.max_age)
 }
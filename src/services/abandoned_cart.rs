@@ -0,0 +1,232 @@
+use crate::config::app::NotificationsConfig;
+use crate::models::cart::CartItem;
+use crate::models::cart_reminder::{CartReminder, RecoveryStats};
+use crate::models::notification::Notification;
+use crate::models::order::Order;
+use crate::models::user::User;
+use crate::services::cart::CartService;
+use crate::services::notification::NotificationService;
+use crate::utils::error::Result;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::StreamExt;
+use mongodb::bson::serde_helpers::chrono_datetime_as_bson_datetime;
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use serde::Deserialize;
+use std::str::FromStr;
+
+// An order placed this long after a reminder counts as recovered by it once paid
+const RECOVERY_WINDOW_DAYS: i64 = 7;
+
+// A customer's cart summed up by the aggregation in `find_idle_carts`
+#[derive(Debug, Deserialize)]
+struct IdleCart {
+    #[serde(rename = "_id")]
+    user_id: String,
+    #[serde(with = "chrono_datetime_as_bson_datetime")]
+    last_activity: DateTime<Utc>,
+    item_count: i64,
+    cart_value: f64,
+}
+
+pub struct AbandonedCartService;
+
+impl AbandonedCartService {
+    // Queue a reminder for every cart that has been idle long enough. Customers who ordered
+    // since, or were reminded about this cart or recently, are skipped. Returns how many
+    // reminders were queued.
+    pub async fn queue_reminders(
+        cart: &Collection<CartItem>,
+        orders: &Collection<Order>,
+        users: &Collection<User>,
+        reminders: &Collection<CartReminder>,
+        notifications: &Collection<Notification>,
+        config: &NotificationsConfig,
+    ) -> Result<u64> {
+        let now = Utc::now();
+        let idle_since = now - Duration::hours(config.cart_reminder_idle_hours);
+        let cooldown_since = now - Duration::days(config.cart_reminder_cooldown_days);
+
+        let mut queued = 0;
+        for idle in Self::find_idle_carts(cart, idle_since).await? {
+            // Cancelled or failed orders leave the customer still needing a nudge
+            let ordered = orders
+                .find_one(doc! {
+                    "user_id": &idle.user_id,
+                    "created_at": { "$gte": mongodb::bson::DateTime::from_chrono(idle.last_activity) },
+                    "payment_status": { "$in": ["pending", "completed"] },
+                    "order_status": { "$ne": "cancelled" },
+                })
+                .await?;
+            if ordered.is_some() {
+                continue;
+            }
+
+            // One reminder per cart, and none while the cooldown of the last one runs
+            let reminded = reminders
+                .find_one(doc! {
+                    "user_id": &idle.user_id,
+                    "$or": [
+                        { "sent_at": { "$gte": mongodb::bson::DateTime::from_chrono(cooldown_since) } },
+                        { "last_activity_at": mongodb::bson::DateTime::from_chrono(idle.last_activity) },
+                    ],
+                })
+                .await?;
+            if reminded.is_some() {
+                continue;
+            }
+
+            let Ok(user_id) = ObjectId::from_str(&idle.user_id) else {
+                continue;
+            };
+            let Some(user) = users.find_one(doc! { "_id": user_id }).await? else {
+                continue;
+            };
+
+            let items = CartService::get_items(cart, &idle.user_id).await?;
+
+            let greeting = user.full_name.as_deref().unwrap_or("there");
+            let mut body = format!("Hi {},\n\nYou left these items in your cart:\n", greeting);
+            for item in &items {
+                body.push_str(&format!("- {} x{}\n", item.product_name, item.quantity));
+            }
+            body.push_str("\nThey're still waiting for you, but stock can run out.");
+
+            NotificationService::queue(
+                notifications,
+                "abandoned_cart",
+                Some(&idle.user_id),
+                &user.email,
+                "You left something in your cart".to_string(),
+                body,
+            )
+            .await?;
+
+            let reminder = CartReminder {
+                id: None,
+                user_id: idle.user_id,
+                item_count: idle.item_count as i32,
+                cart_value: (idle.cart_value * 100.0).round() / 100.0,
+                last_activity_at: idle.last_activity,
+                sent_at: now,
+                recovered_order_id: None,
+                recovered_amount: 0.0,
+                recovered_at: None,
+            };
+            reminders.insert_one(&reminder).await?;
+
+            queued += 1;
+        }
+
+        Ok(queued)
+    }
+
+    // Credit a paid order to the latest reminder the customer got before placing it, if it
+    // is recent enough
+    pub async fn mark_recovered(reminders: &Collection<CartReminder>, order: &Order) -> Result<()> {
+        let (Some(user_id), Some(order_id)) = (&order.user_id, order.id) else {
+            return Ok(());
+        };
+        let window_start = order.created_at - Duration::days(RECOVERY_WINDOW_DAYS);
+
+        let reminder = reminders
+            .find_one(doc! {
+                "user_id": user_id,
+                "recovered_order_id": null,
+                "sent_at": {
+                    "$gte": mongodb::bson::DateTime::from_chrono(window_start),
+                    "$lte": mongodb::bson::DateTime::from_chrono(order.created_at),
+                },
+            })
+            .sort(doc! { "sent_at": -1 })
+            .await?;
+        let Some(reminder) = reminder else {
+            return Ok(());
+        };
+
+        reminders
+            .update_one(
+                doc! { "_id": reminder.id, "recovered_order_id": null },
+                doc! {
+                    "$set": {
+                        "recovered_order_id": order_id.to_hex(),
+                        "recovered_amount": order.total_amount,
+                        "recovered_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                    }
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // How many reminders sent in the last `days` days led to an order (admin)
+    pub async fn recovery_stats(
+        reminders: &Collection<CartReminder>,
+        days: i64,
+    ) -> Result<RecoveryStats> {
+        let since = Utc::now() - Duration::days(days);
+        let mut cursor = reminders
+            .find(doc! { "sent_at": { "$gte": mongodb::bson::DateTime::from_chrono(since) } })
+            .await?;
+
+        let mut stats = RecoveryStats {
+            days,
+            reminders_sent: 0,
+            carts_recovered: 0,
+            recovery_rate: 0.0,
+            reminded_value: 0.0,
+            recovered_revenue: 0.0,
+        };
+        while let Some(result) = cursor.next().await {
+            let reminder = result?;
+            stats.reminders_sent += 1;
+            stats.reminded_value += reminder.cart_value;
+            if reminder.recovered_order_id.is_some() {
+                stats.carts_recovered += 1;
+                stats.recovered_revenue += reminder.recovered_amount;
+            }
+        }
+
+        if stats.reminders_sent > 0 {
+            let rate = stats.carts_recovered as f64 * 100.0 / stats.reminders_sent as f64;
+            stats.recovery_rate = (rate * 100.0).round() / 100.0;
+        }
+        stats.reminded_value = (stats.reminded_value * 100.0).round() / 100.0;
+        stats.recovered_revenue = (stats.recovered_revenue * 100.0).round() / 100.0;
+
+        Ok(stats)
+    }
+
+    // Carts of signed in customers with no change since `idle_since`
+    async fn find_idle_carts(
+        cart: &Collection<CartItem>,
+        idle_since: DateTime<Utc>,
+    ) -> Result<Vec<IdleCart>> {
+        let pipeline = vec![
+            // Guest carts have no one to remind
+            doc! { "$match": { "user_id": { "$not": { "$regex": "^guest:" } } } },
+            doc! {
+                "$group": {
+                    "_id": "$user_id",
+                    "last_activity": { "$max": "$updated_at" },
+                    "item_count": { "$sum": "$quantity" },
+                    "cart_value": { "$sum": { "$multiply": ["$product_price", "$quantity"] } },
+                }
+            },
+            doc! {
+                "$match": {
+                    "last_activity": { "$lt": mongodb::bson::DateTime::from_chrono(idle_since) }
+                }
+            },
+        ];
+
+        let mut cursor = cart.aggregate(pipeline).with_type::<IdleCart>().await?;
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?);
+        }
+
+        Ok(results)
+    }
+}
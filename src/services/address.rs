@@ -0,0 +1,241 @@
+use crate::models::address::{SaveAddressRequest, SavedAddress, SavedAddressResponse};
+use crate::models::shipping::Address;
+use crate::services::shipping::ShippingService;
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use std::str::FromStr;
+
+const MAX_SAVED_ADDRESSES: u64 = 20;
+
+pub struct AddressService;
+
+impl AddressService {
+    // Save an address to the customer's address book
+    pub async fn create_address(
+        addresses: &Collection<SavedAddress>,
+        user_id: &str,
+        req: SaveAddressRequest,
+    ) -> Result<SavedAddressResponse> {
+        Self::validate(&req.address)?;
+
+        let existing = addresses.count_documents(doc! { "user_id": user_id }).await?;
+        if existing >= MAX_SAVED_ADDRESSES {
+            return Err(AppError::ValidationError(format!(
+                "Maximum {} saved addresses allowed",
+                MAX_SAVED_ADDRESSES
+            )));
+        }
+
+        // The first address becomes the default
+        let is_default = req.is_default.unwrap_or(false) || existing == 0;
+
+        let now = Utc::now();
+        let saved = SavedAddress {
+            id: None,
+            user_id: user_id.to_string(),
+            label: req.label,
+            address: req.address,
+            is_default: false,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let result = addresses.insert_one(&saved).await?;
+        let inserted_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| AppError::InternalError)?;
+
+        if is_default {
+            Self::make_default(addresses, user_id, inserted_id).await?;
+        }
+
+        Ok(Self::find_address(addresses, inserted_id, user_id).await?.to_response())
+    }
+
+    // List the customer's addresses, default first
+    pub async fn get_addresses(
+        addresses: &Collection<SavedAddress>,
+        user_id: &str,
+    ) -> Result<Vec<SavedAddressResponse>> {
+        let mut cursor = addresses
+            .find(doc! { "user_id": user_id })
+            .sort(doc! { "is_default": -1, "created_at": -1 })
+            .await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?.to_response());
+        }
+
+        Ok(results)
+    }
+
+    // Get one of the customer's addresses
+    pub async fn get_address(
+        addresses: &Collection<SavedAddress>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<SavedAddressResponse> {
+        let object_id = Self::parse_id(id)?;
+        Ok(Self::find_address(addresses, object_id, user_id).await?.to_response())
+    }
+
+    // Replace a saved address
+    pub async fn update_address(
+        addresses: &Collection<SavedAddress>,
+        id: &str,
+        user_id: &str,
+        req: SaveAddressRequest,
+    ) -> Result<SavedAddressResponse> {
+        let object_id = Self::parse_id(id)?;
+        Self::validate(&req.address)?;
+
+        let result = addresses
+            .update_one(
+                doc! { "_id": object_id, "user_id": user_id },
+                doc! {
+                    "$set": {
+                        "label": req.label,
+                        "address": mongodb::bson::to_bson(&req.address)?,
+                        "updated_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                    }
+                },
+            )
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(AppError::NotFound("Address not found".to_string()));
+        }
+
+        if req.is_default == Some(true) {
+            Self::make_default(addresses, user_id, object_id).await?;
+        }
+
+        Ok(Self::find_address(addresses, object_id, user_id).await?.to_response())
+    }
+
+    // Make an address the one used when checkout doesn't name one
+    pub async fn set_default(
+        addresses: &Collection<SavedAddress>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<SavedAddressResponse> {
+        let object_id = Self::parse_id(id)?;
+
+        // Fails if the address isn't the customer's
+        Self::find_address(addresses, object_id, user_id).await?;
+        Self::make_default(addresses, user_id, object_id).await?;
+
+        Ok(Self::find_address(addresses, object_id, user_id).await?.to_response())
+    }
+
+    // Delete a saved address, the newest remaining one takes over as default
+    pub async fn delete_address(
+        addresses: &Collection<SavedAddress>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        let object_id = Self::parse_id(id)?;
+
+        let deleted = addresses
+            .find_one_and_delete(doc! { "_id": object_id, "user_id": user_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Address not found".to_string()))?;
+
+        if deleted.is_default {
+            let next = addresses
+                .find_one(doc! { "user_id": user_id })
+                .sort(doc! { "created_at": -1 })
+                .await?;
+            if let Some(next_id) = next.and_then(|a| a.id) {
+                Self::make_default(addresses, user_id, next_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Address an order ships to: a saved one, a one-off one, or the default
+    pub async fn resolve_for_checkout(
+        addresses: &Collection<SavedAddress>,
+        user_id: &str,
+        address_id: Option<String>,
+        inline: Option<Address>,
+    ) -> Result<Address> {
+        match (address_id, inline) {
+            (Some(_), Some(_)) => Err(AppError::ValidationError(
+                "Send either address_id or shipping_address, not both".to_string(),
+            )),
+            (Some(id), None) => {
+                let object_id = Self::parse_id(&id)?;
+                Ok(Self::find_address(addresses, object_id, user_id).await?.address)
+            }
+            (None, Some(address)) => Ok(address),
+            (None, None) => addresses
+                .find_one(doc! { "user_id": user_id, "is_default": true })
+                .await?
+                .map(|saved| saved.address)
+                .ok_or_else(|| {
+                    AppError::ValidationError("A shipping address is required".to_string())
+                }),
+        }
+    }
+
+    async fn make_default(
+        addresses: &Collection<SavedAddress>,
+        user_id: &str,
+        id: ObjectId,
+    ) -> Result<()> {
+        addresses
+            .update_many(
+                doc! { "user_id": user_id, "_id": { "$ne": id }, "is_default": true },
+                doc! { "$set": { "is_default": false } },
+            )
+            .await?;
+        addresses
+            .update_one(
+                doc! { "_id": id, "user_id": user_id },
+                doc! { "$set": { "is_default": true } },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Saved addresses must have a phone number for the courier
+    fn validate(address: &Address) -> Result<()> {
+        ShippingService::validate_address(address)?;
+
+        let phone = address.phone.as_deref().unwrap_or_default();
+        let digits = phone.chars().filter(|c| c.is_ascii_digit()).count();
+        let allowed = phone
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | ' ' | '-'));
+        if !allowed || !(7..=15).contains(&digits) {
+            return Err(AppError::ValidationError(
+                "A valid phone number is required".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn find_address(
+        addresses: &Collection<SavedAddress>,
+        id: ObjectId,
+        user_id: &str,
+    ) -> Result<SavedAddress> {
+        addresses
+            .find_one(doc! { "_id": id, "user_id": user_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Address not found".to_string()))
+    }
+
+    fn parse_id(id: &str) -> Result<ObjectId> {
+        ObjectId::from_str(id).map_err(|_| AppError::ValidationError("Invalid address ID".to_string()))
+    }
+}
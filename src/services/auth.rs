@@ -51,6 +51,7 @@ impl AuthService {
             password_hash,
             full_name: req.full_name,
             role: "customer".to_string(),
+            store_credit: 0.0,
             created_at: Utc::now(),
         };
 
@@ -137,6 +138,7 @@ impl AuthService {
             email: user.email.clone(),
             full_name: user.full_name.clone(),
             role: user.role.clone(),
+            store_credit: user.store_credit,
         }
     }
 }
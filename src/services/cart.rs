@@ -0,0 +1,367 @@
+use crate::models::cart::{AddToCartRequest, CartItem, CartMergeLine, CartResponse, CartWarning};
+use crate::models::product::Product;
+use crate::models::promotion::Promotion;
+use crate::services::promotion::PromotionService;
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+pub struct CartService;
+
+impl CartService {
+    // Add a product to the cart, or increase its quantity if already present
+    pub async fn add_to_cart(
+        cart: &Collection<CartItem>,
+        products: &Collection<Product>,
+        active: &[Promotion],
+        user_id: &str,
+        req: AddToCartRequest,
+    ) -> Result<CartResponse> {
+        if req.quantity <= 0 {
+            return Err(AppError::ValidationError(
+                "Quantity must be greater than zero".to_string(),
+            ));
+        }
+
+        let product = Self::find_product(products, &req.product_id).await?;
+
+        let existing = cart
+            .find_one(doc! { "user_id": user_id, "product_id": &req.product_id })
+            .await?;
+        let in_cart = existing.as_ref().map(|i| i.quantity).unwrap_or(0);
+
+        if in_cart + req.quantity > product.stock_quantity {
+            return Err(AppError::ValidationError(format!(
+                "Only {} of {} in stock",
+                product.stock_quantity, product.name
+            )));
+        }
+
+        let price = Self::effective_price(active, &product);
+
+        let now = Utc::now();
+        match existing {
+            Some(item) => {
+                cart.update_one(
+                    doc! { "_id": item.id },
+                    doc! {
+                        "$inc": { "quantity": req.quantity },
+                        "$set": {
+                            "product_name": &product.name,
+                            "product_price": price,
+                            "updated_at": mongodb::bson::DateTime::from_millis(now.timestamp_millis()),
+                        },
+                    },
+                )
+                .await?;
+            }
+            None => {
+                let item = CartItem {
+                    id: None,
+                    user_id: user_id.to_string(),
+                    product_id: req.product_id,
+                    product_name: product.name,
+                    product_price: price,
+                    quantity: req.quantity,
+                    created_at: now,
+                    updated_at: now,
+                };
+                cart.insert_one(item).await?;
+            }
+        }
+
+        Self::get_cart(cart, user_id).await
+    }
+
+    // Set the quantity of a product already in the cart
+    pub async fn update_quantity(
+        cart: &Collection<CartItem>,
+        products: &Collection<Product>,
+        user_id: &str,
+        product_id: &str,
+        quantity: i32,
+    ) -> Result<CartResponse> {
+        if quantity <= 0 {
+            return Self::remove_item(cart, user_id, product_id).await;
+        }
+
+        let product = Self::find_product(products, product_id).await?;
+        if quantity > product.stock_quantity {
+            return Err(AppError::ValidationError(format!(
+                "Only {} of {} in stock",
+                product.stock_quantity, product.name
+            )));
+        }
+
+        let result = cart
+            .update_one(
+                doc! { "user_id": user_id, "product_id": product_id },
+                doc! {
+                    "$set": {
+                        "quantity": quantity,
+                        "updated_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                    },
+                },
+            )
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(AppError::NotFound("Product not in cart".to_string()));
+        }
+
+        Self::get_cart(cart, user_id).await
+    }
+
+    // Remove a product from the cart
+    pub async fn remove_item(
+        cart: &Collection<CartItem>,
+        user_id: &str,
+        product_id: &str,
+    ) -> Result<CartResponse> {
+        let result = cart
+            .delete_one(doc! { "user_id": user_id, "product_id": product_id })
+            .await?;
+
+        if result.deleted_count == 0 {
+            return Err(AppError::NotFound("Product not in cart".to_string()));
+        }
+
+        Self::get_cart(cart, user_id).await
+    }
+
+    // Get the cart with totals
+    pub async fn get_cart(cart: &Collection<CartItem>, user_id: &str) -> Result<CartResponse> {
+        let items = Self::get_items(cart, user_id).await?;
+
+        let item_count = items.iter().map(|i| i.quantity).sum();
+        let subtotal = items
+            .iter()
+            .map(|i| i.product_price * i.quantity as f64)
+            .sum();
+
+        Ok(CartResponse {
+            items: items.iter().map(|i| i.to_response()).collect(),
+            item_count,
+            subtotal,
+            discount: None,
+            coupon_message: None,
+            tax_amount: 0.0,
+            tax_lines: Vec::new(),
+            total: subtotal,
+            warnings: Vec::new(),
+        })
+    }
+
+    // Get the raw cart items of a user
+    pub async fn get_items(cart: &Collection<CartItem>, user_id: &str) -> Result<Vec<CartItem>> {
+        let mut cursor = cart
+            .find(doc! { "user_id": user_id })
+            .sort(doc! { "created_at": 1 })
+            .await?;
+
+        let mut items = Vec::new();
+        while let Some(result) = cursor.next().await {
+            items.push(result?);
+        }
+
+        Ok(items)
+    }
+
+    // Move the items of a guest cart into a customer's cart. Quantities of products in both
+    // are summed, and capped at what is in stock.
+    pub async fn merge_carts(
+        cart: &Collection<CartItem>,
+        products: &Collection<Product>,
+        guest_key: &str,
+        user_id: &str,
+    ) -> Result<Vec<CartMergeLine>> {
+        let guest_items = Self::get_items(cart, guest_key).await?;
+
+        let mut lines = Vec::new();
+        for item in guest_items {
+            let product = match Self::find_product(products, &item.product_id).await {
+                Ok(product) => Some(product),
+                Err(AppError::NotFound(_)) | Err(AppError::ValidationError(_)) => None,
+                Err(e) => return Err(e),
+            };
+
+            let existing = cart
+                .find_one(doc! { "user_id": user_id, "product_id": &item.product_id })
+                .await?;
+            let in_cart = existing.as_ref().map(|i| i.quantity).unwrap_or(0);
+
+            let available = product
+                .as_ref()
+                .map(|p| (p.stock_quantity - in_cart).max(0))
+                .unwrap_or(0);
+            let added = item.quantity.min(available);
+
+            let status = if added == item.quantity {
+                "added"
+            } else if added > 0 {
+                "capped"
+            } else {
+                "unavailable"
+            };
+            lines.push(CartMergeLine {
+                product_id: item.product_id.clone(),
+                product_name: item.product_name.clone(),
+                requested: item.quantity,
+                added,
+                status: status.to_string(),
+            });
+
+            if added == 0 {
+                continue;
+            }
+
+            // Price snapshots are kept, revalidating the cart reports what changed since
+            let now = Utc::now();
+            match existing {
+                Some(existing) => {
+                    cart.update_one(
+                        doc! { "_id": existing.id },
+                        doc! {
+                            "$inc": { "quantity": added },
+                            "$set": {
+                                "updated_at": mongodb::bson::DateTime::from_millis(now.timestamp_millis()),
+                            },
+                        },
+                    )
+                    .await?;
+                }
+                None => {
+                    let merged = CartItem {
+                        id: None,
+                        user_id: user_id.to_string(),
+                        product_id: item.product_id,
+                        product_name: item.product_name,
+                        product_price: item.product_price,
+                        quantity: added,
+                        created_at: now,
+                        updated_at: now,
+                    };
+                    cart.insert_one(merged).await?;
+                }
+            }
+        }
+
+        Self::clear_cart(cart, guest_key).await?;
+
+        Ok(lines)
+    }
+
+    // Bring the cart's name and price snapshots up to date with the current products.
+    // Returns warnings for price changes since the customer last saw the cart, and for
+    // products that were deleted or are short of stock.
+    pub async fn revalidate(
+        cart: &Collection<CartItem>,
+        products: &Collection<Product>,
+        active: &[Promotion],
+        user_id: &str,
+    ) -> Result<Vec<CartWarning>> {
+        let items = Self::get_items(cart, user_id).await?;
+
+        let ids: Vec<ObjectId> = items
+            .iter()
+            .filter_map(|i| ObjectId::from_str(&i.product_id).ok())
+            .collect();
+        let mut current: HashMap<String, Product> = HashMap::new();
+        let mut cursor = products.find(doc! { "_id": { "$in": ids } }).await?;
+        while let Some(result) = cursor.next().await {
+            let product = result?;
+            if let Some(id) = product.id {
+                current.insert(id.to_hex(), product);
+            }
+        }
+
+        let mut warnings = Vec::new();
+        for item in items {
+            let warning = |issue: &str, message: String| CartWarning {
+                product_id: item.product_id.clone(),
+                product_name: item.product_name.clone(),
+                issue: issue.to_string(),
+                message,
+            };
+
+            let Some(product) = current.get(&item.product_id) else {
+                warnings.push(warning(
+                    "removed",
+                    format!("{} is no longer sold", item.product_name),
+                ));
+                continue;
+            };
+
+            if product.stock_quantity <= 0 {
+                warnings.push(warning("out_of_stock", format!("{} is out of stock", product.name)));
+            } else if item.quantity > product.stock_quantity {
+                warnings.push(warning(
+                    "low_stock",
+                    format!("Only {} of {} left in stock", product.stock_quantity, product.name),
+                ));
+            }
+
+            let price = Self::effective_price(active, product);
+            if Self::same_price(price, item.product_price) && product.name == item.product_name {
+                continue;
+            }
+
+            if !Self::same_price(price, item.product_price) {
+                warnings.push(warning(
+                    "price_changed",
+                    format!(
+                        "The price of {} changed from {:.2} to {:.2}",
+                        product.name, item.product_price, price
+                    ),
+                ));
+            }
+
+            // Each change is reported once
+            cart.update_one(
+                doc! { "_id": item.id },
+                doc! {
+                    "$set": {
+                        "product_name": &product.name,
+                        "product_price": price,
+                        "updated_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                    },
+                },
+            )
+            .await?;
+        }
+
+        Ok(warnings)
+    }
+
+    // Prices closer than half a cent are the same
+    pub fn same_price(a: f64, b: f64) -> bool {
+        (a - b).abs() < 0.005
+    }
+
+    // Empty the cart
+    pub async fn clear_cart(cart: &Collection<CartItem>, user_id: &str) -> Result<()> {
+        cart.delete_many(doc! { "user_id": user_id }).await?;
+        Ok(())
+    }
+
+    fn effective_price(active: &[Promotion], product: &Product) -> f64 {
+        let id = product.id.map(|id| id.to_hex()).unwrap_or_default();
+        PromotionService::best_offer(active, &id, &product.category, product.price)
+            .map(|(_, price)| price)
+            .unwrap_or(product.price)
+    }
+
+    async fn find_product(products: &Collection<Product>, id: &str) -> Result<Product> {
+        let object_id = ObjectId::from_str(id)
+            .map_err(|_| AppError::ValidationError("Invalid product ID".to_string()))?;
+
+        products
+            .find_one(doc! { "_id": object_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Product not found".to_string()))
+    }
+}
@@ -0,0 +1,488 @@
+use crate::models::cart::CartResponse;
+use crate::models::coupon::{
+    AppliedCoupon, Coupon, CouponRedemption, CouponResponse, CreateCouponRequest, DiscountLine,
+    UpdateCouponRequest,
+};
+use crate::models::product::Product;
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::Collection;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// A priced line of a cart or order the discount is computed on
+pub struct DiscountableItem {
+    pub product_id: String,
+    pub price: f64,
+    pub quantity: i32,
+}
+
+pub struct CouponService;
+
+impl CouponService {
+    // Create a coupon (admin)
+    pub async fn create_coupon(
+        coupons: &Collection<Coupon>,
+        req: CreateCouponRequest,
+    ) -> Result<CouponResponse> {
+        let code = Self::normalize_code(&req.code);
+        if code.is_empty() {
+            return Err(AppError::ValidationError("Coupon code is required".to_string()));
+        }
+        Self::validate_value(&req.discount_type, req.value)?;
+
+        if coupons.find_one(doc! { "code": &code }).await?.is_some() {
+            return Err(AppError::ValidationError(
+                "A coupon with this code already exists".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let coupon = Coupon {
+            id: None,
+            code: code.clone(),
+            description: req.description,
+            discount_type: req.discount_type,
+            value: req.value,
+            min_order_value: req.min_order_value,
+            usage_limit: req.usage_limit,
+            per_user_limit: req.per_user_limit,
+            used_count: 0,
+            starts_at: req.starts_at,
+            ends_at: req.ends_at,
+            categories: req.categories,
+            product_ids: req.product_ids,
+            active: true,
+            created_at: now,
+            updated_at: now,
+        };
+
+        coupons.insert_one(coupon).await?;
+
+        Self::get_coupon(coupons, &code).await
+    }
+
+    // List coupons (admin)
+    pub async fn get_coupons(coupons: &Collection<Coupon>) -> Result<Vec<CouponResponse>> {
+        let mut cursor = coupons.find(doc! {}).sort(doc! { "created_at": -1 }).await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?.to_response());
+        }
+
+        Ok(results)
+    }
+
+    // Get a coupon by code
+    pub async fn get_coupon(coupons: &Collection<Coupon>, code: &str) -> Result<CouponResponse> {
+        let coupon = coupons
+            .find_one(doc! { "code": Self::normalize_code(code) })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Coupon not found".to_string()))?;
+
+        Ok(coupon.to_response())
+    }
+
+    // Update a coupon (admin)
+    pub async fn update_coupon(
+        coupons: &Collection<Coupon>,
+        code: &str,
+        req: UpdateCouponRequest,
+    ) -> Result<CouponResponse> {
+        let code = Self::normalize_code(code);
+        let existing = coupons
+            .find_one(doc! { "code": &code })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Coupon not found".to_string()))?;
+
+        let mut update_doc = Document::new();
+
+        if let Some(description) = req.description {
+            update_doc.insert("description", description);
+        }
+        if let Some(value) = req.value {
+            Self::validate_value(&existing.discount_type, value)?;
+            update_doc.insert("value", value);
+        }
+        if let Some(min_order_value) = req.min_order_value {
+            update_doc.insert("min_order_value", min_order_value);
+        }
+        if let Some(usage_limit) = req.usage_limit {
+            update_doc.insert("usage_limit", usage_limit);
+        }
+        if let Some(per_user_limit) = req.per_user_limit {
+            update_doc.insert("per_user_limit", per_user_limit);
+        }
+        if let Some(starts_at) = req.starts_at {
+            update_doc.insert("starts_at", mongodb::bson::DateTime::from_chrono(starts_at));
+        }
+        if let Some(ends_at) = req.ends_at {
+            update_doc.insert("ends_at", mongodb::bson::DateTime::from_chrono(ends_at));
+        }
+        if let Some(categories) = req.categories {
+            update_doc.insert("categories", categories);
+        }
+        if let Some(product_ids) = req.product_ids {
+            update_doc.insert("product_ids", product_ids);
+        }
+        if let Some(active) = req.active {
+            update_doc.insert("active", active);
+        }
+
+        update_doc.insert("updated_at", mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()));
+
+        coupons
+            .update_one(doc! { "code": &code }, doc! { "$set": update_doc })
+            .await?;
+
+        Self::get_coupon(coupons, &code).await
+    }
+
+    // Delete a coupon (admin)
+    pub async fn delete_coupon(coupons: &Collection<Coupon>, code: &str) -> Result<()> {
+        let result = coupons
+            .delete_one(doc! { "code": Self::normalize_code(code) })
+            .await?;
+
+        if result.deleted_count == 0 {
+            return Err(AppError::NotFound("Coupon not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Check that a code can be used by the customer on these items and compute the discount
+    pub async fn evaluate(
+        coupons: &Collection<Coupon>,
+        redemptions: &Collection<CouponRedemption>,
+        products: &Collection<Product>,
+        code: &str,
+        user_id: &str,
+        items: &[DiscountableItem],
+    ) -> Result<(Coupon, DiscountLine)> {
+        let coupon = coupons
+            .find_one(doc! { "code": Self::normalize_code(code), "active": true })
+            .await?
+            .ok_or_else(|| AppError::ValidationError("Invalid coupon code".to_string()))?;
+
+        let now = Utc::now();
+        if coupon.starts_at.is_some_and(|starts_at| starts_at > now) {
+            return Err(AppError::ValidationError(
+                "This coupon is not active yet".to_string(),
+            ));
+        }
+        if coupon.ends_at.is_some_and(|ends_at| ends_at < now) {
+            return Err(AppError::ValidationError("This coupon has expired".to_string()));
+        }
+        if coupon.usage_limit.is_some_and(|limit| coupon.used_count >= limit) {
+            return Err(AppError::ValidationError(
+                "This coupon has reached its usage limit".to_string(),
+            ));
+        }
+        if let Some(limit) = coupon.per_user_limit {
+            let used = redemptions
+                .count_documents(doc! { "code": &coupon.code, "user_id": user_id })
+                .await?;
+            if used >= limit.max(0) as u64 {
+                return Err(AppError::ValidationError(
+                    "You have already used this coupon".to_string(),
+                ));
+            }
+        }
+
+        let subtotal: f64 = items.iter().map(|i| i.price * i.quantity as f64).sum();
+        if let Some(min_order_value) = coupon.min_order_value {
+            if subtotal < min_order_value {
+                return Err(AppError::ValidationError(format!(
+                    "Orders must be at least {:.2} to use this coupon",
+                    min_order_value
+                )));
+            }
+        }
+
+        let eligible = Self::eligible_subtotal(products, &coupon, items).await?;
+        if eligible <= 0.0 {
+            return Err(AppError::ValidationError(
+                "This coupon does not apply to any item in your cart".to_string(),
+            ));
+        }
+
+        let amount = match coupon.discount_type.as_str() {
+            "percentage" => eligible * coupon.value / 100.0,
+            _ => coupon.value.min(eligible),
+        };
+        let amount = (amount * 100.0).round() / 100.0;
+
+        let line = DiscountLine {
+            code: coupon.code.clone(),
+            description: coupon.description.clone(),
+            amount,
+        };
+
+        Ok((coupon, line))
+    }
+
+    // Count one use of the coupon, failing if a limit was reached in the meantime.
+    // Returns the redemption id so it can be linked to the order or released.
+    pub async fn redeem(
+        coupons: &Collection<Coupon>,
+        redemptions: &Collection<CouponRedemption>,
+        coupon: &Coupon,
+        user_id: &str,
+        discount_amount: f64,
+    ) -> Result<ObjectId> {
+        let mut filter = doc! { "_id": coupon.id, "active": true };
+        if let Some(limit) = coupon.usage_limit {
+            filter.insert("used_count", doc! { "$lt": limit });
+        }
+
+        let result = coupons
+            .update_one(filter, doc! { "$inc": { "used_count": 1 } })
+            .await?;
+        if result.modified_count == 0 {
+            return Err(AppError::ValidationError(
+                "This coupon has reached its usage limit".to_string(),
+            ));
+        }
+
+        let redemption = CouponRedemption {
+            id: None,
+            code: coupon.code.clone(),
+            user_id: user_id.to_string(),
+            order_id: None,
+            discount_amount,
+            created_at: Utc::now(),
+        };
+        let inserted = redemptions.insert_one(redemption).await?;
+        let redemption_id = inserted
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| AppError::InternalError)?;
+
+        // Concurrent checkouts by the same customer both insert, then both see the excess
+        if let Some(limit) = coupon.per_user_limit {
+            let used = redemptions
+                .count_documents(doc! { "code": &coupon.code, "user_id": user_id })
+                .await?;
+            if used > limit.max(0) as u64 {
+                Self::release(coupons, redemptions, redemption_id).await?;
+                return Err(AppError::ValidationError(
+                    "You have already used this coupon".to_string(),
+                ));
+            }
+        }
+
+        Ok(redemption_id)
+    }
+
+    // Link a redemption to the order it was used on
+    pub async fn attach_order(
+        redemptions: &Collection<CouponRedemption>,
+        redemption_id: ObjectId,
+        order_id: &str,
+    ) -> Result<()> {
+        redemptions
+            .update_one(
+                doc! { "_id": redemption_id },
+                doc! { "$set": { "order_id": order_id } },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Give back a use of the coupon when checkout did not go through
+    pub async fn release(
+        coupons: &Collection<Coupon>,
+        redemptions: &Collection<CouponRedemption>,
+        redemption_id: ObjectId,
+    ) -> Result<()> {
+        Self::release_where(coupons, redemptions, doc! { "_id": redemption_id }).await
+    }
+
+    // Give back the use of a coupon made by an order that was cancelled before it was paid
+    pub async fn release_for_order(
+        coupons: &Collection<Coupon>,
+        redemptions: &Collection<CouponRedemption>,
+        order_id: &str,
+    ) -> Result<()> {
+        Self::release_where(coupons, redemptions, doc! { "order_id": order_id }).await
+    }
+
+    async fn release_where(
+        coupons: &Collection<Coupon>,
+        redemptions: &Collection<CouponRedemption>,
+        filter: Document,
+    ) -> Result<()> {
+        if let Some(redemption) = redemptions.find_one_and_delete(filter).await? {
+            coupons
+                .update_one(
+                    doc! { "code": &redemption.code },
+                    doc! { "$inc": { "used_count": -1 } },
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // Remember the code on the customer's cart
+    pub async fn apply_to_cart(
+        applied: &Collection<AppliedCoupon>,
+        user_id: &str,
+        code: &str,
+    ) -> Result<()> {
+        applied
+            .update_one(
+                doc! { "user_id": user_id },
+                doc! {
+                    "$set": {
+                        "code": Self::normalize_code(code),
+                        "applied_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                    }
+                },
+            )
+            .upsert(true)
+            .await?;
+
+        Ok(())
+    }
+
+    // Take the code off the customer's cart
+    pub async fn remove_from_cart(applied: &Collection<AppliedCoupon>, user_id: &str) -> Result<()> {
+        applied.delete_one(doc! { "user_id": user_id }).await?;
+        Ok(())
+    }
+
+    // Carry the code applied to one cart over to another that has none. Returns the code
+    // carried over.
+    pub async fn move_applied(
+        applied: &Collection<AppliedCoupon>,
+        from_user_id: &str,
+        to_user_id: &str,
+    ) -> Result<Option<String>> {
+        let Some(code) = Self::get_applied_code(applied, from_user_id).await? else {
+            return Ok(None);
+        };
+        Self::remove_from_cart(applied, from_user_id).await?;
+
+        if Self::get_applied_code(applied, to_user_id).await?.is_some() {
+            return Ok(None);
+        }
+        Self::apply_to_cart(applied, to_user_id, &code).await?;
+
+        Ok(Some(code))
+    }
+
+    // Code currently applied to the customer's cart
+    pub async fn get_applied_code(
+        applied: &Collection<AppliedCoupon>,
+        user_id: &str,
+    ) -> Result<Option<String>> {
+        Ok(applied
+            .find_one(doc! { "user_id": user_id })
+            .await?
+            .map(|a| a.code))
+    }
+
+    // Add the discount line of the applied coupon to a cart
+    pub async fn price_cart(
+        coupons: &Collection<Coupon>,
+        redemptions: &Collection<CouponRedemption>,
+        applied: &Collection<AppliedCoupon>,
+        products: &Collection<Product>,
+        user_id: &str,
+        cart: &mut CartResponse,
+    ) -> Result<()> {
+        let Some(code) = Self::get_applied_code(applied, user_id).await? else {
+            return Ok(());
+        };
+
+        let items: Vec<DiscountableItem> = cart
+            .items
+            .iter()
+            .map(|i| DiscountableItem {
+                product_id: i.product_id.clone(),
+                price: i.product_price,
+                quantity: i.quantity,
+            })
+            .collect();
+
+        match Self::evaluate(coupons, redemptions, products, &code, user_id, &items).await {
+            Ok((_, line)) => {
+                cart.total = cart.subtotal - line.amount;
+                cart.discount = Some(line);
+            }
+            // Keep the code applied, the cart may become eligible again
+            Err(AppError::ValidationError(message)) => {
+                cart.coupon_message = Some(format!("{}: {}", code, message));
+            }
+            Err(e) => return Err(e),
+        }
+
+        Ok(())
+    }
+
+    // Subtotal of the items the coupon is restricted to
+    async fn eligible_subtotal(
+        products: &Collection<Product>,
+        coupon: &Coupon,
+        items: &[DiscountableItem],
+    ) -> Result<f64> {
+        let product_ids = coupon.product_ids.as_deref().unwrap_or_default();
+        let categories = coupon.categories.as_deref().unwrap_or_default();
+
+        if product_ids.is_empty() && categories.is_empty() {
+            return Ok(items.iter().map(|i| i.price * i.quantity as f64).sum());
+        }
+
+        let mut item_categories: HashMap<String, String> = HashMap::new();
+        if !categories.is_empty() {
+            let ids: Vec<ObjectId> = items
+                .iter()
+                .filter_map(|i| ObjectId::from_str(&i.product_id).ok())
+                .collect();
+            let mut cursor = products.find(doc! { "_id": { "$in": ids } }).await?;
+            while let Some(result) = cursor.next().await {
+                let product = result?;
+                if let Some(id) = product.id {
+                    item_categories.insert(id.to_hex(), product.category);
+                }
+            }
+        }
+
+        Ok(items
+            .iter()
+            .filter(|i| {
+                product_ids.contains(&i.product_id)
+                    || item_categories
+                        .get(&i.product_id)
+                        .is_some_and(|category| categories.contains(category))
+            })
+            .map(|i| i.price * i.quantity as f64)
+            .sum())
+    }
+
+    fn validate_value(discount_type: &str, value: f64) -> Result<()> {
+        match discount_type {
+            "percentage" if value > 0.0 && value <= 100.0 => Ok(()),
+            "percentage" => Err(AppError::ValidationError(
+                "Percentage must be between 0 and 100".to_string(),
+            )),
+            "fixed" if value > 0.0 => Ok(()),
+            "fixed" => Err(AppError::ValidationError(
+                "Fixed discount must be greater than zero".to_string(),
+            )),
+            _ => Err(AppError::ValidationError(
+                "Discount type must be either percentage or fixed".to_string(),
+            )),
+        }
+    }
+
+    fn normalize_code(code: &str) -> String {
+        code.trim().to_uppercase()
+    }
+}
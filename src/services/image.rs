@@ -0,0 +1,248 @@
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::services::storage::ObjectStorage;
+use crate::utils::error::{AppError, Result};
+
+/// Largest source image we agree to decode
+const MAX_SOURCE_DIMENSION: u32 = 10_000;
+/// Memory a single decode may use, which rules out the largest dimensions in RGBA
+/// (10k x 10k would take 400MB)
+const MAX_DECODE_BYTES: u64 = 128 * 1024 * 1024;
+/// Bounding box of each variant, the large one is also the max size we keep
+const VARIANTS: [(&str, u32); 3] = [("thumbnail", 200), ("medium", 800), ("large", 1600)];
+const JPEG_QUALITY: u8 = 85;
+const WEBP_QUALITY: f32 = 80.0;
+
+/// One encoded size of an uploaded image
+struct EncodedVariant {
+    name: &'static str,
+    width: u32,
+    height: u32,
+    content_type: &'static str,
+    extension: &'static str,
+    data: Vec<u8>,
+    webp: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageVariantUrls {
+    pub url: String,
+    pub webp_url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// URLs of every size of a processed upload
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadedImage {
+    pub url: String,  // large variant
+    pub thumbnail: ImageVariantUrls,
+    pub medium: ImageVariantUrls,
+    pub large: ImageVariantUrls,
+}
+
+impl UploadedImage {
+    // URLs of every stored file
+    pub fn files(&self) -> Vec<String> {
+        [&self.thumbnail, &self.medium, &self.large]
+            .iter()
+            .flat_map(|variant| [variant.url.clone(), variant.webp_url.clone()])
+            .collect()
+    }
+}
+
+pub struct ImageService;
+
+impl ImageService {
+    // MIME type of an image read from its magic bytes, the client's content type isn't trusted
+    pub fn sniff_content_type(data: &[u8]) -> Result<&'static str> {
+        match image::guess_format(data) {
+            Ok(ImageFormat::Jpeg) => Ok("image/jpeg"),
+            Ok(ImageFormat::Png) => Ok("image/png"),
+            Ok(ImageFormat::Gif) => Ok("image/gif"),
+            Ok(ImageFormat::WebP) => Ok("image/webp"),
+            _ => Err(AppError::ValidationError(
+                "Only JPEG, PNG, GIF and WebP images are allowed".to_string(),
+            )),
+        }
+    }
+
+    // Decode, resize to every variant and store them all (plus WebP copies) under one folder
+    pub async fn upload_variants(
+        storage: &dyn ObjectStorage,
+        data: Vec<u8>,
+        folder: &str,
+    ) -> Result<UploadedImage> {
+        let variants = run_blocking(move || {
+            let image = decode(&data)?;
+            VARIANTS
+                .iter()
+                .map(|(name, size)| encode_variant(&image, name, *size))
+                .collect::<Result<Vec<_>>>()
+        })
+        .await?;
+
+        let base = format!("{}/{}", folder, Uuid::new_v4());
+        let mut stored = Vec::new();
+        let mut urls = Vec::new();
+        for variant in variants {
+            let key = format!("{}/{}.{}", base, variant.name, variant.extension);
+            let webp_key = format!("{}/{}.webp", base, variant.name);
+
+            let put = async {
+                let url = storage.put(&key, variant.data, variant.content_type).await?;
+                stored.push(url.clone());
+                let webp_url = storage.put(&webp_key, variant.webp, "image/webp").await?;
+                stored.push(webp_url.clone());
+                Ok::<_, AppError>((url, webp_url))
+            };
+
+            // Don't leave the variants stored so far behind
+            let (url, webp_url) = match put.await {
+                Ok(urls) => urls,
+                Err(e) => {
+                    for url in &stored {
+                        if let Err(e) = storage.delete(url).await {
+                            tracing::error!("Could not clean up {}: {:?}", url, e);
+                        }
+                    }
+                    return Err(e);
+                }
+            };
+
+            urls.push(ImageVariantUrls {
+                url,
+                webp_url,
+                width: variant.width,
+                height: variant.height,
+            });
+        }
+
+        let [thumbnail, medium, large]: [ImageVariantUrls; 3] =
+            urls.try_into().map_err(|_| AppError::InternalError)?;
+
+        Ok(UploadedImage {
+            url: large.url.clone(),
+            thumbnail,
+            medium,
+            large,
+        })
+    }
+
+    // Re-encode images that only need one size (receipts, return photos), dropping metadata
+    pub async fn sanitize(data: Vec<u8>) -> Result<(Vec<u8>, String)> {
+        run_blocking(move || {
+            let image = decode(&data)?;
+            let (_, large) = VARIANTS[VARIANTS.len() - 1];
+            let resized = fit(&image, large);
+            let (encoded, content_type, _) = encode(&resized)?;
+
+            Ok((encoded, content_type.to_string()))
+        })
+        .await
+    }
+}
+
+// Image decoding and encoding is CPU bound, keep it off the async workers
+async fn run_blocking<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await.map_err(|e| {
+        tracing::error!("Image processing task failed: {:?}", e);
+        AppError::InternalError
+    })?
+}
+
+// Decode an image with its EXIF orientation applied. The metadata itself is
+// dropped since we re-encode from pixels.
+fn decode(data: &[u8]) -> Result<DynamicImage> {
+    ImageService::sniff_content_type(data)?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_BYTES);
+
+    let invalid = |e: image::ImageError| {
+        tracing::warn!("Rejected image upload: {:?}", e);
+        AppError::ValidationError("File is not a valid image or is too large".to_string())
+    };
+
+    let mut reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|_| AppError::InternalError)?;
+    reader.limits(limits.clone());
+
+    let mut decoder = reader.into_decoder().map_err(invalid)?;
+    // `into_decoder` doesn't count the pixel buffer against `max_alloc`, unlike `decode`
+    limits.reserve(decoder.total_bytes()).map_err(invalid)?;
+    let orientation = decoder.orientation().map_err(invalid)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(invalid)?;
+    image.apply_orientation(orientation);
+
+    Ok(image)
+}
+
+// Shrink an image to fit a square box, never upscaling
+fn fit(image: &DynamicImage, size: u32) -> DynamicImage {
+    if image.width() <= size && image.height() <= size {
+        image.clone()
+    } else {
+        image.resize(size, size, image::imageops::FilterType::Lanczos3)
+    }
+}
+
+fn encode_variant(image: &DynamicImage, name: &'static str, size: u32) -> Result<EncodedVariant> {
+    let resized = fit(image, size);
+    let (data, content_type, extension) = encode(&resized)?;
+
+    // Lossy like the JPEG variant, lossless WebP of a photo is often bigger than the JPEG
+    let rgba = resized.to_rgba8();
+    let webp = webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height())
+        .encode_simple(false, WEBP_QUALITY)
+        .map_err(|e| {
+            tracing::error!("WebP encoding failed: {:?}", e);
+            AppError::InternalError
+        })?
+        .to_vec();
+
+    Ok(EncodedVariant {
+        name,
+        width: resized.width(),
+        height: resized.height(),
+        content_type,
+        extension,
+        data,
+        webp,
+    })
+}
+
+// PNG for images with transparency, JPEG for everything else
+fn encode(image: &DynamicImage) -> Result<(Vec<u8>, &'static str, &'static str)> {
+    let mut data = Vec::new();
+
+    if image.color().has_alpha() {
+        image
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+            .map_err(encode_error)?;
+        Ok((data, "image/png", "png"))
+    } else {
+        image
+            .to_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut data, JPEG_QUALITY))
+            .map_err(encode_error)?;
+        Ok((data, "image/jpeg", "jpg"))
+    }
+}
+
+fn encode_error(e: image::ImageError) -> AppError {
+    tracing::error!("Image encoding error: {:?}", e);
+    AppError::InternalError
+}
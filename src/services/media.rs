@@ -0,0 +1,248 @@
+use chrono::{Duration, Utc};
+use futures_util::StreamExt;
+use mongodb::bson::{doc, Document};
+use mongodb::Collection;
+
+use crate::config::app::MediaConfig;
+use crate::models::media::{Media, MediaFilter, MediaReference, MediaResponse};
+use crate::services::storage::ObjectStorage;
+use crate::utils::error::{AppError, Result};
+
+const SWEEP_BATCH: usize = 100;
+
+pub struct MediaService;
+
+impl MediaService {
+    // Add an uploaded image to the library, unreferenced media are swept after the grace period
+    pub async fn record(
+        media: &Collection<Media>,
+        owner_id: &str,
+        url: &str,
+        files: Vec<String>,
+        reference: Option<MediaReference>,
+    ) -> Result<()> {
+        let now = Utc::now();
+        let item = Media {
+            id: None,
+            owner_id: owner_id.to_string(),
+            url: url.to_string(),
+            files,
+            unreferenced_since: if reference.is_some() { None } else { Some(now) },
+            references: reference.into_iter().collect(),
+            created_at: now,
+        };
+
+        media.insert_one(&item).await?;
+
+        Ok(())
+    }
+
+    // Mark the media behind these URLs as used. URLs outside the library are ignored.
+    pub async fn reference(
+        media: &Collection<Media>,
+        urls: &[String],
+        reference: &MediaReference,
+    ) -> Result<()> {
+        if urls.is_empty() {
+            return Ok(());
+        }
+
+        media
+            .update_many(
+                doc! { "files": { "$in": urls } },
+                doc! {
+                    "$addToSet": { "references": mongodb::bson::to_bson(reference)? },
+                    "$set": { "unreferenced_since": null },
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Mark the media behind this URL as used, failing if it isn't in the library. A single
+    // update, so the sweeper either deleted it already or sees the reference.
+    pub async fn reference_existing(
+        media: &Collection<Media>,
+        url: &str,
+        reference: &MediaReference,
+    ) -> Result<()> {
+        let result = media
+            .update_one(
+                doc! { "files": url },
+                doc! {
+                    "$addToSet": { "references": mongodb::bson::to_bson(reference)? },
+                    "$set": { "unreferenced_since": null },
+                },
+            )
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(AppError::ValidationError("Upload the image first".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Mark these uploads of the owner as used. Fails without referencing anything unless
+    // every URL is an image they uploaded that is still in the library.
+    pub async fn reference_owned(
+        media: &Collection<Media>,
+        owner_id: &str,
+        urls: &[String],
+        reference: &MediaReference,
+    ) -> Result<()> {
+        if urls.is_empty() {
+            return Ok(());
+        }
+
+        let filter = doc! { "url": { "$in": urls }, "owner_id": owner_id };
+        let result = media
+            .update_many(
+                filter.clone(),
+                doc! {
+                    "$addToSet": { "references": mongodb::bson::to_bson(reference)? },
+                    "$set": { "unreferenced_since": null },
+                },
+            )
+            .await?;
+
+        if (result.matched_count as usize) < urls.len() {
+            Self::release_where(media, filter, reference).await?;
+            return Err(AppError::ValidationError(
+                "Photos must be images you uploaded".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Drop a reference from the media behind these URLs
+    pub async fn release(
+        media: &Collection<Media>,
+        urls: &[String],
+        reference: &MediaReference,
+    ) -> Result<()> {
+        if urls.is_empty() {
+            return Ok(());
+        }
+
+        Self::release_where(media, doc! { "files": { "$in": urls } }, reference).await
+    }
+
+    // Drop a reference from every media, e.g. when the product using them is deleted
+    pub async fn release_all(media: &Collection<Media>, reference: &MediaReference) -> Result<()> {
+        let filter = doc! { "references": mongodb::bson::to_bson(reference)? };
+        Self::release_where(media, filter, reference).await
+    }
+
+    async fn release_where(
+        media: &Collection<Media>,
+        filter: Document,
+        reference: &MediaReference,
+    ) -> Result<()> {
+        let reference = mongodb::bson::to_bson(reference)?;
+        let mut ids = Vec::new();
+        let mut cursor = media.find(filter).await?;
+        while let Some(result) = cursor.next().await {
+            if let Some(id) = result?.id {
+                ids.push(id);
+            }
+        }
+
+        media
+            .update_many(
+                doc! { "_id": { "$in": &ids } },
+                doc! { "$pull": { "references": reference } },
+            )
+            .await?;
+
+        // Start the grace period of those left without references
+        media
+            .update_many(
+                doc! {
+                    "_id": { "$in": &ids },
+                    "references": { "$size": 0 },
+                    "unreferenced_since": null,
+                },
+                doc! { "$set": { "unreferenced_since": mongodb::bson::DateTime::now() } },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // List the media library, newest first
+    pub async fn get_media(
+        media: &Collection<Media>,
+        filter: MediaFilter,
+    ) -> Result<Vec<MediaResponse>> {
+        let mut query = Document::new();
+        if let Some(owner_id) = filter.owner_id {
+            query.insert("owner_id", owner_id);
+        }
+        match filter.unreferenced {
+            Some(true) => {
+                query.insert("references", doc! { "$size": 0 });
+            }
+            Some(false) => {
+                query.insert("references.0", doc! { "$exists": true });
+            }
+            None => {}
+        }
+
+        let mut cursor = media.find(query).sort(doc! { "created_at": -1 }).await?;
+
+        let mut items = Vec::new();
+        while let Some(result) = cursor.next().await {
+            items.push(result?.to_response());
+        }
+
+        Ok(items)
+    }
+
+    // Delete media nothing has referenced for the grace period, returns how many were deleted
+    pub async fn sweep_orphans(
+        media: &Collection<Media>,
+        storage: &dyn ObjectStorage,
+        config: &MediaConfig,
+    ) -> Result<u64> {
+        let cutoff = Utc::now() - Duration::hours(config.orphan_grace_hours);
+        let orphaned = doc! {
+            "references": { "$size": 0 },
+            "unreferenced_since": { "$lt": mongodb::bson::DateTime::from_chrono(cutoff) },
+        };
+
+        let mut deleted = 0;
+        let mut failed = Vec::new();
+        for _ in 0..SWEEP_BATCH {
+            // Removing the document first means nothing can reference it while its files go
+            let Some(item) = media.find_one_and_delete(orphaned.clone()).await? else {
+                break;
+            };
+
+            match Self::delete_files(storage, &item).await {
+                Ok(()) => deleted += 1,
+                Err(e) => {
+                    tracing::error!("Deleting files of media {} failed: {:?}", item.url, e);
+                    failed.push(item);
+                }
+            }
+        }
+
+        // Put the failures back so the next sweep retries them
+        if !failed.is_empty() {
+            media.insert_many(&failed).await?;
+        }
+
+        Ok(deleted)
+    }
+
+    async fn delete_files(storage: &dyn ObjectStorage, item: &Media) -> Result<()> {
+        for file in &item.files {
+            storage.delete(file).await?;
+        }
+
+        Ok(())
+    }
+}
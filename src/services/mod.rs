@@ -1,4 +1,20 @@
 pub mod product;
 pub mod auth;
-pub mod s3;
-// pub mod payment;
+pub mod storage;
+pub mod image;
+pub mod upload;
+pub mod media;
+pub mod cart;
+pub mod order;
+pub mod returns;
+pub mod shipping;
+pub mod address;
+pub mod tax;
+pub mod payment;
+pub mod coupon;
+pub mod promotion;
+pub mod notification;
+pub mod abandoned_cart;
+pub mod wishlist;
+pub mod product_alert;
+pub mod product_image;
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+
+use super::Notifier;
+use crate::models::notification::Notification;
+use crate::utils::error::{AppError, Result};
+
+/// Appends notifications to a file as JSON lines
+pub struct FileNotifier {
+    path: String,
+}
+
+impl FileNotifier {
+    pub fn new(path: &str) -> Self {
+        FileNotifier {
+            path: path.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for FileNotifier {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let mut line = serde_json::to_string(&serde_json::json!({
+            "kind": notification.kind,
+            "to": notification.email,
+            "subject": notification.subject,
+            "body": notification.body,
+            "created_at": notification.created_at,
+        }))
+        .map_err(|_| AppError::InternalError)?;
+        line.push('\n');
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| {
+                tracing::error!("Could not open {}: {:?}", self.path, e);
+                AppError::InternalError
+            })?;
+
+        file.write_all(line.as_bytes()).await.map_err(|e| {
+            tracing::error!("Could not write to {}: {:?}", self.path, e);
+            AppError::InternalError
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use async_trait::async_trait;
+
+use super::Notifier;
+use crate::models::notification::Notification;
+use crate::utils::error::Result;
+
+/// Writes notifications to the application log, for development
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        tracing::info!(
+            "Notification to {}: {}\n{}",
+            notification.email,
+            notification.subject,
+            notification.body
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+pub mod file;
+pub mod log;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::doc;
+use mongodb::Collection;
+
+use self::file::FileNotifier;
+use self::log::LogNotifier;
+use crate::config::app::NotificationsConfig;
+use crate::models::notification::Notification;
+use crate::utils::error::{AppError, Result};
+
+pub const NOTIFIERS: [&str; 2] = ["log", "file"];
+
+/// Failed sends are retried this many times in total
+const MAX_ATTEMPTS: i32 = 5;
+const DISPATCH_BATCH: i64 = 50;
+
+/// Delivers notifications to customers
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn send(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Notifier picked in the notifications config
+pub fn notifier_from_config(config: &NotificationsConfig) -> Result<Box<dyn Notifier>> {
+    match config.notifier.as_str() {
+        "log" => Ok(Box::new(LogNotifier)),
+        "file" => Ok(Box::new(FileNotifier::new(&config.file_path))),
+        other => Err(AppError::ValidationError(format!(
+            "Unknown notifier {}, must be one of: {}",
+            other,
+            NOTIFIERS.join(", ")
+        ))),
+    }
+}
+
+pub struct NotificationService;
+
+impl NotificationService {
+    // Put a notification in the outbox, the dispatcher job sends it
+    pub async fn queue(
+        notifications: &Collection<Notification>,
+        kind: &str,
+        user_id: Option<&str>,
+        email: &str,
+        subject: String,
+        body: String,
+    ) -> Result<()> {
+        let notification = Notification {
+            id: None,
+            kind: kind.to_string(),
+            user_id: user_id.map(str::to_string),
+            email: email.to_string(),
+            subject,
+            body,
+            status: "pending".to_string(),
+            attempts: 0,
+            last_error: None,
+            created_at: Utc::now(),
+            sent_at: None,
+        };
+
+        notifications.insert_one(&notification).await?;
+        Ok(())
+    }
+
+    // Send queued notifications, oldest first. Returns how many were sent.
+    pub async fn dispatch_pending(
+        notifications: &Collection<Notification>,
+        notifier: &dyn Notifier,
+    ) -> Result<u64> {
+        let mut cursor = notifications
+            .find(doc! { "status": "pending" })
+            .sort(doc! { "created_at": 1 })
+            .limit(DISPATCH_BATCH)
+            .await?;
+
+        let mut pending = Vec::new();
+        while let Some(result) = cursor.next().await {
+            pending.push(result?);
+        }
+
+        let mut sent = 0;
+        for notification in pending {
+            let update = match notifier.send(&notification).await {
+                Ok(()) => {
+                    sent += 1;
+                    doc! {
+                        "$set": {
+                            "status": "sent",
+                            "sent_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                        },
+                        "$inc": { "attempts": 1 },
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "{} notifier failed to send notification {:?}: {:?}",
+                        notifier.name(),
+                        notification.id,
+                        e
+                    );
+                    let status = if notification.attempts + 1 >= MAX_ATTEMPTS {
+                        "failed"
+                    } else {
+                        "pending"
+                    };
+                    doc! {
+                        "$set": { "status": status, "last_error": e.to_string() },
+                        "$inc": { "attempts": 1 },
+                    }
+                }
+            };
+
+            notifications
+                .update_one(doc! { "_id": notification.id }, update)
+                .await?;
+        }
+
+        Ok(sent)
+    }
+}
@@ -0,0 +1,997 @@
+use crate::config::app::Config;
+use crate::db::AppState;
+use crate::models::address::SavedAddress;
+use crate::models::cart::CartItem;
+use crate::models::cart_reminder::CartReminder;
+use crate::models::coupon::{AppliedCoupon, Coupon, CouponRedemption, DiscountLine};
+use crate::models::media::{Media, MediaReference};
+use crate::models::order::{
+    CreateOrderRequest, Order, OrderFilter, OrderItem, OrderLookupClaims, OrderResponse,
+    PaymentProof,
+};
+use crate::models::product::Product;
+use crate::models::promotion::Promotion;
+use crate::models::shipping::ShippingZone;
+use crate::models::tax::TaxRule;
+use crate::services::abandoned_cart::AbandonedCartService;
+use crate::services::address::AddressService;
+use crate::services::cart::CartService;
+use crate::services::coupon::{CouponService, DiscountableItem};
+use crate::services::media::MediaService;
+use crate::services::promotion::PromotionService;
+use crate::services::shipping::ShippingService;
+use crate::services::tax::{TaxService, TaxableLine};
+use crate::services::payment::{
+    PaymentInit, PaymentProvider, PaymentSession, PaymentStatus, PaymentVerification,
+    PAYMENT_METHODS,
+};
+use crate::utils::error::{AppError, Result};
+use chrono::{Duration, Utc};
+use futures_util::StreamExt;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::Collection;
+use std::str::FromStr;
+
+const ORDER_STATUSES: [&str; 5] = ["pending", "processing", "shipped", "completed", "cancelled"];
+// Status changes admins can make. Paid orders enter "processing" by themselves.
+const ORDER_TRANSITIONS: [(&str, &str); 3] = [
+    ("pending", "processing"),
+    ("processing", "shipped"),
+    ("shipped", "completed"),
+];
+const ORDER_LOOKUP_PURPOSE: &str = "order_lookup";
+const ORDER_LOOKUP_DAYS: i64 = 90;
+
+// Collections touched when placing an order
+pub struct CheckoutCollections {
+    pub orders: Collection<Order>,
+    pub cart: Collection<CartItem>,
+    pub products: Collection<Product>,
+    pub coupons: Collection<Coupon>,
+    pub redemptions: Collection<CouponRedemption>,
+    pub applied_coupons: Collection<AppliedCoupon>,
+    pub promotions: Collection<Promotion>,
+    pub shipping_zones: Collection<ShippingZone>,
+    pub addresses: Collection<SavedAddress>,
+    pub tax_rules: Collection<TaxRule>,
+    pub cart_reminders: Collection<CartReminder>,
+}
+
+impl CheckoutCollections {
+    pub fn from_state(state: &AppState) -> Self {
+        CheckoutCollections {
+            orders: state.collection("MONGO_ORDERS_COLLECTION"),
+            cart: state.collection("MONGO_CART_COLLECTION"),
+            products: state.collection("MONGO_PRODUCTS_COLLECTION"),
+            coupons: state.collection("MONGO_COUPONS_COLLECTION"),
+            redemptions: state.collection("MONGO_COUPON_REDEMPTIONS_COLLECTION"),
+            applied_coupons: state.collection("MONGO_CART_COUPONS_COLLECTION"),
+            promotions: state.collection("MONGO_PROMOTIONS_COLLECTION"),
+            shipping_zones: state.collection("MONGO_SHIPPING_ZONES_COLLECTION"),
+            addresses: state.collection("MONGO_ADDRESSES_COLLECTION"),
+            tax_rules: state.collection("MONGO_TAX_RULES_COLLECTION"),
+            cart_reminders: state.collection("MONGO_CART_REMINDERS_COLLECTION"),
+        }
+    }
+}
+
+/// Who may act on an existing order
+pub enum OrderOwner {
+    User(String),  // the account it belongs to
+    Guest(String),  // whoever holds the lookup link sent for the guest email
+}
+
+impl OrderOwner {
+    fn filter(&self) -> Document {
+        match self {
+            OrderOwner::User(user_id) => doc! { "user_id": user_id },
+            OrderOwner::Guest(email) => doc! { "guest_email": email },
+        }
+    }
+}
+
+// Who an order is placed by
+pub struct Buyer {
+    pub cart_key: String,         // key the cart and applied coupon are stored under
+    pub user_id: Option<String>,  // None for guest checkout
+    pub email: String,
+    pub phone: Option<String>,
+}
+
+impl Buyer {
+    // Per-customer coupon limits count guests by email, guest tokens are easy to replace
+    fn coupon_key(&self) -> String {
+        match &self.user_id {
+            Some(user_id) => user_id.clone(),
+            None => format!("guest:{}", self.email),
+        }
+    }
+}
+
+pub struct OrderService;
+
+impl OrderService {
+    // Buyer for a guest checkout, contact details are checked since they replace the account
+    pub fn guest_buyer(cart_key: String, email: &str, phone: &str) -> Result<Buyer> {
+        let email = email.trim().to_lowercase();
+        let valid_email = email
+            .split_once('@')
+            .is_some_and(|(name, domain)| !name.is_empty() && domain.contains('.'));
+        if !valid_email {
+            return Err(AppError::ValidationError("A valid email is required".to_string()));
+        }
+
+        let phone = phone.trim();
+        let digits = phone.chars().filter(|c| c.is_ascii_digit()).count();
+        let allowed = phone
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | ' ' | '-'));
+        if !allowed || !(7..=15).contains(&digits) {
+            return Err(AppError::ValidationError(
+                "A valid phone number is required".to_string(),
+            ));
+        }
+
+        Ok(Buyer {
+            cart_key,
+            user_id: None,
+            email,
+            phone: Some(phone.to_string()),
+        })
+    }
+
+    // Place an order for everything in the buyer's cart and start the online payment, if any
+    pub async fn create_order(
+        c: &CheckoutCollections,
+        config: &Config,
+        provider: Option<&dyn PaymentProvider>,
+        buyer: &Buyer,
+        req: CreateOrderRequest,
+    ) -> Result<(OrderResponse, Option<PaymentSession>)> {
+        if !PAYMENT_METHODS.contains(&req.payment_method.as_str()) {
+            return Err(AppError::ValidationError(format!(
+                "Payment method must be one of: {}",
+                PAYMENT_METHODS.join(", ")
+            )));
+        }
+
+        // The address is copied onto the order, later address book edits don't change it
+        let address = AddressService::resolve_for_checkout(
+            &c.addresses,
+            &buyer.cart_key,
+            req.address_id,
+            req.shipping_address,
+        )
+        .await?;
+        ShippingService::validate_address(&address)?;
+
+        // Never charge a price the customer hasn't been shown, or go ahead with lines that
+        // can't be filled
+        let active = PromotionService::active_promotions(&c.promotions).await?;
+        let warnings =
+            CartService::revalidate(&c.cart, &c.products, &active, &buyer.cart_key).await?;
+        if !warnings.is_empty() {
+            let messages: Vec<String> = warnings.into_iter().map(|w| w.message).collect();
+            return Err(AppError::Conflict(format!(
+                "Your cart has changed, please review it before checking out: {}",
+                messages.join("; ")
+            )));
+        }
+
+        let cart_items = CartService::get_items(&c.cart, &buyer.cart_key).await?;
+        if cart_items.is_empty() {
+            return Err(AppError::ValidationError("Cart is empty".to_string()));
+        }
+
+        // Reserve stock item by item, releasing what was taken if any item falls short.
+        // Items are priced at the effective price of this moment.
+        let mut items: Vec<OrderItem> = Vec::new();
+        for cart_item in &cart_items {
+            match Self::reserve_stock(&c.products, &c.promotions, &active, cart_item).await {
+                Ok(item) => items.push(item),
+                Err(e) => {
+                    Self::restock(&c.products, &c.promotions, &items).await?;
+                    return Err(e);
+                }
+            }
+        }
+
+        let subtotal: f64 = items.iter().map(|i| i.price * i.quantity as f64).sum();
+
+        let lines: Vec<(String, i32)> =
+            items.iter().map(|i| (i.product_id.clone(), i.quantity)).collect();
+        let shipping = match ShippingService::total_weight(&c.products, &lines).await {
+            Ok(weight_kg) => {
+                ShippingService::quote(&c.shipping_zones, &address, weight_kg, subtotal).await
+            }
+            Err(e) => Err(e),
+        };
+        let shipping = match shipping {
+            Ok(shipping) => shipping,
+            Err(e) => {
+                Self::restock(&c.products, &c.promotions, &items).await?;
+                return Err(e);
+            }
+        };
+
+        // An explicit code wins over the one applied to the cart
+        let coupon_code = match req.coupon_code {
+            Some(code) => Some(code),
+            None => CouponService::get_applied_code(&c.applied_coupons, &buyer.cart_key).await?,
+        };
+
+        let mut redemption = None;
+        if let Some(code) = coupon_code {
+            match Self::redeem_coupon(c, &code, &buyer.coupon_key(), &items).await {
+                Ok(redeemed) => redemption = Some(redeemed),
+                Err(e) => {
+                    Self::restock(&c.products, &c.promotions, &items).await?;
+                    return Err(e);
+                }
+            }
+        }
+        let (coupon_code, discount_amount) = match &redemption {
+            Some((_, line)) => (Some(line.code.clone()), line.amount),
+            None => (None, 0.0),
+        };
+
+        // Tax is charged on what the customer pays for each item, after the discount
+        let amounts: Vec<f64> = items.iter().map(|i| i.price * i.quantity as f64).collect();
+        let taxable: Vec<TaxableLine> = items
+            .iter()
+            .zip(TaxService::allocate_discount(&amounts, discount_amount))
+            .map(|(item, amount)| TaxableLine {
+                product_id: item.product_id.clone(),
+                amount,
+            })
+            .collect();
+        let tax = match TaxService::calculate(&c.tax_rules, &c.products, &taxable, Some(&address.state))
+            .await
+        {
+            Ok(tax) => tax,
+            Err(e) => {
+                Self::restock(&c.products, &c.promotions, &items).await?;
+                if let Some((redemption_id, _)) = redemption {
+                    CouponService::release(&c.coupons, &c.redemptions, redemption_id).await?;
+                }
+                return Err(e);
+            }
+        };
+        for (item, item_tax) in items.iter_mut().zip(tax.items) {
+            item.tax = item_tax;
+        }
+
+        let now = Utc::now();
+        let payment_due_at = if req.payment_method == "offline" {
+            Some(now + Duration::hours(config.orders.offline_payment_deadline_hours))
+        } else {
+            None
+        };
+
+        let order = Order {
+            id: None,
+            user_id: buyer.user_id.clone(),
+            guest_email: buyer.user_id.is_none().then(|| buyer.email.clone()),
+            guest_phone: buyer.user_id.is_none().then(|| buyer.phone.clone()).flatten(),
+            items,
+            total_amount: subtotal - discount_amount + tax.exclusive_total + shipping.fee,
+            discount_amount,
+            tax_amount: tax.total,
+            tax_lines: tax.lines,
+            shipping_fee: shipping.fee,
+            coupon_code,
+            payment_method: req.payment_method,
+            payment_reference: None,
+            payment_status: "pending".to_string(),
+            order_status: "pending".to_string(),
+            shipping_address: Some(address),
+            created_at: now,
+            completed_at: None,
+            refunded_amount: 0.0,
+            payment_due_at,
+            payment_proof: None,
+            payment_note: None,
+        };
+
+        let result = match c.orders.insert_one(&order).await {
+            Ok(result) => result,
+            Err(e) => {
+                Self::restock(&c.products, &c.promotions, &order.items).await?;
+                if let Some((redemption_id, _)) = redemption {
+                    CouponService::release(&c.coupons, &c.redemptions, redemption_id).await?;
+                }
+                return Err(e.into());
+            }
+        };
+
+        let inserted_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| AppError::InternalError)?;
+
+        if let Some((redemption_id, _)) = &redemption {
+            CouponService::attach_order(&c.redemptions, *redemption_id, &inserted_id.to_hex())
+                .await?;
+        }
+
+        let session = match provider {
+            Some(provider) => {
+                let payment = PaymentInit {
+                    reference: inserted_id.to_hex(),
+                    amount: order.total_amount,
+                    email: buyer.email.clone(),
+                    description: format!("Order {}", inserted_id.to_hex()),
+                    callback_url: config.payments.callback_url.clone(),
+                };
+
+                match provider.initialize(&payment).await {
+                    Ok(session) => {
+                        c.orders
+                            .update_one(
+                                doc! { "_id": inserted_id },
+                                doc! { "$set": { "payment_reference": &session.reference } },
+                            )
+                            .await?;
+                        Some(session)
+                    }
+                    Err(e) => {
+                        tracing::error!(
+                            "{} payment initialization failed for order {}: {:?}",
+                            provider.name(),
+                            inserted_id.to_hex(),
+                            e
+                        );
+                        // Keep the cart and the coupon so the customer can retry
+                        let note = "Payment could not be started";
+                        Self::cancel_unpaid_order(c, doc! { "_id": inserted_id }, note).await?;
+                        return Err(e);
+                    }
+                }
+            }
+            None => None,
+        };
+
+        CartService::clear_cart(&c.cart, &buyer.cart_key).await?;
+        CouponService::remove_from_cart(&c.applied_coupons, &buyer.cart_key).await?;
+
+        let order = Self::find_order(&c.orders, doc! { "_id": inserted_id }).await?;
+
+        Ok((order, session))
+    }
+
+    // Record the payment state confirmed by the provider
+    pub async fn apply_payment_verification(
+        c: &CheckoutCollections,
+        verification: &PaymentVerification,
+    ) -> Result<OrderResponse> {
+        let orders = &c.orders;
+        let order = orders
+            .find_one(doc! { "payment_reference": &verification.reference })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+
+        let unpaid_filter = doc! {
+            "payment_reference": &verification.reference,
+            "payment_status": "pending",
+            "order_status": "pending",
+        };
+
+        match verification.status {
+            PaymentStatus::Success => {
+                // Allow for rounding to the minor unit
+                if verification.amount + 0.005 < order.total_amount {
+                    tracing::error!(
+                        "Payment {} of {:.2} does not cover order total {:.2}",
+                        verification.reference,
+                        verification.amount,
+                        order.total_amount
+                    );
+                    return Err(AppError::PaymentError(
+                        "Amount paid does not match the order total".to_string(),
+                    ));
+                }
+
+                let result = orders
+                    .update_one(
+                        unpaid_filter,
+                        doc! { "$set": { "payment_status": "completed", "order_status": "processing" } },
+                    )
+                    .await?;
+                if result.modified_count > 0 {
+                    AbandonedCartService::mark_recovered(&c.cart_reminders, &order).await?;
+                }
+            }
+            PaymentStatus::Failed => {
+                Self::cancel_unpaid_order(c, unpaid_filter, "Payment failed").await?;
+            }
+            PaymentStatus::Reversed => {
+                // Money went back to the customer, staff decide what happens to the order
+                tracing::warn!("Payment {} was reversed", verification.reference);
+                orders
+                    .update_one(
+                        doc! {
+                            "payment_reference": &verification.reference,
+                            "payment_status": { "$in": ["pending", "completed"] },
+                        },
+                        doc! { "$set": {
+                            "payment_status": "reversed",
+                            "payment_note": "Payment reversed by the provider",
+                        } },
+                    )
+                    .await?;
+            }
+            PaymentStatus::Pending => {}
+        }
+
+        Self::find_order(orders, doc! { "payment_reference": &verification.reference }).await
+    }
+
+    // List orders of a user, newest first
+    pub async fn get_user_orders(
+        collection: &Collection<Order>,
+        user_id: &str,
+    ) -> Result<Vec<OrderResponse>> {
+        Self::find_orders(collection, doc! { "user_id": user_id }).await
+    }
+
+    // Get an order that belongs to the given user
+    pub async fn get_user_order(
+        collection: &Collection<Order>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<OrderResponse> {
+        let object_id = Self::parse_id(id)?;
+        Self::find_order(collection, doc! { "_id": object_id, "user_id": user_id }).await
+    }
+
+    // Signed link token a guest can look up their order with
+    pub fn lookup_token(order: &OrderResponse, secret: &str) -> Result<String> {
+        let email = order.guest_email.clone().ok_or_else(|| {
+            AppError::ValidationError("Only guest orders have lookup links".to_string())
+        })?;
+
+        let now = Utc::now();
+        let claims = OrderLookupClaims {
+            sub: order.id.clone(),
+            email,
+            purpose: ORDER_LOOKUP_PURPOSE.to_string(),
+            exp: (now + Duration::days(ORDER_LOOKUP_DAYS)).timestamp() as usize,
+            iat: now.timestamp() as usize,
+        };
+
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .map_err(|_| AppError::InternalError)
+    }
+
+    // Order id and guest email a lookup token was issued for
+    pub fn verify_lookup_token(token: &str, secret: &str) -> Result<(String, String)> {
+        let claims = decode::<OrderLookupClaims>(
+            token,
+            &DecodingKey::from_secret(secret.as_bytes()),
+            &Validation::default(),
+        )
+        .map(|data| data.claims)
+        .ok()
+        .filter(|claims| claims.purpose == ORDER_LOOKUP_PURPOSE)
+        .ok_or_else(|| AppError::AuthError("Invalid or expired order link".to_string()))?;
+
+        Ok((claims.sub, claims.email))
+    }
+
+    // Get the order a lookup token was issued for
+    pub async fn get_order_by_lookup_token(
+        collection: &Collection<Order>,
+        token: &str,
+        secret: &str,
+    ) -> Result<OrderResponse> {
+        let (id, email) = Self::verify_lookup_token(token, secret)?;
+
+        let mut filter = OrderOwner::Guest(email).filter();
+        filter.insert("_id", Self::parse_id(&id)?);
+        Self::find_order(collection, filter).await
+    }
+
+    // Attach a guest order to the signed-in account. The lookup token proves the customer
+    // placed it, and it must have been placed with the account's email.
+    pub async fn claim_guest_order(
+        collection: &Collection<Order>,
+        token: &str,
+        secret: &str,
+        user_id: &str,
+        email: &str,
+    ) -> Result<OrderResponse> {
+        let (id, guest_email) = Self::verify_lookup_token(token, secret)?;
+        let object_id = Self::parse_id(&id)?;
+
+        if !guest_email.trim().eq_ignore_ascii_case(email.trim()) {
+            return Err(AppError::Forbidden(
+                "This order was placed with another email".to_string(),
+            ));
+        }
+
+        let mut filter = OrderOwner::Guest(guest_email).filter();
+        filter.insert("_id", object_id);
+        filter.insert("user_id", mongodb::bson::Bson::Null);
+
+        let result = collection
+            .update_one(filter, doc! { "$set": { "user_id": user_id } })
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(AppError::Conflict(
+                "This order already belongs to an account".to_string(),
+            ));
+        }
+
+        Self::find_order(collection, doc! { "_id": object_id }).await
+    }
+
+    // List all orders (admin)
+    pub async fn get_orders(
+        collection: &Collection<Order>,
+        filter: OrderFilter,
+    ) -> Result<Vec<OrderResponse>> {
+        let mut query = Document::new();
+        if let Some(order_status) = filter.order_status {
+            query.insert("order_status", order_status);
+        }
+        if let Some(payment_status) = filter.payment_status {
+            query.insert("payment_status", payment_status);
+        }
+        if let Some(payment_method) = filter.payment_method {
+            query.insert("payment_method", payment_method);
+        }
+
+        Self::find_orders(collection, query).await
+    }
+
+    // Update order status (admin)
+    pub async fn update_order_status(
+        c: &CheckoutCollections,
+        id: &str,
+        order_status: &str,
+    ) -> Result<OrderResponse> {
+        let collection = &c.orders;
+        let object_id = Self::parse_id(id)?;
+
+        if !ORDER_STATUSES.contains(&order_status) {
+            return Err(AppError::ValidationError(format!(
+                "Order status must be one of: {}",
+                ORDER_STATUSES.join(", ")
+            )));
+        }
+
+        let order = collection
+            .find_one(doc! { "_id": object_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+
+        if order_status == "cancelled" && order.order_status != "cancelled" {
+            return Self::cancel_order(c, &order).await;
+        }
+        if !Self::check_transition(&order, order_status)? {
+            return Ok(order.to_response());
+        }
+
+        let mut update = doc! { "$set": { "order_status": order_status } };
+        // Completion date starts the return window, an earlier one is kept
+        if order_status == "completed" {
+            update.insert("$min", doc! { "completed_at": mongodb::bson::DateTime::now() });
+        }
+
+        // Only if nobody changed the order since it was read
+        let result = collection
+            .update_one(
+                doc! {
+                    "_id": object_id,
+                    "order_status": &order.order_status,
+                    "payment_status": &order.payment_status,
+                },
+                update,
+            )
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(AppError::Conflict(
+                "The order was changed meanwhile, try again".to_string(),
+            ));
+        }
+
+        Self::find_order(collection, doc! { "_id": object_id }).await
+    }
+
+    // Cancel an unpaid order like a failed payment would, paid orders are refunded
+    // through a return instead
+    async fn cancel_order(c: &CheckoutCollections, order: &Order) -> Result<OrderResponse> {
+        if order.payment_status != "pending" {
+            return Err(AppError::ValidationError(format!(
+                "Orders with a {} payment can't be cancelled, refund paid orders through a return",
+                order.payment_status
+            )));
+        }
+
+        let filter = doc! {
+            "_id": order.id,
+            "payment_status": "pending",
+            "order_status": &order.order_status,
+        };
+        if !Self::cancel_unpaid_order(c, filter, "Cancelled by an admin").await? {
+            return Err(AppError::Conflict(
+                "The order was changed meanwhile, try again".to_string(),
+            ));
+        }
+
+        Self::find_order(&c.orders, doc! { "_id": order.id }).await
+    }
+
+    // Whether an admin may move the order to `order_status`, false if it's already there
+    fn check_transition(order: &Order, order_status: &str) -> Result<bool> {
+        if order.order_status == order_status {
+            return Ok(false);
+        }
+
+        if !ORDER_TRANSITIONS.contains(&(order.order_status.as_str(), order_status)) {
+            return Err(AppError::ValidationError(format!(
+                "A {} order can't be marked {}",
+                order.order_status, order_status
+            )));
+        }
+        if order.payment_status != "completed" {
+            return Err(AppError::ValidationError(format!(
+                "Only paid orders can be marked {}",
+                order_status
+            )));
+        }
+
+        Ok(true)
+    }
+
+    // Check that an order of the owner is still waiting for an offline payment
+    pub async fn ensure_awaiting_offline_payment(
+        collection: &Collection<Order>,
+        id: &str,
+        owner: &OrderOwner,
+    ) -> Result<()> {
+        let object_id = Self::parse_id(id)?;
+
+        let mut filter = Self::offline_payment_filter();
+        filter.extend(owner.filter());
+        filter.insert("_id", object_id);
+
+        if collection.find_one(filter).await?.is_none() {
+            return Err(Self::not_awaiting_payment(collection, object_id, Some(owner)).await);
+        }
+
+        Ok(())
+    }
+
+    // Attach a bank transfer receipt to an unpaid offline order. A replaced receipt is no
+    // longer used by the order, so the sweeper can delete it.
+    pub async fn attach_payment_proof(
+        collection: &Collection<Order>,
+        media: &Collection<Media>,
+        id: &str,
+        owner: &OrderOwner,
+        url: String,
+    ) -> Result<OrderResponse> {
+        let object_id = Self::parse_id(id)?;
+
+        let proof = PaymentProof {
+            url,
+            uploaded_at: Utc::now(),
+        };
+
+        let mut filter = Self::offline_payment_filter();
+        filter.extend(owner.filter());
+        filter.insert("_id", object_id);
+
+        // Returns the order as it was, with the receipt being replaced
+        let previous = collection
+            .find_one_and_update(
+                filter,
+                doc! { "$set": { "payment_proof": mongodb::bson::to_bson(&proof)? } },
+            )
+            .await?;
+
+        let Some(previous) = previous else {
+            return Err(Self::not_awaiting_payment(collection, object_id, Some(owner)).await);
+        };
+        if let Some(replaced) = previous.payment_proof.filter(|p| p.url != proof.url) {
+            MediaService::release(media, &[replaced.url], &MediaReference::new("order", id))
+                .await?;
+        }
+
+        Self::find_order(collection, doc! { "_id": object_id }).await
+    }
+
+    // Confirm that an offline payment was received (admin)
+    pub async fn confirm_offline_payment(
+        c: &CheckoutCollections,
+        id: &str,
+        note: Option<String>,
+    ) -> Result<OrderResponse> {
+        let collection = &c.orders;
+        let object_id = Self::parse_id(id)?;
+
+        let mut set_doc = doc! {
+            "payment_status": "completed",
+            "order_status": "processing",
+        };
+        if let Some(note) = note {
+            set_doc.insert("payment_note", note);
+        }
+
+        let mut filter = Self::offline_payment_filter();
+        filter.insert("_id", object_id);
+
+        let confirmed = collection
+            .find_one_and_update(filter, doc! { "$set": set_doc })
+            .await?;
+
+        let Some(order) = confirmed else {
+            return Err(Self::not_awaiting_payment(collection, object_id, None).await);
+        };
+        AbandonedCartService::mark_recovered(&c.cart_reminders, &order).await?;
+
+        Self::find_order(collection, doc! { "_id": object_id }).await
+    }
+
+    // Reject an offline payment, cancelling the order and releasing its stock (admin)
+    pub async fn reject_offline_payment(
+        c: &CheckoutCollections,
+        id: &str,
+        note: Option<String>,
+    ) -> Result<OrderResponse> {
+        let orders = &c.orders;
+        let object_id = Self::parse_id(id)?;
+        let note = note.unwrap_or_else(|| "Payment could not be verified".to_string());
+
+        let mut filter = Self::offline_payment_filter();
+        filter.insert("_id", object_id);
+
+        if !Self::cancel_unpaid_order(c, filter, &note).await? {
+            return Err(Self::not_awaiting_payment(orders, object_id, None).await);
+        }
+
+        Self::find_order(orders, doc! { "_id": object_id }).await
+    }
+
+    // Cancel offline orders whose payment deadline has passed, returns how many were cancelled
+    pub async fn cancel_expired_offline_orders(c: &CheckoutCollections) -> Result<u64> {
+        let now = mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis());
+
+        let mut expired_filter = Self::offline_payment_filter();
+        expired_filter.insert("payment_due_at", doc! { "$lt": now });
+
+        let mut cursor = c.orders.find(expired_filter.clone()).await?;
+        let mut expired_ids = Vec::new();
+        while let Some(result) = cursor.next().await {
+            if let Some(id) = result?.id {
+                expired_ids.push(id);
+            }
+        }
+
+        let mut cancelled = 0;
+        for id in expired_ids {
+            let mut filter = expired_filter.clone();
+            filter.insert("_id", id);
+
+            let note = "Payment was not confirmed before the deadline";
+            if Self::cancel_unpaid_order(c, filter, note).await? {
+                tracing::info!("Cancelled unpaid offline order {}", id.to_hex());
+                cancelled += 1;
+            }
+        }
+
+        Ok(cancelled)
+    }
+
+    // Atomically cancel the unpaid order matching `filter`, put its items back in stock
+    // and give back the coupon use it took
+    async fn cancel_unpaid_order(
+        c: &CheckoutCollections,
+        filter: Document,
+        note: &str,
+    ) -> Result<bool> {
+        let order = c
+            .orders
+            .find_one_and_update(
+                filter,
+                doc! {
+                    "$set": {
+                        "payment_status": "failed",
+                        "order_status": "cancelled",
+                        "payment_note": note,
+                    }
+                },
+            )
+            .await?;
+
+        match order {
+            Some(order) => {
+                Self::restock(&c.products, &c.promotions, &order.items).await?;
+                if let Some(order_id) = order.id {
+                    CouponService::release_for_order(&c.coupons, &c.redemptions, &order_id.to_hex())
+                        .await?;
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    // Validate the coupon against the order items and count its use
+    async fn redeem_coupon(
+        c: &CheckoutCollections,
+        code: &str,
+        user_id: &str,
+        items: &[OrderItem],
+    ) -> Result<(ObjectId, DiscountLine)> {
+        let discountable: Vec<DiscountableItem> = items
+            .iter()
+            .map(|i| DiscountableItem {
+                product_id: i.product_id.clone(),
+                price: i.price,
+                quantity: i.quantity,
+            })
+            .collect();
+
+        let (coupon, line) = CouponService::evaluate(
+            &c.coupons,
+            &c.redemptions,
+            &c.products,
+            code,
+            user_id,
+            &discountable,
+        )
+        .await?;
+
+        let redemption_id =
+            CouponService::redeem(&c.coupons, &c.redemptions, &coupon, user_id, line.amount).await?;
+
+        Ok((redemption_id, line))
+    }
+
+    // Take the cart quantity out of stock at the current effective price
+    async fn reserve_stock(
+        products: &Collection<Product>,
+        promotions: &Collection<Promotion>,
+        active: &[Promotion],
+        cart_item: &CartItem,
+    ) -> Result<OrderItem> {
+        let product_id = ObjectId::from_str(&cart_item.product_id)
+            .map_err(|_| AppError::ValidationError("Invalid product ID".to_string()))?;
+
+        let product = products
+            .find_one_and_update(
+                doc! { "_id": product_id, "stock_quantity": { "$gte": cart_item.quantity } },
+                doc! { "$inc": { "stock_quantity": -cart_item.quantity } },
+            )
+            .await?
+            .ok_or_else(|| {
+                AppError::ValidationError(format!(
+                    "{} is no longer available in the requested quantity",
+                    cart_item.product_name
+                ))
+            })?;
+
+        let mut item = OrderItem {
+            product_id: cart_item.product_id.clone(),
+            product_name: product.name.clone(),
+            quantity: cart_item.quantity,
+            price: product.price,
+            promotion_id: None,
+            tax: None,
+        };
+
+        let offer =
+            PromotionService::best_offer(active, &cart_item.product_id, &product.category, product.price);
+        if let Some((promotion, price)) = offer {
+            if !PromotionService::reserve_flash_sale(promotions, promotion, cart_item.quantity).await? {
+                Self::restock(products, promotions, std::slice::from_ref(&item)).await?;
+                return Err(AppError::ValidationError(format!(
+                    "Not enough {} left at the {} price, please review your cart",
+                    product.name, promotion.name
+                )));
+            }
+
+            item.price = price;
+            item.promotion_id = promotion.id.map(|id| id.to_hex());
+        }
+
+        // The price changed after the cart was checked
+        if !CartService::same_price(item.price, cart_item.product_price) {
+            Self::restock(products, promotions, std::slice::from_ref(&item)).await?;
+            return Err(AppError::Conflict(format!(
+                "The price of {} has just changed, please review your cart",
+                product.name
+            )));
+        }
+
+        Ok(item)
+    }
+
+    // Put order items back in stock, along with any flash sale units they took
+    async fn restock(
+        products: &Collection<Product>,
+        promotions: &Collection<Promotion>,
+        items: &[OrderItem],
+    ) -> Result<()> {
+        for item in items {
+            if let Ok(product_id) = ObjectId::from_str(&item.product_id) {
+                products
+                    .update_one(
+                        doc! { "_id": product_id },
+                        doc! { "$inc": { "stock_quantity": item.quantity } },
+                    )
+                    .await?;
+            }
+            if let Some(promotion_id) = &item.promotion_id {
+                PromotionService::release_flash_sale(promotions, promotion_id, item.quantity)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Offline orders that are still waiting for the money to arrive
+    fn offline_payment_filter() -> Document {
+        doc! {
+            "payment_method": "offline",
+            "payment_status": "pending",
+            "order_status": "pending",
+        }
+    }
+
+    async fn not_awaiting_payment(
+        collection: &Collection<Order>,
+        object_id: ObjectId,
+        owner: Option<&OrderOwner>,
+    ) -> AppError {
+        let mut query = doc! { "_id": object_id };
+        if let Some(owner) = owner {
+            query.extend(owner.filter());
+        }
+
+        match collection.find_one(query).await {
+            Ok(Some(_)) => AppError::ValidationError(
+                "This order is not awaiting an offline payment".to_string(),
+            ),
+            Ok(None) => AppError::NotFound("Order not found".to_string()),
+            Err(e) => e.into(),
+        }
+    }
+
+    async fn find_order(collection: &Collection<Order>, query: Document) -> Result<OrderResponse> {
+        let order = collection
+            .find_one(query)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+
+        Ok(order.to_response())
+    }
+
+    async fn find_orders(
+        collection: &Collection<Order>,
+        query: Document,
+    ) -> Result<Vec<OrderResponse>> {
+        let mut cursor = collection.find(query).sort(doc! { "created_at": -1 }).await?;
+
+        let mut orders = Vec::new();
+        while let Some(result) = cursor.next().await {
+            orders.push(result?.to_response());
+        }
+
+        Ok(orders)
+    }
+
+    fn parse_id(id: &str) -> Result<ObjectId> {
+        ObjectId::from_str(id).map_err(|_| AppError::ValidationError("Invalid order ID".to_string()))
+    }
+}
@@ -0,0 +1,100 @@
+pub mod opay;
+pub mod paystack;
+
+use crate::config::app::PaymentsConfig;
+use crate::utils::error::{AppError, Result};
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use serde::Serialize;
+
+use self::opay::OpayProvider;
+use self::paystack::PaystackProvider;
+
+pub const PAYMENT_METHODS: [&str; 3] = ["paystack", "opay", "offline"];
+
+/// What we ask a provider to charge
+#[derive(Debug, Clone)]
+pub struct PaymentInit {
+    pub reference: String,
+    pub amount: f64,
+    pub email: String,
+    pub description: String,
+    pub callback_url: Option<String>,
+}
+
+/// Where the customer goes to pay
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentSession {
+    pub reference: String,
+    pub authorization_url: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentStatus {
+    Success,
+    Pending,
+    Failed,
+    Reversed,  // paid, then refunded by the provider
+}
+
+/// Authoritative payment state fetched from the provider
+#[derive(Debug, Clone)]
+pub struct PaymentVerification {
+    pub reference: String,
+    pub status: PaymentStatus,
+    pub amount: f64,
+}
+
+/// Notification pushed by the provider; only trusted after `verify`
+#[derive(Debug, Clone)]
+pub struct WebhookEvent {
+    pub reference: String,
+    pub status: PaymentStatus,
+}
+
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn initialize(&self, payment: &PaymentInit) -> Result<PaymentSession>;
+
+    async fn verify(&self, reference: &str) -> Result<PaymentVerification>;
+
+    fn parse_webhook(&self, headers: &HeaderMap, body: &[u8]) -> Result<WebhookEvent>;
+}
+
+/// Provider handling the given payment method, `None` for offline payments
+pub fn provider_for(
+    config: &PaymentsConfig,
+    payment_method: &str,
+) -> Result<Option<Box<dyn PaymentProvider>>> {
+    let provider: Option<Option<Box<dyn PaymentProvider>>> = match payment_method {
+        "paystack" => config
+            .paystack
+            .as_ref()
+            .map(|c| Some(Box::new(PaystackProvider::from_config(c)) as _)),
+        "opay" => config.opay.as_ref().map(|c| Some(Box::new(OpayProvider::from_config(c)) as _)),
+        "offline" => config.bank_transfer.as_ref().map(|_| None),
+        _ => {
+            return Err(AppError::ValidationError(format!(
+                "Payment method must be one of: {}",
+                PAYMENT_METHODS.join(", ")
+            )))
+        }
+    };
+
+    // Methods whose provider or bank account isn't configured aren't offered
+    provider.ok_or_else(|| {
+        AppError::PaymentError(format!("Payment method {} is not available", payment_method))
+    })
+}
+
+// Providers charge in the minor unit (kobo)
+fn to_minor_units(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
+
+fn from_minor_units(amount: i64) -> f64 {
+    amount as f64 / 100.0
+}
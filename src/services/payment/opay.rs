@@ -0,0 +1,448 @@
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha512;
+
+use super::{
+    from_minor_units, to_minor_units, PaymentInit, PaymentProvider, PaymentSession,
+    PaymentStatus, PaymentVerification, WebhookEvent,
+};
+use crate::config::app::OpayConfig;
+use crate::utils::error::{AppError, Result};
+
+pub const DEFAULT_BASE_URL: &str = "https://liveapi.opaycheckout.com";
+const SUCCESS_CODE: &str = "00000";
+
+pub struct OpayProvider {
+    client: reqwest::Client,
+    base_url: String,
+    merchant_id: String,
+    public_key: String,
+    secret_key: String,
+    country: String,
+    currency: String,
+}
+
+#[derive(Deserialize)]
+struct OpayResponse<T> {
+    code: String,
+    message: String,
+    data: Option<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateData {
+    reference: String,
+    cashier_url: String,
+}
+
+#[derive(Deserialize)]
+struct StatusData {
+    reference: String,
+    status: String,
+    amount: OpayAmount,
+}
+
+#[derive(Deserialize)]
+struct OpayAmount {
+    total: i64,
+}
+
+#[derive(Deserialize)]
+struct CallbackBody {
+    payload: CallbackPayload,
+    sha512: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CallbackPayload {
+    amount: String,
+    currency: String,
+    reference: String,
+    refunded: bool,
+    status: String,
+    timestamp: String,
+    token: String,
+    transaction_id: String,
+}
+
+impl CallbackPayload {
+    // OPay signs these fields in this fixed layout rather than the raw body
+    fn signed_content(&self) -> String {
+        format!(
+            "{{Amount:\"{}\",Currency:\"{}\",Reference:\"{}\",Refunded:{},Status:\"{}\",Timestamp:\"{}\",Token:\"{}\",TransactionID:\"{}\"}}",
+            self.amount,
+            self.currency,
+            self.reference,
+            if self.refunded { "t" } else { "f" },
+            self.status,
+            self.timestamp,
+            self.token,
+            self.transaction_id,
+        )
+    }
+}
+
+impl OpayProvider {
+    pub fn new(base_url: &str, merchant_id: &str, public_key: &str, secret_key: &str) -> Self {
+        OpayProvider {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            merchant_id: merchant_id.to_string(),
+            public_key: public_key.to_string(),
+            secret_key: secret_key.to_string(),
+            country: "NG".to_string(),
+            currency: "NGN".to_string(),
+        }
+    }
+
+    pub fn from_config(config: &OpayConfig) -> Self {
+        Self::new(&config.base_url, &config.merchant_id, &config.public_key, &config.secret_key)
+    }
+
+    fn mac(&self, content: &[u8]) -> Result<Hmac<Sha512>> {
+        let mut mac = Hmac::<Sha512>::new_from_slice(self.secret_key.as_bytes())
+            .map_err(|_| AppError::InternalError)?;
+        mac.update(content);
+        Ok(mac)
+    }
+
+    // Status queries are authorized with an HMAC-SHA512 of the body instead of the public key
+    fn sign(&self, body: &[u8]) -> Result<String> {
+        Ok(hex::encode(self.mac(body)?.finalize().into_bytes()))
+    }
+
+    async fn read_response<T: for<'de> Deserialize<'de>>(response: reqwest::Response) -> Result<T> {
+        let body: OpayResponse<T> = response.json().await.map_err(|e| {
+            tracing::error!("OPay response error: {:?}", e);
+            AppError::PaymentError("Invalid response from OPay".to_string())
+        })?;
+
+        if body.code != SUCCESS_CODE {
+            return Err(AppError::PaymentError(body.message));
+        }
+
+        body.data
+            .ok_or_else(|| AppError::PaymentError("Empty response from OPay".to_string()))
+    }
+
+    fn map_status(status: &str) -> PaymentStatus {
+        match status {
+            "SUCCESS" => PaymentStatus::Success,
+            "FAIL" | "CLOSE" => PaymentStatus::Failed,
+            _ => PaymentStatus::Pending,
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for OpayProvider {
+    fn name(&self) -> &'static str {
+        "opay"
+    }
+
+    async fn initialize(&self, payment: &PaymentInit) -> Result<PaymentSession> {
+        let mut body = serde_json::json!({
+            "country": self.country,
+            "reference": payment.reference,
+            "amount": {
+                "total": to_minor_units(payment.amount),
+                "currency": self.currency,
+            },
+            "userInfo": { "userEmail": payment.email },
+            "product": {
+                "name": payment.description,
+                "description": payment.description,
+            },
+        });
+        if let Some(callback_url) = &payment.callback_url {
+            body["returnUrl"] = serde_json::json!(callback_url);
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/api/v1/international/cashier/create", self.base_url))
+            .bearer_auth(&self.public_key)
+            .header("MerchantId", &self.merchant_id)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("OPay initialize error: {:?}", e);
+                AppError::PaymentError("Could not reach OPay".to_string())
+            })?;
+
+        let data: CreateData = Self::read_response(response).await?;
+
+        Ok(PaymentSession {
+            reference: data.reference,
+            authorization_url: data.cashier_url,
+        })
+    }
+
+    async fn verify(&self, reference: &str) -> Result<PaymentVerification> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "country": self.country,
+            "reference": reference,
+        }))
+        .map_err(|_| AppError::InternalError)?;
+
+        let response = self
+            .client
+            .post(format!("{}/api/v1/international/cashier/status", self.base_url))
+            .bearer_auth(self.sign(&body)?)
+            .header("MerchantId", &self.merchant_id)
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("OPay verify error: {:?}", e);
+                AppError::PaymentError("Could not reach OPay".to_string())
+            })?;
+
+        let data: StatusData = Self::read_response(response).await?;
+
+        Ok(PaymentVerification {
+            reference: data.reference,
+            status: Self::map_status(&data.status),
+            amount: from_minor_units(data.amount.total),
+        })
+    }
+
+    fn parse_webhook(&self, _headers: &HeaderMap, body: &[u8]) -> Result<WebhookEvent> {
+        // The callback is only used to learn which reference changed;
+        // the order is updated from `verify`, so a forged callback cannot mark it paid.
+        let callback: CallbackBody = serde_json::from_slice(body)
+            .map_err(|_| AppError::ValidationError("Invalid webhook payload".to_string()))?;
+
+        // Still drop callbacks OPay didn't sign before they trigger a status query
+        let signature = hex::decode(&callback.sha512)
+            .map_err(|_| AppError::AuthError("Invalid webhook signature".to_string()))?;
+        self.mac(callback.payload.signed_content().as_bytes())?
+            .verify_slice(&signature)
+            .map_err(|_| AppError::AuthError("Invalid webhook signature".to_string()))?;
+
+        Ok(WebhookEvent {
+            reference: callback.payload.reference,
+            status: Self::map_status(&callback.payload.status),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, routing::post, Json, Router};
+
+    const MERCHANT_ID: &str = "256612345678901";
+    const PUBLIC_KEY: &str = "OPAYPUB-test";
+    const SECRET_KEY: &str = "OPAYPRV-test";
+
+    // Serve `app` on a random local port and return its base URL
+    async fn fake_opay(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    fn provider(base_url: &str) -> OpayProvider {
+        OpayProvider::new(base_url, MERCHANT_ID, PUBLIC_KEY, SECRET_KEY)
+    }
+
+    fn payment() -> PaymentInit {
+        PaymentInit {
+            reference: "ORDER-1".to_string(),
+            amount: 2500.5,
+            email: "buyer@example.com".to_string(),
+            description: "Order ORDER-1".to_string(),
+            callback_url: Some("https://shop.example.com/orders/ORDER-1".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn initialize_returns_cashier_url() {
+        let app = Router::new().route(
+            "/api/v1/international/cashier/create",
+            post(|headers: HeaderMap, Json(body): Json<serde_json::Value>| async move {
+                assert_eq!(headers["authorization"], format!("Bearer {}", PUBLIC_KEY).as_str());
+                assert_eq!(headers["merchantid"], MERCHANT_ID);
+                assert_eq!(body["reference"], "ORDER-1");
+                assert_eq!(body["amount"]["total"], 250050);
+                assert_eq!(body["amount"]["currency"], "NGN");
+                assert_eq!(body["returnUrl"], "https://shop.example.com/orders/ORDER-1");
+
+                Json(serde_json::json!({
+                    "code": "00000",
+                    "message": "SUCCESSFUL",
+                    "data": {
+                        "reference": "ORDER-1",
+                        "orderNo": "211004140885521681",
+                        "cashierUrl": "https://cashier.opaycheckout.com/pay/211004140885521681",
+                        "status": "INITIAL"
+                    }
+                }))
+            }),
+        );
+        let base_url = fake_opay(app).await;
+
+        let session = provider(&base_url).initialize(&payment()).await.unwrap();
+
+        assert_eq!(session.reference, "ORDER-1");
+        assert_eq!(
+            session.authorization_url,
+            "https://cashier.opaycheckout.com/pay/211004140885521681"
+        );
+    }
+
+    #[tokio::test]
+    async fn initialize_surfaces_provider_errors() {
+        let app = Router::new().route(
+            "/api/v1/international/cashier/create",
+            post(|| async {
+                Json(serde_json::json!({
+                    "code": "02000",
+                    "message": "authentication failed"
+                }))
+            }),
+        );
+        let base_url = fake_opay(app).await;
+
+        let err = provider(&base_url).initialize(&payment()).await.unwrap_err();
+
+        assert!(matches!(err, AppError::PaymentError(ref msg) if msg == "authentication failed"));
+    }
+
+    #[tokio::test]
+    async fn verify_signs_request_and_maps_status() {
+        let app = Router::new().route(
+            "/api/v1/international/cashier/status",
+            post(|headers: HeaderMap, body: Bytes| async move {
+                let mut mac = Hmac::<Sha512>::new_from_slice(SECRET_KEY.as_bytes()).unwrap();
+                mac.update(&body);
+                let expected = format!("Bearer {}", hex::encode(mac.finalize().into_bytes()));
+                assert_eq!(headers["authorization"], expected.as_str());
+                assert_eq!(headers["merchantid"], MERCHANT_ID);
+
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                assert_eq!(body["reference"], "ORDER-1");
+                assert_eq!(body["country"], "NG");
+
+                Json(serde_json::json!({
+                    "code": "00000",
+                    "message": "SUCCESSFUL",
+                    "data": {
+                        "reference": "ORDER-1",
+                        "orderNo": "211004140885521681",
+                        "status": "SUCCESS",
+                        "amount": { "total": 250050, "currency": "NGN" }
+                    }
+                }))
+            }),
+        );
+        let base_url = fake_opay(app).await;
+
+        let verification = provider(&base_url).verify("ORDER-1").await.unwrap();
+
+        assert_eq!(verification.reference, "ORDER-1");
+        assert_eq!(verification.status, PaymentStatus::Success);
+        assert_eq!(verification.amount, 2500.5);
+    }
+
+    #[tokio::test]
+    async fn verify_maps_closed_payments_to_failed() {
+        let app = Router::new().route(
+            "/api/v1/international/cashier/status",
+            post(|| async {
+                Json(serde_json::json!({
+                    "code": "00000",
+                    "message": "SUCCESSFUL",
+                    "data": {
+                        "reference": "ORDER-1",
+                        "status": "CLOSE",
+                        "amount": { "total": 250050, "currency": "NGN" }
+                    }
+                }))
+            }),
+        );
+        let base_url = fake_opay(app).await;
+
+        let verification = provider(&base_url).verify("ORDER-1").await.unwrap();
+
+        assert_eq!(verification.status, PaymentStatus::Failed);
+    }
+
+    // A transaction-status callback signed with `secret_key`
+    fn callback(secret_key: &str) -> serde_json::Value {
+        let signed = "{Amount:\"250050\",Currency:\"NGN\",Reference:\"ORDER-1\",Refunded:f,\
+            Status:\"SUCCESS\",Timestamp:\"2024-05-07T06:20:46Z\",Token:\"220507145660712931829\",\
+            TransactionID:\"220507145660712931829\"}";
+        let mut mac = Hmac::<Sha512>::new_from_slice(secret_key.as_bytes()).unwrap();
+        mac.update(signed.as_bytes());
+
+        serde_json::json!({
+            "payload": {
+                "amount": "250050",
+                "currency": "NGN",
+                "reference": "ORDER-1",
+                "refunded": false,
+                "status": "SUCCESS",
+                "timestamp": "2024-05-07T06:20:46Z",
+                "token": "220507145660712931829",
+                "transactionId": "220507145660712931829"
+            },
+            "sha512": hex::encode(mac.finalize().into_bytes()),
+            "type": "transaction-status"
+        })
+    }
+
+    #[test]
+    fn parse_webhook_reads_reference_and_status() {
+        let body = callback(SECRET_KEY);
+
+        let event = provider("http://127.0.0.1")
+            .parse_webhook(&HeaderMap::new(), body.to_string().as_bytes())
+            .unwrap();
+
+        assert_eq!(event.reference, "ORDER-1");
+        assert_eq!(event.status, PaymentStatus::Success);
+    }
+
+    #[test]
+    fn parse_webhook_rejects_wrong_signature() {
+        let body = callback("OPAYPRV-other");
+
+        let err = provider("http://127.0.0.1")
+            .parse_webhook(&HeaderMap::new(), body.to_string().as_bytes())
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::AuthError(_)));
+    }
+
+    #[test]
+    fn parse_webhook_rejects_tampered_payload() {
+        let mut body = callback(SECRET_KEY);
+        body["payload"]["reference"] = serde_json::json!("ORDER-2");
+
+        let err = provider("http://127.0.0.1")
+            .parse_webhook(&HeaderMap::new(), body.to_string().as_bytes())
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::AuthError(_)));
+    }
+
+    #[test]
+    fn parse_webhook_rejects_malformed_payload() {
+        let err = provider("http://127.0.0.1")
+            .parse_webhook(&HeaderMap::new(), b"not json")
+            .unwrap_err();
+
+        assert!(matches!(err, AppError::ValidationError(_)));
+    }
+}
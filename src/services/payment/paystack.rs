@@ -0,0 +1,172 @@
+
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha512;
+
+use super::{
+    from_minor_units, to_minor_units, PaymentInit, PaymentProvider, PaymentSession,
+    PaymentStatus, PaymentVerification, WebhookEvent,
+};
+use crate::config::app::PaystackConfig;
+use crate::utils::error::{AppError, Result};
+
+pub const DEFAULT_BASE_URL: &str = "https://api.paystack.co";
+
+pub struct PaystackProvider {
+    client: reqwest::Client,
+    base_url: String,
+    secret_key: String,
+}
+
+#[derive(Deserialize)]
+struct PaystackResponse<T> {
+    status: bool,
+    message: String,
+    data: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct InitializeData {
+    authorization_url: String,
+    reference: String,
+}
+
+#[derive(Deserialize)]
+struct VerifyData {
+    reference: String,
+    status: String,
+    amount: i64,
+}
+
+#[derive(Deserialize)]
+struct WebhookBody {
+    data: WebhookData,
+}
+
+#[derive(Deserialize)]
+struct WebhookData {
+    reference: String,
+    status: String,
+}
+
+impl PaystackProvider {
+    pub fn new(base_url: &str, secret_key: &str) -> Self {
+        PaystackProvider {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            secret_key: secret_key.to_string(),
+        }
+    }
+
+    pub fn from_config(config: &PaystackConfig) -> Self {
+        Self::new(&config.base_url, &config.secret_key)
+    }
+
+    async fn read_response<T: for<'de> Deserialize<'de>>(response: reqwest::Response) -> Result<T> {
+        let body: PaystackResponse<T> = response.json().await.map_err(|e| {
+            tracing::error!("Paystack response error: {:?}", e);
+            AppError::PaymentError("Invalid response from Paystack".to_string())
+        })?;
+
+        if !body.status {
+            return Err(AppError::PaymentError(body.message));
+        }
+
+        body.data
+            .ok_or_else(|| AppError::PaymentError("Empty response from Paystack".to_string()))
+    }
+
+    fn map_status(status: &str) -> PaymentStatus {
+        match status {
+            "success" => PaymentStatus::Success,
+            "failed" => PaymentStatus::Failed,
+            "reversed" => PaymentStatus::Reversed,
+            // "abandoned" only means the customer hasn't paid yet, it can still succeed
+            _ => PaymentStatus::Pending,
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentProvider for PaystackProvider {
+    fn name(&self) -> &'static str {
+        "paystack"
+    }
+
+    async fn initialize(&self, payment: &PaymentInit) -> Result<PaymentSession> {
+        let mut body = serde_json::json!({
+            "email": payment.email,
+            "amount": to_minor_units(payment.amount),
+            "reference": payment.reference,
+            "metadata": { "description": payment.description },
+        });
+        if let Some(callback_url) = &payment.callback_url {
+            body["callback_url"] = serde_json::json!(callback_url);
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/transaction/initialize", self.base_url))
+            .bearer_auth(&self.secret_key)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Paystack initialize error: {:?}", e);
+                AppError::PaymentError("Could not reach Paystack".to_string())
+            })?;
+
+        let data: InitializeData = Self::read_response(response).await?;
+
+        Ok(PaymentSession {
+            reference: data.reference,
+            authorization_url: data.authorization_url,
+        })
+    }
+
+    async fn verify(&self, reference: &str) -> Result<PaymentVerification> {
+        let response = self
+            .client
+            .get(format!("{}/transaction/verify/{}", self.base_url, reference))
+            .bearer_auth(&self.secret_key)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("Paystack verify error: {:?}", e);
+                AppError::PaymentError("Could not reach Paystack".to_string())
+            })?;
+
+        let data: VerifyData = Self::read_response(response).await?;
+
+        Ok(PaymentVerification {
+            reference: data.reference,
+            status: Self::map_status(&data.status),
+            amount: from_minor_units(data.amount),
+        })
+    }
+
+    fn parse_webhook(&self, headers: &HeaderMap, body: &[u8]) -> Result<WebhookEvent> {
+        // Paystack signs the raw body with the secret key
+        let signature = headers
+            .get("x-paystack-signature")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| hex::decode(h).ok())
+            .ok_or_else(|| AppError::AuthError("Missing webhook signature".to_string()))?;
+
+        let mut mac = Hmac::<Sha512>::new_from_slice(self.secret_key.as_bytes())
+            .map_err(|_| AppError::InternalError)?;
+        mac.update(body);
+        mac.verify_slice(&signature)
+            .map_err(|_| AppError::AuthError("Invalid webhook signature".to_string()))?;
+
+        let event: WebhookBody = serde_json::from_slice(body)
+            .map_err(|_| AppError::ValidationError("Invalid webhook payload".to_string()))?;
+
+        Ok(WebhookEvent {
+            reference: event.data.reference,
+            status: Self::map_status(&event.data.status),
+        })
+    }
+}
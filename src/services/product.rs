@@ -1,12 +1,43 @@
+use crate::db::AppState;
+use crate::models::media::{Media, MediaReference};
+use crate::models::notification::Notification;
+use crate::models::price_history::{PriceHistoryEntry, PriceHistoryResponse};
 use crate::models::product::{
-    CreateProductRequest, Product, ProductFilter, ProductResponse, UpdateProductRequest,
+    CreateProductRequest, Product, ProductFilter, ProductImage, ProductResponse,
+    UpdateProductRequest, MAX_PRODUCT_IMAGES,
 };
+use crate::models::product_alert::ProductAlert;
+use crate::models::promotion::Promotion;
+use crate::services::media::MediaService;
+use crate::services::product_alert::ProductAlertService;
 use crate::utils::error::{AppError, Result};
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use mongodb::bson::{doc, oid::ObjectId, Document};
 use mongodb::Collection;
 use std::str::FromStr;
 use futures_util::StreamExt;
+use uuid::Uuid;
+
+/// Collections a product update touches
+pub struct ProductCollections {
+    pub products: Collection<Product>,
+    pub history: Collection<PriceHistoryEntry>,
+    pub alerts: Collection<ProductAlert>,
+    pub notifications: Collection<Notification>,
+    pub promotions: Collection<Promotion>,
+}
+
+impl ProductCollections {
+    pub fn from_state(state: &AppState) -> Self {
+        ProductCollections {
+            products: state.collection("MONGO_PRODUCTS_COLLECTION"),
+            history: state.collection("MONGO_PRICE_HISTORY_COLLECTION"),
+            alerts: state.collection("MONGO_PRODUCT_ALERTS_COLLECTION"),
+            notifications: state.collection("MONGO_NOTIFICATIONS_COLLECTION"),
+            promotions: state.collection("MONGO_PROMOTIONS_COLLECTION"),
+        }
+    }
+}
 
 pub struct ProductService;
 
@@ -15,12 +46,22 @@ impl ProductService{
     //create a new product 
     pub async fn create_prouct (
         collection: &Collection<Product>,
+        media: &Collection<Media>,
         req: CreateProductRequest
     ) -> Result<ProductResponse> { 
 
         let now = Utc::now();
 
-        let product = Product {
+        let image_count = req.cover_image.iter().count()
+            + req.additional_images.as_ref().map_or(0, Vec::len);
+        if image_count > MAX_PRODUCT_IMAGES {
+            return Err(AppError::ValidationError(format!(
+                "A product can have at most {} images",
+                MAX_PRODUCT_IMAGES
+            )));
+        }
+
+        let mut product = Product {
             id: None,
             name: req.name,
             description: req.description,
@@ -28,8 +69,10 @@ impl ProductService{
             prodcut_type: req.product_type,
             price: req.price,
             stock_quantity: req.stock_quantity,
+            weight_kg: req.weight_kg,
             cover_image: req.cover_image,
             aditional_images: req.additional_images,
+            images: Vec::new(),
             label: None,
             average_rating: 0.0,
             rating_count: 0,
@@ -38,6 +81,15 @@ impl ProductService{
             updated_at: now,
         };
 
+        product.images = product
+            .image_list()
+            .into_iter()
+            .map(|image| ProductImage {
+                id: Uuid::new_v4().simple().to_string(),
+                ..image
+            })
+            .collect();
+
         let result = collection.insert_one(product, ).await?;
 
         let inserted_id = result.inserted_id.as_object_id().ok_or_else(|| AppError::InternalError)?;
@@ -48,6 +100,11 @@ impl ProductService{
         .await?
         .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
 
+    // Keep the uploaded images from being swept
+    let reference = MediaReference::new("product", &inserted_id.to_hex());
+    let urls: Vec<String> = created_product.images.iter().map(|image| image.url.clone()).collect();
+    MediaService::reference(media, &urls, &reference).await?;
+
     Ok(created_product.to_response())
     
 
@@ -158,12 +215,15 @@ pub async fn get_products(
         Ok(product.to_response())
     }
 
-    // Update product
+    // Update product, recording a price change made by `actor` and setting off the
+    // back-in-stock and price-drop alerts it triggers
     pub async fn update_product(
-        collection: &Collection<Product>,
+        collections: &ProductCollections,
         id: &str,
         req: UpdateProductRequest,
+        actor: &str,
     ) -> Result<ProductResponse> {
+        let collection = &collections.products;
         let object_id = ObjectId::from_str(id)
             .map_err(|_| AppError::ValidationError("Invalid product ID".to_string()))?;
 
@@ -181,26 +241,111 @@ pub async fn get_products(
         if let Some(stock) = req.stock_quantity {
             update_doc.insert("stock_quantity", stock);
         }
+        if let Some(weight_kg) = req.weight_kg {
+            update_doc.insert("weight_kg", weight_kg);
+        }
         if let Some(tags) = req.tags {
             update_doc.insert("tags", tags);
         }
         
-        update_doc.insert("updated_at", mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()));
+        // Stored the same way as created_at, so the product still deserializes
+        update_doc.insert("updated_at", mongodb::bson::to_bson(&Utc::now())?);
 
-        collection
-            .update_one(
+        // The document as it was before the update holds the old price
+        let previous = collection
+            .find_one_and_update(
                 doc! { "_id": object_id },
                 doc! { "$set": update_doc },
-                
             )
+            .await?
+            .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+        if let Some(new_price) = req.price {
+            if new_price != previous.price {
+                let entry = PriceHistoryEntry {
+                    id: None,
+                    product_id: id.to_string(),
+                    old_price: previous.price,
+                    new_price,
+                    changed_by: actor.to_string(),
+                    changed_at: Utc::now(),
+                };
+                collections.history.insert_one(entry).await?;
+            }
+        }
+
+        let updated = collection
+            .find_one(doc! { "_id": object_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+        // The update is done, a failure to queue alerts must not report it as failed
+        if let Err(e) = ProductAlertService::notify_changes(
+            &collections.alerts,
+            &collections.notifications,
+            &collections.promotions,
+            &previous,
+            &updated,
+        )
+        .await
+        {
+            tracing::error!("Queueing alerts for product {} failed: {:?}", id, e);
+        }
+
+        Ok(updated.to_response())
+    }
+
+    // Price changes of a product, newest first
+    pub async fn get_price_history(
+        collection: &Collection<Product>,
+        history: &Collection<PriceHistoryEntry>,
+        id: &str,
+    ) -> Result<Vec<PriceHistoryResponse>> {
+        // Fails if the product doesn't exist
+        Self::get_product_by_id(collection, id).await?;
+
+        let mut cursor = history
+            .find(doc! { "product_id": id })
+            .sort(doc! { "changed_at": -1 })
             .await?;
 
-        Self::get_product_by_id(collection, id).await
+        let mut entries = Vec::new();
+        while let Some(result) = cursor.next().await {
+            entries.push(result?.to_response());
+        }
+
+        Ok(entries)
     }
 
-    // Delete product
+    // Lowest list price the product had at any point in the last `days` days
+    pub async fn lowest_price_since(
+        history: &Collection<PriceHistoryEntry>,
+        product: &ProductResponse,
+        days: i64,
+    ) -> Result<f64> {
+        let since = Utc::now() - Duration::days(days);
+
+        // Both sides of a change in the window were in effect at some point in it
+        let mut cursor = history
+            .find(doc! {
+                "product_id": &product.id,
+                "changed_at": { "$gte": mongodb::bson::DateTime::from_chrono(since) },
+            })
+            .await?;
+
+        let mut lowest = product.price;
+        while let Some(result) = cursor.next().await {
+            let entry = result?;
+            lowest = lowest.min(entry.old_price).min(entry.new_price);
+        }
+
+        Ok(lowest)
+    }
+
+    // Delete product, its images are swept once nothing else uses them
     pub async fn delete_product(
         collection: &Collection<Product>,
+        media: &Collection<Media>,
         id: &str,
     ) -> Result<()> {
         let object_id = ObjectId::from_str(id)
@@ -214,8 +359,9 @@ pub async fn get_products(
             return Err(AppError::NotFound("Product not found".to_string()));
         }
 
+        MediaService::release_all(media, &MediaReference::new("product", id)).await?;
+
         Ok(())
     }
 
 }
-
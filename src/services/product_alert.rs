@@ -0,0 +1,266 @@
+use crate::models::notification::Notification;
+use crate::models::product::Product;
+use crate::models::product_alert::{ProductAlert, ProductAlertResponse};
+use crate::models::promotion::Promotion;
+use crate::services::notification::NotificationService;
+use crate::services::promotion::PromotionService;
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::Collection;
+use std::str::FromStr;
+
+const ALERT_KINDS: [&str; 2] = ["back_in_stock", "price_drop"];
+
+pub struct ProductAlertService;
+
+impl ProductAlertService {
+    // Subscribe to an alert for a product, subscribing twice keeps the first subscription.
+    // Price drops are measured from the price the customer sees, promotions included.
+    pub async fn subscribe(
+        alerts: &Collection<ProductAlert>,
+        products: &Collection<Product>,
+        promotions: &Collection<Promotion>,
+        product_id: &str,
+        user_id: &str,
+        email: &str,
+        kind: &str,
+    ) -> Result<ProductAlertResponse> {
+        if !ALERT_KINDS.contains(&kind) {
+            return Err(AppError::ValidationError(format!(
+                "Alert kind must be one of: {}",
+                ALERT_KINDS.join(", ")
+            )));
+        }
+
+        let object_id = ObjectId::from_str(product_id)
+            .map_err(|_| AppError::ValidationError("Invalid product ID".to_string()))?;
+        let product = products
+            .find_one(doc! { "_id": object_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+        if kind == "back_in_stock" && product.stock_quantity > 0 {
+            return Err(AppError::ValidationError(format!(
+                "{} is in stock",
+                product.name
+            )));
+        }
+
+        let pending = doc! {
+            "user_id": user_id,
+            "product_id": product_id,
+            "kind": kind,
+            "delivered_at": null,
+        };
+        if let Some(existing) = alerts.find_one(pending).await? {
+            return Ok(existing.to_response());
+        }
+
+        let active = PromotionService::active_promotions(promotions).await?;
+        let alert = ProductAlert {
+            id: None,
+            user_id: user_id.to_string(),
+            email: email.to_string(),
+            product_id: product_id.to_string(),
+            kind: kind.to_string(),
+            subscribed_price: Self::effective_price(&active, &product),
+            created_at: Utc::now(),
+            delivered_at: None,
+        };
+
+        let result = alerts.insert_one(&alert).await?;
+        let inserted_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| AppError::InternalError)?;
+
+        Ok(Self::find_alert(alerts, doc! { "_id": inserted_id }).await?.to_response())
+    }
+
+    // List the customer's alerts, newest first
+    pub async fn get_user_alerts(
+        alerts: &Collection<ProductAlert>,
+        user_id: &str,
+    ) -> Result<Vec<ProductAlertResponse>> {
+        let mut cursor = alerts
+            .find(doc! { "user_id": user_id })
+            .sort(doc! { "created_at": -1 })
+            .await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?.to_response());
+        }
+
+        Ok(results)
+    }
+
+    // Cancel one of the customer's alerts
+    pub async fn delete_alert(
+        alerts: &Collection<ProductAlert>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        let object_id = ObjectId::from_str(id)
+            .map_err(|_| AppError::ValidationError("Invalid alert ID".to_string()))?;
+
+        let result = alerts
+            .delete_one(doc! { "_id": object_id, "user_id": user_id })
+            .await?;
+        if result.deleted_count == 0 {
+            return Err(AppError::NotFound("Alert not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Queue notifications for the alerts a product update set off. Returns how many were queued.
+    pub async fn notify_changes(
+        alerts: &Collection<ProductAlert>,
+        notifications: &Collection<Notification>,
+        promotions: &Collection<Promotion>,
+        previous: &Product,
+        updated: &Product,
+    ) -> Result<u64> {
+        let product_id = updated.id.map(|id| id.to_hex()).unwrap_or_default();
+        let active = PromotionService::active_promotions(promotions).await?;
+        let price = Self::effective_price(&active, updated);
+        let mut queued = 0;
+
+        if previous.stock_quantity <= 0 && updated.stock_quantity > 0 {
+            let filter = doc! { "product_id": &product_id, "kind": "back_in_stock" };
+            queued += Self::deliver(alerts, notifications, filter, |_| {
+                (
+                    format!("{} is back in stock", updated.name),
+                    format!(
+                        "Good news: {} is available again at {:.2}. Order soon, stock is limited.",
+                        updated.name, price
+                    ),
+                )
+            })
+            .await?;
+        }
+
+        if price < Self::effective_price(&active, previous) {
+            queued += Self::notify_price_drop(alerts, notifications, updated, price).await?;
+        }
+
+        Ok(queued)
+    }
+
+    // Queue notifications for price drops that running promotions brought about without a
+    // product update. Returns how many were queued.
+    pub async fn notify_promotional_drops(
+        alerts: &Collection<ProductAlert>,
+        notifications: &Collection<Notification>,
+        products: &Collection<Product>,
+        promotions: &Collection<Promotion>,
+    ) -> Result<u64> {
+        let active = PromotionService::active_promotions(promotions).await?;
+        if active.is_empty() {
+            return Ok(0);
+        }
+
+        let product_ids = alerts
+            .distinct("product_id", doc! { "kind": "price_drop", "delivered_at": null })
+            .await?;
+        let ids: Vec<ObjectId> = product_ids
+            .iter()
+            .filter_map(|id| id.as_str())
+            .filter_map(|id| ObjectId::from_str(id).ok())
+            .collect();
+
+        let mut queued = 0;
+        let mut cursor = products.find(doc! { "_id": { "$in": ids } }).await?;
+        while let Some(result) = cursor.next().await {
+            let product = result?;
+            let price = Self::effective_price(&active, &product);
+            if price < product.price {
+                queued += Self::notify_price_drop(alerts, notifications, &product, price).await?;
+            }
+        }
+
+        Ok(queued)
+    }
+
+    // What the product sells for right now, promotions included
+    fn effective_price(active: &[Promotion], product: &Product) -> f64 {
+        let product_id = product.id.map(|id| id.to_hex()).unwrap_or_default();
+        PromotionService::best_offer(active, &product_id, &product.category, product.price)
+            .map(|(_, price)| price)
+            .unwrap_or(product.price)
+    }
+
+    async fn notify_price_drop(
+        alerts: &Collection<ProductAlert>,
+        notifications: &Collection<Notification>,
+        product: &Product,
+        price: f64,
+    ) -> Result<u64> {
+        // Only subscribers who saw a higher price hear about it
+        let filter = doc! {
+            "product_id": product.id.map(|id| id.to_hex()).unwrap_or_default(),
+            "kind": "price_drop",
+            "subscribed_price": { "$gt": price },
+        };
+        Self::deliver(alerts, notifications, filter, |alert| {
+            (
+                format!("Price drop on {}", product.name),
+                format!(
+                    "{} is now {:.2}, down from {:.2} when you asked us to watch it.",
+                    product.name, price, alert.subscribed_price
+                ),
+            )
+        })
+        .await
+    }
+
+    // Claim undelivered alerts matching `filter` one by one and queue their notification,
+    // so an alert is never sent twice
+    async fn deliver<F>(
+        alerts: &Collection<ProductAlert>,
+        notifications: &Collection<Notification>,
+        mut filter: Document,
+        message: F,
+    ) -> Result<u64>
+    where
+        F: Fn(&ProductAlert) -> (String, String),
+    {
+        filter.insert("delivered_at", mongodb::bson::Bson::Null);
+
+        let mut queued = 0;
+        loop {
+            let now = mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis());
+            let claimed = alerts
+                .find_one_and_update(filter.clone(), doc! { "$set": { "delivered_at": now } })
+                .await?;
+            let Some(alert) = claimed else {
+                break;
+            };
+
+            let (subject, body) = message(&alert);
+            let kind = format!("product_{}", alert.kind);
+            NotificationService::queue(
+                notifications,
+                &kind,
+                Some(&alert.user_id),
+                &alert.email,
+                subject,
+                body,
+            )
+            .await?;
+            queued += 1;
+        }
+
+        Ok(queued)
+    }
+
+    async fn find_alert(alerts: &Collection<ProductAlert>, query: Document) -> Result<ProductAlert> {
+        alerts
+            .find_one(query)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Alert not found".to_string()))
+    }
+}
@@ -0,0 +1,224 @@
+use crate::models::media::{Media, MediaReference};
+use crate::models::product::{
+    AttachImageRequest, Product, ProductImage, ProductResponse, ReorderImagesRequest,
+    UpdateImageRequest, MAX_PRODUCT_IMAGES,
+};
+use crate::services::media::MediaService;
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use std::str::FromStr;
+use uuid::Uuid;
+
+const MAX_ALT_TEXT_LENGTH: usize = 250;
+
+pub struct ProductImageService;
+
+impl ProductImageService {
+    // Attach an uploaded image after the existing ones, the first image becomes the cover
+    pub async fn attach(
+        products: &Collection<Product>,
+        media: &Collection<Media>,
+        product_id: &str,
+        req: AttachImageRequest,
+    ) -> Result<ProductResponse> {
+        let (object_id, product) = Self::load(products, product_id).await?;
+        let mut images = product.image_list();
+
+        if images.len() >= MAX_PRODUCT_IMAGES {
+            return Err(AppError::ValidationError(format!(
+                "A product can have at most {} images",
+                MAX_PRODUCT_IMAGES
+            )));
+        }
+        if images.iter().any(|image| image.url == req.url) {
+            return Err(AppError::Conflict("Image is already attached".to_string()));
+        }
+
+        let alt_text = alt_text(req.alt_text)?;
+
+        // Only images uploaded through the API, referenced before the product uses them so the
+        // sweeper can't delete them in between
+        let reference = MediaReference::new("product", product_id);
+        MediaService::reference_existing(media, &req.url, &reference).await?;
+
+        images.push(ProductImage {
+            id: Uuid::new_v4().simple().to_string(),
+            url: req.url.clone(),
+            alt_text,
+        });
+
+        match Self::save(products, object_id, &product, images).await {
+            Ok(updated) => Ok(updated),
+            Err(e) => {
+                // Unless a concurrent attach of the same image got saved instead
+                let (_, current) = Self::load(products, product_id).await?;
+                if !current.image_list().iter().any(|image| image.url == req.url) {
+                    MediaService::release(media, &[req.url], &reference).await?;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    // Put the images in the given order, the first one becomes the cover
+    pub async fn reorder(
+        products: &Collection<Product>,
+        product_id: &str,
+        req: ReorderImagesRequest,
+    ) -> Result<ProductResponse> {
+        let (object_id, product) = Self::load(products, product_id).await?;
+        let mut images = product.image_list();
+
+        let mut ordered = Vec::new();
+        for image_id in &req.image_ids {
+            let position = images
+                .iter()
+                .position(|image| &image.id == image_id)
+                .ok_or_else(|| {
+                    AppError::ValidationError(format!("Unknown or repeated image {}", image_id))
+                })?;
+            ordered.push(images.remove(position));
+        }
+
+        if !images.is_empty() {
+            return Err(AppError::ValidationError(
+                "List every image of the product".to_string(),
+            ));
+        }
+
+        Self::save(products, object_id, &product, ordered).await
+    }
+
+    // Move an image to the front
+    pub async fn set_cover(
+        products: &Collection<Product>,
+        product_id: &str,
+        image_id: &str,
+    ) -> Result<ProductResponse> {
+        let (object_id, product) = Self::load(products, product_id).await?;
+        let mut images = product.image_list();
+
+        let position = Self::position(&images, image_id)?;
+        let cover = images.remove(position);
+        images.insert(0, cover);
+
+        Self::save(products, object_id, &product, images).await
+    }
+
+    // Change the alt text of an image
+    pub async fn update_image(
+        products: &Collection<Product>,
+        product_id: &str,
+        image_id: &str,
+        req: UpdateImageRequest,
+    ) -> Result<ProductResponse> {
+        let (object_id, product) = Self::load(products, product_id).await?;
+        let mut images = product.image_list();
+
+        let position = Self::position(&images, image_id)?;
+        images[position].alt_text = alt_text(req.alt_text)?;
+
+        Self::save(products, object_id, &product, images).await
+    }
+
+    // Remove an image from the product, it is swept once nothing else uses it
+    pub async fn detach(
+        products: &Collection<Product>,
+        media: &Collection<Media>,
+        product_id: &str,
+        image_id: &str,
+    ) -> Result<ProductResponse> {
+        let (object_id, product) = Self::load(products, product_id).await?;
+        let mut images = product.image_list();
+
+        let position = Self::position(&images, image_id)?;
+        let removed = images.remove(position);
+
+        let updated = Self::save(products, object_id, &product, images).await?;
+        MediaService::release(media, &[removed.url], &MediaReference::new("product", product_id))
+            .await?;
+
+        Ok(updated)
+    }
+
+    async fn load(products: &Collection<Product>, product_id: &str) -> Result<(ObjectId, Product)> {
+        let object_id = ObjectId::from_str(product_id)
+            .map_err(|_| AppError::ValidationError("Invalid product ID".to_string()))?;
+
+        let product = products
+            .find_one(doc! { "_id": object_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+        Ok((object_id, product))
+    }
+
+    fn position(images: &[ProductImage], image_id: &str) -> Result<usize> {
+        images
+            .iter()
+            .position(|image| image.id == image_id)
+            .ok_or_else(|| AppError::NotFound("Image not found".to_string()))
+    }
+
+    // Store the images along with the cover_image and aditional_images mirrors. Fails if the
+    // product changed since it was loaded, so concurrent edits don't drop images.
+    async fn save(
+        products: &Collection<Product>,
+        object_id: ObjectId,
+        product: &Product,
+        images: Vec<ProductImage>,
+    ) -> Result<ProductResponse> {
+        let cover_image = images.first().map(|image| image.url.clone());
+        let additional_images: Vec<String> =
+            images.iter().skip(1).map(|image| image.url.clone()).collect();
+
+        let result = products
+            .update_one(
+                doc! {
+                    "_id": object_id,
+                    "updated_at": mongodb::bson::to_bson(&product.updated_at)?,
+                },
+                doc! { "$set": {
+                    "images": mongodb::bson::to_bson(&images)?,
+                    "cover_image": cover_image,
+                    "aditional_images": additional_images,
+                    "updated_at": mongodb::bson::to_bson(&Utc::now())?,
+                } },
+            )
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(AppError::Conflict(
+                "The product was changed meanwhile, try again".to_string(),
+            ));
+        }
+
+        let updated = products
+            .find_one(doc! { "_id": object_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+        Ok(updated.to_response())
+    }
+}
+
+// Trimmed alt text, `None` when blank
+fn alt_text(alt_text: Option<String>) -> Result<Option<String>> {
+    let alt_text = alt_text
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+
+    if alt_text
+        .as_ref()
+        .is_some_and(|text| text.chars().count() > MAX_ALT_TEXT_LENGTH)
+    {
+        return Err(AppError::ValidationError(format!(
+            "Alt text must be at most {} characters",
+            MAX_ALT_TEXT_LENGTH
+        )));
+    }
+
+    Ok(alt_text)
+}
@@ -0,0 +1,544 @@
+use crate::models::cart::CartResponse;
+use crate::models::product::{Product, ProductResponse, UpdateProductRequest};
+use crate::models::promotion::{
+    CreatePromotionRequest, Promotion, PromotionFilter, PromotionResponse, ScheduledPriceChange,
+    ScheduledPriceChangeResponse, SchedulePriceChangeRequest, UpdatePromotionRequest,
+};
+use crate::services::product::{ProductCollections, ProductService};
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::Collection;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const PROMOTION_KINDS: [&str; 2] = ["sale", "flash_sale"];
+
+pub struct PromotionService;
+
+impl PromotionService {
+    // Create a sale or flash sale (admin)
+    pub async fn create_promotion(
+        promotions: &Collection<Promotion>,
+        products: &Collection<Product>,
+        req: CreatePromotionRequest,
+    ) -> Result<PromotionResponse> {
+        let now = Utc::now();
+
+        let promotion = Promotion {
+            id: None,
+            name: req.name.trim().to_string(),
+            kind: req.kind,
+            product_ids: req.product_ids.filter(|ids| !ids.is_empty()),
+            category: req.category.filter(|c| !c.trim().is_empty()),
+            sale_price: req.sale_price,
+            percent_off: req.percent_off,
+            quantity_cap: req.quantity_cap,
+            sold_count: 0,
+            starts_at: req.starts_at,
+            ends_at: req.ends_at,
+            active: true,
+            created_at: now,
+            updated_at: now,
+        };
+
+        Self::validate(&promotion)?;
+
+        if let Some(product_ids) = &promotion.product_ids {
+            let ids = product_ids
+                .iter()
+                .map(|id| ObjectId::from_str(id))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|_| AppError::ValidationError("Invalid product ID".to_string()))?;
+
+            let found = products.count_documents(doc! { "_id": { "$in": &ids } }).await?;
+            if found != ids.len() as u64 {
+                return Err(AppError::NotFound("Product not found".to_string()));
+            }
+        }
+
+        let result = promotions.insert_one(&promotion).await?;
+        let inserted_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| AppError::InternalError)?;
+
+        Self::get_promotion(promotions, &inserted_id.to_hex()).await
+    }
+
+    // List promotions, newest first (admin)
+    pub async fn get_promotions(
+        promotions: &Collection<Promotion>,
+        filter: PromotionFilter,
+    ) -> Result<Vec<PromotionResponse>> {
+        let mut query = Document::new();
+        if let Some(kind) = filter.kind {
+            query.insert("kind", kind);
+        }
+        if let Some(active) = filter.active {
+            query.insert("active", active);
+        }
+
+        let mut cursor = promotions.find(query).sort(doc! { "starts_at": -1 }).await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?.to_response());
+        }
+
+        Ok(results)
+    }
+
+    // Get a single promotion (admin)
+    pub async fn get_promotion(
+        promotions: &Collection<Promotion>,
+        id: &str,
+    ) -> Result<PromotionResponse> {
+        Ok(Self::find_promotion(promotions, id).await?.to_response())
+    }
+
+    // Update a promotion, e.g. to move its end time or switch it off (admin)
+    pub async fn update_promotion(
+        promotions: &Collection<Promotion>,
+        id: &str,
+        req: UpdatePromotionRequest,
+    ) -> Result<PromotionResponse> {
+        let mut promotion = Self::find_promotion(promotions, id).await?;
+
+        if let Some(name) = req.name {
+            promotion.name = name.trim().to_string();
+        }
+        if let Some(sale_price) = req.sale_price {
+            promotion.sale_price = Some(sale_price);
+            promotion.percent_off = None;
+        }
+        if let Some(percent_off) = req.percent_off {
+            promotion.percent_off = Some(percent_off);
+            promotion.sale_price = None;
+        }
+        if let Some(quantity_cap) = req.quantity_cap {
+            promotion.quantity_cap = Some(quantity_cap);
+        }
+        if let Some(starts_at) = req.starts_at {
+            promotion.starts_at = starts_at;
+        }
+        if let Some(ends_at) = req.ends_at {
+            promotion.ends_at = ends_at;
+        }
+        if let Some(active) = req.active {
+            promotion.active = active;
+        }
+
+        Self::validate(&promotion)?;
+
+        // sold_count is left alone, checkouts may be counting units right now
+        promotions
+            .update_one(
+                doc! { "_id": promotion.id },
+                doc! {
+                    "$set": {
+                        "name": &promotion.name,
+                        "sale_price": promotion.sale_price,
+                        "percent_off": promotion.percent_off,
+                        "quantity_cap": promotion.quantity_cap,
+                        "starts_at": mongodb::bson::DateTime::from_chrono(promotion.starts_at),
+                        "ends_at": mongodb::bson::DateTime::from_chrono(promotion.ends_at),
+                        "active": promotion.active,
+                        "updated_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                    }
+                },
+            )
+            .await?;
+
+        Self::get_promotion(promotions, id).await
+    }
+
+    // Delete a promotion (admin)
+    pub async fn delete_promotion(promotions: &Collection<Promotion>, id: &str) -> Result<()> {
+        let object_id = Self::parse_id(id)?;
+
+        let result = promotions.delete_one(doc! { "_id": object_id }).await?;
+        if result.deleted_count == 0 {
+            return Err(AppError::NotFound("Promotion not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Promotions running right now
+    pub async fn active_promotions(promotions: &Collection<Promotion>) -> Result<Vec<Promotion>> {
+        let now = mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis());
+
+        let mut cursor = promotions
+            .find(doc! {
+                "active": true,
+                "starts_at": { "$lte": now },
+                "ends_at": { "$gt": now },
+            })
+            .await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?);
+        }
+
+        Ok(results)
+    }
+
+    // Lowest price any running promotion gives the product, with the promotion giving it
+    pub fn best_offer<'a>(
+        active: &'a [Promotion],
+        product_id: &str,
+        category: &str,
+        price: f64,
+    ) -> Option<(&'a Promotion, f64)> {
+        active
+            .iter()
+            .filter(|p| Self::applies_to(p, product_id, category))
+            .filter(|p| p.quantity_cap.is_none_or(|cap| p.sold_count < cap))
+            .map(|p| (p, Self::promotional_price(p, price)))
+            .filter(|(_, promo_price)| *promo_price < price)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    // Fill in the effective price of products
+    pub async fn price_products(
+        promotions: &Collection<Promotion>,
+        products: &mut [ProductResponse],
+    ) -> Result<()> {
+        let active = Self::active_promotions(promotions).await?;
+        if active.is_empty() {
+            return Ok(());
+        }
+
+        for product in products.iter_mut() {
+            if let Some((promotion, price)) =
+                Self::best_offer(&active, &product.id, &product.category, product.price)
+            {
+                product.effective_price = price;
+                product.promotion = Some(promotion.to_summary());
+            }
+        }
+
+        Ok(())
+    }
+
+    // Price cart lines at the current effective price of their product
+    pub async fn price_cart(
+        promotions: &Collection<Promotion>,
+        products: &Collection<Product>,
+        cart: &mut CartResponse,
+    ) -> Result<()> {
+        let active = Self::active_promotions(promotions).await?;
+
+        let ids: Vec<ObjectId> = cart
+            .items
+            .iter()
+            .filter_map(|i| ObjectId::from_str(&i.product_id).ok())
+            .collect();
+
+        let mut current: HashMap<String, Product> = HashMap::new();
+        let mut cursor = products.find(doc! { "_id": { "$in": ids } }).await?;
+        while let Some(result) = cursor.next().await {
+            let product = result?;
+            if let Some(id) = product.id {
+                current.insert(id.to_hex(), product);
+            }
+        }
+
+        for item in cart.items.iter_mut() {
+            // Lines of deleted products keep the price they were added at
+            let Some(product) = current.get(&item.product_id) else {
+                continue;
+            };
+
+            item.product_price =
+                Self::best_offer(&active, &item.product_id, &product.category, product.price)
+                    .map(|(_, price)| price)
+                    .unwrap_or(product.price);
+            item.line_total = item.product_price * item.quantity as f64;
+        }
+
+        cart.subtotal = cart.items.iter().map(|i| i.line_total).sum();
+        cart.total = cart.subtotal;
+
+        Ok(())
+    }
+
+    // Claim flash sale units for an order, false if not enough are left
+    pub async fn reserve_flash_sale(
+        promotions: &Collection<Promotion>,
+        promotion: &Promotion,
+        quantity: i32,
+    ) -> Result<bool> {
+        let Some(cap) = promotion.quantity_cap else {
+            return Ok(true);
+        };
+
+        let result = promotions
+            .update_one(
+                doc! {
+                    "_id": promotion.id,
+                    "active": true,
+                    "sold_count": { "$lte": cap - quantity },
+                },
+                doc! { "$inc": { "sold_count": quantity } },
+            )
+            .await?;
+
+        Ok(result.modified_count == 1)
+    }
+
+    // Give back flash sale units of a cancelled order
+    pub async fn release_flash_sale(
+        promotions: &Collection<Promotion>,
+        promotion_id: &str,
+        quantity: i32,
+    ) -> Result<()> {
+        let Ok(object_id) = ObjectId::from_str(promotion_id) else {
+            return Ok(());
+        };
+
+        promotions
+            .update_one(
+                doc! { "_id": object_id, "quantity_cap": { "$ne": null } },
+                doc! { "$inc": { "sold_count": -quantity } },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    // Schedule a permanent price change for a product (admin)
+    pub async fn schedule_price_change(
+        changes: &Collection<ScheduledPriceChange>,
+        products: &Collection<Product>,
+        product_id: &str,
+        req: SchedulePriceChangeRequest,
+    ) -> Result<ScheduledPriceChangeResponse> {
+        if req.price <= 0.0 {
+            return Err(AppError::ValidationError(
+                "Price must be greater than zero".to_string(),
+            ));
+        }
+        if req.effective_at <= Utc::now() {
+            return Err(AppError::ValidationError(
+                "Price changes must be scheduled in the future".to_string(),
+            ));
+        }
+
+        // Fails if the product doesn't exist
+        ProductService::get_product_by_id(products, product_id).await?;
+
+        let change = ScheduledPriceChange {
+            id: None,
+            product_id: product_id.to_string(),
+            price: req.price,
+            effective_at: req.effective_at,
+            applied_at: None,
+            created_at: Utc::now(),
+        };
+
+        let result = changes.insert_one(&change).await?;
+        let inserted_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| AppError::InternalError)?;
+
+        let change = changes
+            .find_one(doc! { "_id": inserted_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Price change not found".to_string()))?;
+
+        Ok(change.to_response())
+    }
+
+    // List the scheduled price changes of a product, soonest first (admin)
+    pub async fn get_price_changes(
+        changes: &Collection<ScheduledPriceChange>,
+        product_id: &str,
+    ) -> Result<Vec<ScheduledPriceChangeResponse>> {
+        let mut cursor = changes
+            .find(doc! { "product_id": product_id })
+            .sort(doc! { "effective_at": 1 })
+            .await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?.to_response());
+        }
+
+        Ok(results)
+    }
+
+    // Cancel a price change that hasn't been applied yet (admin)
+    pub async fn cancel_price_change(
+        changes: &Collection<ScheduledPriceChange>,
+        id: &str,
+    ) -> Result<()> {
+        let object_id = ObjectId::from_str(id)
+            .map_err(|_| AppError::ValidationError("Invalid price change ID".to_string()))?;
+
+        let result = changes
+            .delete_one(doc! { "_id": object_id, "applied_at": null })
+            .await?;
+
+        if result.deleted_count == 0 {
+            return match changes.find_one(doc! { "_id": object_id }).await? {
+                Some(_) => Err(AppError::ValidationError(
+                    "This price change was already applied".to_string(),
+                )),
+                None => Err(AppError::NotFound("Price change not found".to_string())),
+            };
+        }
+
+        Ok(())
+    }
+
+    // Apply the price changes that are due, returns how many were applied
+    pub async fn apply_due_price_changes(
+        changes: &Collection<ScheduledPriceChange>,
+        collections: &ProductCollections,
+    ) -> Result<u64> {
+        let mut applied = 0;
+
+        loop {
+            let now = mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis());
+
+            // Claim one change at a time so concurrent runs never apply it twice
+            let change = changes
+                .find_one_and_update(
+                    doc! { "applied_at": null, "effective_at": { "$lte": now } },
+                    doc! { "$set": { "applied_at": now } },
+                )
+                .sort(doc! { "effective_at": 1 })
+                .await?;
+
+            let Some(change) = change else {
+                break;
+            };
+
+            let update = UpdateProductRequest {
+                name: None,
+                description: None,
+                price: Some(change.price),
+                stock_quantity: None,
+                weight_kg: None,
+                tags: None,
+            };
+
+            let updated =
+                ProductService::update_product(collections, &change.product_id, update, "scheduler")
+                    .await;
+
+            match updated {
+                Ok(_) => applied += 1,
+                Err(AppError::NotFound(_)) => {
+                    tracing::warn!(
+                        "Skipped price change for deleted product {}",
+                        change.product_id
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(applied)
+    }
+
+    fn validate(promotion: &Promotion) -> Result<()> {
+        if promotion.name.is_empty() {
+            return Err(AppError::ValidationError("Promotion name is required".to_string()));
+        }
+        if !PROMOTION_KINDS.contains(&promotion.kind.as_str()) {
+            return Err(AppError::ValidationError(format!(
+                "Promotion kind must be one of: {}",
+                PROMOTION_KINDS.join(", ")
+            )));
+        }
+        if promotion.product_ids.is_some() == promotion.category.is_some() {
+            return Err(AppError::ValidationError(
+                "A promotion applies to either a list of products or a category".to_string(),
+            ));
+        }
+
+        match (promotion.sale_price, promotion.percent_off) {
+            (Some(sale_price), None) => {
+                if sale_price <= 0.0 {
+                    return Err(AppError::ValidationError(
+                        "Sale price must be greater than zero".to_string(),
+                    ));
+                }
+                if promotion.category.is_some() {
+                    return Err(AppError::ValidationError(
+                        "Category promotions must use percent_off".to_string(),
+                    ));
+                }
+            }
+            (None, Some(percent_off)) => {
+                if percent_off <= 0.0 || percent_off >= 100.0 {
+                    return Err(AppError::ValidationError(
+                        "percent_off must be between 0 and 100".to_string(),
+                    ));
+                }
+            }
+            _ => {
+                return Err(AppError::ValidationError(
+                    "Set either sale_price or percent_off".to_string(),
+                ));
+            }
+        }
+
+        if promotion.ends_at <= promotion.starts_at {
+            return Err(AppError::ValidationError(
+                "ends_at must be after starts_at".to_string(),
+            ));
+        }
+
+        match (promotion.kind.as_str(), promotion.quantity_cap) {
+            ("flash_sale", None) => Err(AppError::ValidationError(
+                "Flash sales need a quantity_cap".to_string(),
+            )),
+            ("flash_sale", Some(cap)) if cap <= 0 => Err(AppError::ValidationError(
+                "quantity_cap must be greater than zero".to_string(),
+            )),
+            ("sale", Some(_)) => Err(AppError::ValidationError(
+                "Only flash sales have a quantity_cap".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn applies_to(promotion: &Promotion, product_id: &str, category: &str) -> bool {
+        match (&promotion.product_ids, &promotion.category) {
+            (Some(ids), _) => ids.iter().any(|id| id == product_id),
+            (None, Some(c)) => c == category,
+            (None, None) => false,
+        }
+    }
+
+    fn promotional_price(promotion: &Promotion, price: f64) -> f64 {
+        let promo_price = match (promotion.sale_price, promotion.percent_off) {
+            (Some(sale_price), _) => sale_price,
+            (None, Some(percent_off)) => price * (100.0 - percent_off) / 100.0,
+            (None, None) => price,
+        };
+
+        (promo_price * 100.0).round() / 100.0
+    }
+
+    async fn find_promotion(promotions: &Collection<Promotion>, id: &str) -> Result<Promotion> {
+        let object_id = Self::parse_id(id)?;
+
+        promotions
+            .find_one(doc! { "_id": object_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Promotion not found".to_string()))
+    }
+
+    fn parse_id(id: &str) -> Result<ObjectId> {
+        ObjectId::from_str(id)
+            .map_err(|_| AppError::ValidationError("Invalid promotion ID".to_string()))
+    }
+}
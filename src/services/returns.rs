@@ -0,0 +1,643 @@
+use crate::config::app::ReturnsConfig;
+use crate::db::AppState;
+use crate::models::media::{Media, MediaReference};
+use crate::models::order::Order;
+use crate::models::product::Product;
+use crate::models::returns::{
+    CreateReturnRequest, ReturnFilter, ReturnItem, ReturnPolicy, ReturnPolicyResponse,
+    ReturnRequest, ReturnResponse,
+};
+use crate::models::user::User;
+use crate::services::media::MediaService;
+use crate::services::tax::TaxService;
+use crate::utils::error::{AppError, Result};
+use chrono::{Duration, Utc};
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use mongodb::Collection;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+const MAX_RETURN_PHOTOS: usize = 5;
+
+// Collections touched when requesting a return
+pub struct ReturnCollections {
+    pub returns: Collection<ReturnRequest>,
+    pub orders: Collection<Order>,
+    pub products: Collection<Product>,
+    pub policies: Collection<ReturnPolicy>,
+    pub media: Collection<Media>,
+}
+
+impl ReturnCollections {
+    pub fn from_state(state: &AppState) -> Self {
+        ReturnCollections {
+            returns: state.collection("MONGO_RETURNS_COLLECTION"),
+            orders: state.collection("MONGO_ORDERS_COLLECTION"),
+            products: state.collection("MONGO_PRODUCTS_COLLECTION"),
+            policies: state.collection("MONGO_RETURN_POLICIES_COLLECTION"),
+            media: state.collection("MONGO_MEDIA_COLLECTION"),
+        }
+    }
+}
+
+pub struct ReturnService;
+
+impl ReturnService {
+    // Request a return for items of a completed order. Photos must be images the customer
+    // uploaded to the media library.
+    pub async fn create_return(
+        c: &ReturnCollections,
+        config: &ReturnsConfig,
+        user_id: &str,
+        req: CreateReturnRequest,
+    ) -> Result<ReturnResponse> {
+        if req.reason.trim().is_empty() {
+            return Err(AppError::ValidationError("A reason is required".to_string()));
+        }
+        if req.items.is_empty() {
+            return Err(AppError::ValidationError(
+                "At least one item must be returned".to_string(),
+            ));
+        }
+
+        let mut photos = req.photos.unwrap_or_default();
+        let mut seen = HashSet::new();
+        photos.retain(|url| seen.insert(url.clone()));
+        if photos.len() > MAX_RETURN_PHOTOS {
+            return Err(AppError::ValidationError(format!(
+                "Maximum {} photos allowed",
+                MAX_RETURN_PHOTOS
+            )));
+        }
+
+        let order_id = ObjectId::from_str(&req.order_id)
+            .map_err(|_| AppError::ValidationError("Invalid order ID".to_string()))?;
+
+        let order = c
+            .orders
+            .find_one(doc! { "_id": order_id, "user_id": user_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+
+        Self::ensure_returnable(&order)?;
+
+        let paid_unit_prices = Self::paid_unit_prices(&order);
+        let completed_at = order.completed_at.unwrap_or(order.created_at);
+        let already_returned = Self::returned_quantities(&c.returns, &req.order_id).await?;
+        let default_window = config.window_days;
+
+        let mut items: Vec<ReturnItem> = Vec::new();
+        for item_req in req.items {
+            if item_req.quantity <= 0 {
+                return Err(AppError::ValidationError(
+                    "Quantity must be greater than zero".to_string(),
+                ));
+            }
+            if items.iter().any(|i| i.product_id == item_req.product_id) {
+                return Err(AppError::ValidationError(
+                    "Each product can only be listed once".to_string(),
+                ));
+            }
+
+            let (order_item, paid_unit_price) = order
+                .items
+                .iter()
+                .zip(&paid_unit_prices)
+                .find(|(i, _)| i.product_id == item_req.product_id)
+                .ok_or_else(|| {
+                    AppError::ValidationError(format!(
+                        "Product {} is not part of this order",
+                        item_req.product_id
+                    ))
+                })?;
+
+            let returnable = order_item.quantity
+                - already_returned.get(&order_item.product_id).copied().unwrap_or(0);
+            if item_req.quantity > returnable {
+                return Err(AppError::ValidationError(format!(
+                    "Only {} of {} can be returned",
+                    returnable.max(0),
+                    order_item.product_name
+                )));
+            }
+
+            // The window depends on the product's category; deleted products use the default
+            let category = match ObjectId::from_str(&order_item.product_id) {
+                Ok(product_id) => c
+                    .products
+                    .find_one(doc! { "_id": product_id })
+                    .await?
+                    .map(|p| p.category),
+                Err(_) => None,
+            };
+            let window_days = match category {
+                Some(category) => c
+                    .policies
+                    .find_one(doc! { "category": category })
+                    .await?
+                    .map(|p| p.window_days)
+                    .unwrap_or(default_window),
+                None => default_window,
+            };
+
+            if Utc::now() > completed_at + Duration::days(window_days) {
+                return Err(AppError::ValidationError(format!(
+                    "The return window for {} has closed",
+                    order_item.product_name
+                )));
+            }
+
+            items.push(ReturnItem {
+                product_id: order_item.product_id.clone(),
+                product_name: order_item.product_name.clone(),
+                quantity: item_req.quantity,
+                unit_price: order_item.price,
+                paid_unit_price: *paid_unit_price,
+            });
+        }
+
+        let refund_amount: f64 = items
+            .iter()
+            .map(|i| i.paid_unit_price * i.quantity as f64)
+            .sum();
+        let refund_amount = (refund_amount * 100.0).round() / 100.0;
+
+        // Referencing the photos first keeps the sweeper from deleting them meanwhile
+        let return_id = ObjectId::new();
+        let reference = MediaReference::new("return", &return_id.to_hex());
+        MediaService::reference_owned(&c.media, user_id, &photos, &reference).await?;
+
+        let now = Utc::now();
+        let return_request = ReturnRequest {
+            id: Some(return_id),
+            order_id: req.order_id,
+            user_id: user_id.to_string(),
+            items,
+            reason: req.reason,
+            photos,
+            status: "requested".to_string(),
+            resolution: None,
+            refund_amount,
+            admin_note: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        if let Err(e) = c.returns.insert_one(return_request).await {
+            MediaService::release_all(&c.media, &reference).await?;
+            return Err(e.into());
+        }
+
+        Self::find_return(&c.returns, doc! { "_id": return_id }).await
+    }
+
+    // Attach uploaded photos to a return that is still awaiting review
+    pub async fn add_photos(
+        returns: &Collection<ReturnRequest>,
+        id: &str,
+        user_id: &str,
+        urls: Vec<String>,
+    ) -> Result<ReturnResponse> {
+        let object_id = Self::parse_id(id)?;
+
+        let existing = returns
+            .find_one(doc! { "_id": object_id, "user_id": user_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Return not found".to_string()))?;
+
+        if existing.status != "requested" {
+            return Err(AppError::ValidationError(
+                "Photos can only be added before the return is reviewed".to_string(),
+            ));
+        }
+        if existing.photos.len() + urls.len() > MAX_RETURN_PHOTOS {
+            return Err(AppError::ValidationError(format!(
+                "Maximum {} photos allowed",
+                MAX_RETURN_PHOTOS
+            )));
+        }
+
+        returns
+            .update_one(
+                doc! { "_id": object_id },
+                doc! {
+                    "$push": { "photos": { "$each": urls } },
+                    "$set": { "updated_at": Self::now() },
+                },
+            )
+            .await?;
+
+        Self::find_return(returns, doc! { "_id": object_id }).await
+    }
+
+    // List returns of a user
+    pub async fn get_user_returns(
+        returns: &Collection<ReturnRequest>,
+        user_id: &str,
+    ) -> Result<Vec<ReturnResponse>> {
+        Self::find_returns(returns, doc! { "user_id": user_id }).await
+    }
+
+    // Get a single return of a user
+    pub async fn get_user_return(
+        returns: &Collection<ReturnRequest>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<ReturnResponse> {
+        let object_id = Self::parse_id(id)?;
+        Self::find_return(returns, doc! { "_id": object_id, "user_id": user_id }).await
+    }
+
+    // List all returns (admin)
+    pub async fn get_returns(
+        returns: &Collection<ReturnRequest>,
+        filter: ReturnFilter,
+    ) -> Result<Vec<ReturnResponse>> {
+        let mut query = Document::new();
+        if let Some(status) = filter.status {
+            query.insert("status", status);
+        }
+
+        Self::find_returns(returns, query).await
+    }
+
+    // What the customer paid for one unit of each order item: the coupon discount spread
+    // the same way tax was charged, plus tax that wasn't included in the price
+    fn paid_unit_prices(order: &Order) -> Vec<f64> {
+        let amounts: Vec<f64> = order
+            .items
+            .iter()
+            .map(|i| i.price * i.quantity as f64)
+            .collect();
+
+        order
+            .items
+            .iter()
+            .zip(TaxService::allocate_discount(&amounts, order.discount_amount))
+            .map(|(item, amount)| {
+                let exclusive_tax = item
+                    .tax
+                    .as_ref()
+                    .filter(|t| !t.inclusive)
+                    .map(|t| t.amount)
+                    .unwrap_or(0.0);
+                (amount + exclusive_tax) / item.quantity.max(1) as f64
+            })
+            .collect()
+    }
+
+    // Approve a requested return (admin)
+    pub async fn approve_return(
+        returns: &Collection<ReturnRequest>,
+        id: &str,
+        note: Option<String>,
+    ) -> Result<ReturnResponse> {
+        Self::transition(returns, id, "requested", "approved", note).await
+    }
+
+    // Reject a requested return (admin)
+    pub async fn reject_return(
+        returns: &Collection<ReturnRequest>,
+        id: &str,
+        note: Option<String>,
+    ) -> Result<ReturnResponse> {
+        Self::transition(returns, id, "requested", "rejected", note).await
+    }
+
+    // Mark the returned items as received at the warehouse (admin)
+    pub async fn mark_received(
+        returns: &Collection<ReturnRequest>,
+        id: &str,
+        note: Option<String>,
+    ) -> Result<ReturnResponse> {
+        Self::transition(returns, id, "approved", "received", note).await
+    }
+
+    // Record a refund on the order or credit the customer for received items (admin).
+    // Refunds are bookkeeping only: nothing is charged back through the payment provider,
+    // the admin sends the money back by hand, e.g. from the provider's dashboard.
+    pub async fn resolve_return(
+        returns: &Collection<ReturnRequest>,
+        orders: &Collection<Order>,
+        users: &Collection<User>,
+        id: &str,
+        resolution: &str,
+        note: Option<String>,
+    ) -> Result<ReturnResponse> {
+        let status = match resolution {
+            "refund" => "refunded",
+            "store_credit" => "credited",
+            _ => {
+                return Err(AppError::ValidationError(
+                    "Resolution must be either refund or store_credit".to_string(),
+                ))
+            }
+        };
+
+        let object_id = Self::parse_id(id)?;
+        let mut set_doc = doc! {
+            "status": status,
+            "resolution": resolution,
+            "updated_at": Self::now(),
+        };
+        if let Some(note) = note {
+            set_doc.insert("admin_note", note);
+        }
+
+        // Only one admin can move the return out of "received"
+        let return_request = returns
+            .find_one_and_update(
+                doc! { "_id": object_id, "status": "received" },
+                doc! { "$set": set_doc },
+            )
+            .await?;
+
+        let Some(return_request) = return_request else {
+            return Err(Self::transition_error(returns, object_id, "received").await);
+        };
+
+        if resolution == "refund" {
+            Self::record_manual_refund(orders, &return_request).await?;
+        } else {
+            let user_id = ObjectId::from_str(&return_request.user_id)
+                .map_err(|_| AppError::InternalError)?;
+            users
+                .update_one(
+                    doc! { "_id": user_id },
+                    doc! { "$inc": { "store_credit": return_request.refund_amount } },
+                )
+                .await?;
+        }
+
+        Self::find_return(returns, doc! { "_id": object_id }).await
+    }
+
+    // Add the refund to what the order has been paid back, the payout itself happens elsewhere
+    async fn record_manual_refund(
+        orders: &Collection<Order>,
+        return_request: &ReturnRequest,
+    ) -> Result<()> {
+        let order_id =
+            ObjectId::from_str(&return_request.order_id).map_err(|_| AppError::InternalError)?;
+        orders
+            .update_one(
+                doc! { "_id": order_id },
+                doc! { "$inc": { "refunded_amount": return_request.refund_amount } },
+            )
+            .await?;
+
+        tracing::info!(
+            "Manual refund of {:.2} recorded for order {}, to be paid out by an admin",
+            return_request.refund_amount,
+            return_request.order_id
+        );
+        Ok(())
+    }
+
+    // Set the return window for a category (admin)
+    pub async fn upsert_policy(
+        policies: &Collection<ReturnPolicy>,
+        category: &str,
+        window_days: i64,
+    ) -> Result<ReturnPolicyResponse> {
+        if window_days < 0 {
+            return Err(AppError::ValidationError(
+                "Return window cannot be negative".to_string(),
+            ));
+        }
+
+        policies
+            .update_one(
+                doc! { "category": category },
+                doc! { "$set": { "window_days": window_days, "updated_at": Self::now() } },
+            )
+            .upsert(true)
+            .await?;
+
+        let policy = policies
+            .find_one(doc! { "category": category })
+            .await?
+            .ok_or_else(|| AppError::InternalError)?;
+
+        Ok(policy.to_response())
+    }
+
+    // List per-category return windows (admin)
+    pub async fn get_policies(
+        policies: &Collection<ReturnPolicy>,
+    ) -> Result<Vec<ReturnPolicyResponse>> {
+        let mut cursor = policies.find(doc! {}).sort(doc! { "category": 1 }).await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?.to_response());
+        }
+
+        Ok(results)
+    }
+
+    // Quantities per product already covered by non-rejected returns of an order
+    async fn returned_quantities(
+        returns: &Collection<ReturnRequest>,
+        order_id: &str,
+    ) -> Result<HashMap<String, i32>> {
+        let mut cursor = returns
+            .find(doc! { "order_id": order_id, "status": { "$ne": "rejected" } })
+            .await?;
+
+        let mut quantities = HashMap::new();
+        while let Some(result) = cursor.next().await {
+            for item in result?.items {
+                *quantities.entry(item.product_id).or_insert(0) += item.quantity;
+            }
+        }
+
+        Ok(quantities)
+    }
+
+    async fn transition(
+        returns: &Collection<ReturnRequest>,
+        id: &str,
+        from: &str,
+        to: &str,
+        note: Option<String>,
+    ) -> Result<ReturnResponse> {
+        let object_id = Self::parse_id(id)?;
+
+        let mut set_doc = doc! { "status": to, "updated_at": Self::now() };
+        if let Some(note) = note {
+            set_doc.insert("admin_note", note);
+        }
+
+        let result = returns
+            .update_one(doc! { "_id": object_id, "status": from }, doc! { "$set": set_doc })
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(Self::transition_error(returns, object_id, from).await);
+        }
+
+        Self::find_return(returns, doc! { "_id": object_id }).await
+    }
+
+    async fn transition_error(
+        returns: &Collection<ReturnRequest>,
+        object_id: ObjectId,
+        expected: &str,
+    ) -> AppError {
+        match returns.find_one(doc! { "_id": object_id }).await {
+            Ok(Some(existing)) => AppError::ValidationError(format!(
+                "Return is {}, expected {}",
+                existing.status, expected
+            )),
+            Ok(None) => AppError::NotFound("Return not found".to_string()),
+            Err(e) => e.into(),
+        }
+    }
+
+    async fn find_return(
+        returns: &Collection<ReturnRequest>,
+        query: Document,
+    ) -> Result<ReturnResponse> {
+        let return_request = returns
+            .find_one(query)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Return not found".to_string()))?;
+
+        Ok(return_request.to_response())
+    }
+
+    async fn find_returns(
+        returns: &Collection<ReturnRequest>,
+        query: Document,
+    ) -> Result<Vec<ReturnResponse>> {
+        let mut cursor = returns.find(query).sort(doc! { "created_at": -1 }).await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?.to_response());
+        }
+
+        Ok(results)
+    }
+
+    // Only delivered orders that were paid for can be refunded or credited
+    fn ensure_returnable(order: &Order) -> Result<()> {
+        if order.order_status != "completed" {
+            return Err(AppError::ValidationError(
+                "Only completed orders can be returned".to_string(),
+            ));
+        }
+        if order.payment_status != "completed" {
+            return Err(AppError::ValidationError(format!(
+                "Orders with a {} payment can't be returned",
+                order.payment_status
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn parse_id(id: &str) -> Result<ObjectId> {
+        ObjectId::from_str(id).map_err(|_| AppError::ValidationError("Invalid return ID".to_string()))
+    }
+
+    fn now() -> mongodb::bson::DateTime {
+        mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::order::OrderItem;
+    use crate::models::tax::ItemTax;
+
+    fn order(order_status: &str, payment_status: &str) -> Order {
+        Order {
+            id: Some(ObjectId::new()),
+            user_id: Some("user-1".to_string()),
+            guest_email: None,
+            guest_phone: None,
+            items: Vec::new(),
+            total_amount: 1000.0,
+            discount_amount: 0.0,
+            tax_amount: 0.0,
+            tax_lines: Vec::new(),
+            shipping_fee: 0.0,
+            coupon_code: None,
+            payment_method: "paystack".to_string(),
+            payment_reference: Some("ref-1".to_string()),
+            payment_status: payment_status.to_string(),
+            order_status: order_status.to_string(),
+            shipping_address: None,
+            created_at: Utc::now(),
+            completed_at: Some(Utc::now()),
+            refunded_amount: 0.0,
+            payment_due_at: None,
+            payment_proof: None,
+            payment_note: None,
+        }
+    }
+
+    #[test]
+    fn paid_completed_orders_are_returnable() {
+        assert!(ReturnService::ensure_returnable(&order("completed", "completed")).is_ok());
+    }
+
+    #[test]
+    fn unpaid_orders_are_not_returnable() {
+        for payment_status in ["pending", "failed", "reversed"] {
+            let result = ReturnService::ensure_returnable(&order("completed", payment_status));
+            assert!(
+                matches!(result, Err(AppError::ValidationError(_))),
+                "{} payment was accepted",
+                payment_status
+            );
+        }
+    }
+
+    #[test]
+    fn undelivered_orders_are_not_returnable() {
+        let result = ReturnService::ensure_returnable(&order("shipped", "completed"));
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+    }
+
+    #[test]
+    fn refunds_use_the_discounted_price_plus_exclusive_tax() {
+        let mut order = order("completed", "completed");
+        order.items = vec![
+            OrderItem {
+                product_id: "taxed".to_string(),
+                product_name: "Taxed".to_string(),
+                quantity: 2,
+                price: 100.0,
+                promotion_id: None,
+                tax: Some(ItemTax {
+                    rule: "VAT".to_string(),
+                    rate: 7.5,
+                    inclusive: false,
+                    taxable_amount: 180.0,
+                    amount: 13.5,
+                }),
+            },
+            OrderItem {
+                product_id: "untaxed".to_string(),
+                product_name: "Untaxed".to_string(),
+                quantity: 4,
+                price: 50.0,
+                promotion_id: None,
+                tax: None,
+            },
+        ];
+        // A 40.00 coupon, split evenly over the two 200.00 lines
+        order.discount_amount = 40.0;
+        order.coupon_code = Some("SAVE40".to_string());
+
+        let paid = ReturnService::paid_unit_prices(&order);
+
+        assert!((paid[0] - 96.75).abs() < 1e-9, "taxed unit paid {}", paid[0]);
+        assert!((paid[1] - 45.0).abs() < 1e-9, "untaxed unit paid {}", paid[1]);
+    }
+}
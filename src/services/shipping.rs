@@ -0,0 +1,253 @@
+use crate::models::product::Product;
+use crate::models::shipping::{
+    Address, ShippingQuote, ShippingZone, ShippingZoneRequest, ShippingZoneResponse,
+};
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const RATE_TYPES: [&str; 2] = ["flat", "weight_based"];
+
+pub struct ShippingService;
+
+impl ShippingService {
+    // Create a shipping zone (admin)
+    pub async fn create_zone(
+        zones: &Collection<ShippingZone>,
+        req: ShippingZoneRequest,
+    ) -> Result<ShippingZoneResponse> {
+        Self::validate_zone(&req)?;
+
+        let now = Utc::now();
+        let zone = ShippingZone {
+            id: None,
+            name: req.name.trim().to_string(),
+            states: req.states,
+            cities: req.cities.filter(|c| !c.is_empty()),
+            rate_type: req.rate_type,
+            fee: req.fee,
+            per_kg: req.per_kg,
+            free_over: req.free_over,
+            active: req.active.unwrap_or(true),
+            created_at: now,
+            updated_at: now,
+        };
+
+        let result = zones.insert_one(&zone).await?;
+        let inserted_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| AppError::InternalError)?;
+
+        Ok(Self::find_zone(zones, inserted_id).await?.to_response())
+    }
+
+    // List shipping zones (admin)
+    pub async fn get_zones(zones: &Collection<ShippingZone>) -> Result<Vec<ShippingZoneResponse>> {
+        let mut cursor = zones.find(doc! {}).sort(doc! { "name": 1 }).await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?.to_response());
+        }
+
+        Ok(results)
+    }
+
+    // Replace the settings of a shipping zone (admin)
+    pub async fn update_zone(
+        zones: &Collection<ShippingZone>,
+        id: &str,
+        req: ShippingZoneRequest,
+    ) -> Result<ShippingZoneResponse> {
+        let object_id = Self::parse_id(id)?;
+        Self::validate_zone(&req)?;
+
+        let result = zones
+            .update_one(
+                doc! { "_id": object_id },
+                doc! {
+                    "$set": {
+                        "name": req.name.trim(),
+                        "states": req.states,
+                        "cities": req.cities.filter(|c| !c.is_empty()),
+                        "rate_type": req.rate_type,
+                        "fee": req.fee,
+                        "per_kg": req.per_kg,
+                        "free_over": req.free_over,
+                        "active": req.active.unwrap_or(true),
+                        "updated_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                    }
+                },
+            )
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(AppError::NotFound("Shipping zone not found".to_string()));
+        }
+
+        Ok(Self::find_zone(zones, object_id).await?.to_response())
+    }
+
+    // Delete a shipping zone (admin)
+    pub async fn delete_zone(zones: &Collection<ShippingZone>, id: &str) -> Result<()> {
+        let object_id = Self::parse_id(id)?;
+
+        let result = zones.delete_one(doc! { "_id": object_id }).await?;
+        if result.deleted_count == 0 {
+            return Err(AppError::NotFound("Shipping zone not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Delivery fee for a parcel going to `address`
+    pub async fn quote(
+        zones: &Collection<ShippingZone>,
+        address: &Address,
+        weight_kg: f64,
+        items_subtotal: f64,
+    ) -> Result<ShippingQuote> {
+        let zone = Self::zone_for(zones, address).await?;
+
+        let fee = if zone.free_over.is_some_and(|threshold| items_subtotal >= threshold) {
+            0.0
+        } else {
+            match zone.rate_type.as_str() {
+                "weight_based" => zone.fee + zone.per_kg.unwrap_or(0.0) * weight_kg,
+                _ => zone.fee,
+            }
+        };
+
+        Ok(ShippingQuote {
+            zone: zone.name,
+            weight_kg,
+            items_subtotal,
+            fee: (fee * 100.0).round() / 100.0,
+        })
+    }
+
+    // Total weight of (product ID, quantity) lines, products without a weight count as 0
+    pub async fn total_weight(
+        products: &Collection<Product>,
+        lines: &[(String, i32)],
+    ) -> Result<f64> {
+        let ids: Vec<ObjectId> = lines
+            .iter()
+            .filter_map(|(id, _)| ObjectId::from_str(id).ok())
+            .collect();
+
+        let mut weights: HashMap<String, f64> = HashMap::new();
+        let mut cursor = products.find(doc! { "_id": { "$in": ids } }).await?;
+        while let Some(result) = cursor.next().await {
+            let product = result?;
+            if let (Some(id), Some(weight_kg)) = (product.id, product.weight_kg) {
+                weights.insert(id.to_hex(), weight_kg);
+            }
+        }
+
+        Ok(lines
+            .iter()
+            .map(|(id, quantity)| weights.get(id).copied().unwrap_or(0.0) * *quantity as f64)
+            .sum())
+    }
+
+    // Check that an address has everything a courier needs
+    pub fn validate_address(address: &Address) -> Result<()> {
+        let required = [
+            ("recipient_name", &address.recipient_name),
+            ("line1", &address.line1),
+            ("city", &address.city),
+            ("state", &address.state),
+        ];
+        for (field, value) in required {
+            if value.trim().is_empty() {
+                return Err(AppError::ValidationError(format!(
+                    "Address {} is required",
+                    field
+                )));
+            }
+        }
+
+        let has_lga = address.lga.as_deref().is_some_and(|l| !l.trim().is_empty());
+        let has_postcode = address.postcode.as_deref().is_some_and(|p| !p.trim().is_empty());
+        if !has_lga && !has_postcode {
+            return Err(AppError::ValidationError(
+                "Address needs an LGA or a postcode".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Zone covering the address, a zone listing the city wins over a state-wide zone
+    async fn zone_for(zones: &Collection<ShippingZone>, address: &Address) -> Result<ShippingZone> {
+        let mut cursor = zones.find(doc! { "active": true }).await?;
+
+        let mut state_match = None;
+        while let Some(result) = cursor.next().await {
+            let zone = result?;
+            if !zone.states.iter().any(|s| s.eq_ignore_ascii_case(address.state.trim())) {
+                continue;
+            }
+
+            match &zone.cities {
+                Some(cities) => {
+                    if cities.iter().any(|c| c.eq_ignore_ascii_case(address.city.trim())) {
+                        return Ok(zone);
+                    }
+                }
+                None => {
+                    state_match.get_or_insert(zone);
+                }
+            }
+        }
+
+        state_match.ok_or_else(|| {
+            AppError::ValidationError(format!("We don't deliver to {} yet", address.state))
+        })
+    }
+
+    fn validate_zone(req: &ShippingZoneRequest) -> Result<()> {
+        if req.name.trim().is_empty() {
+            return Err(AppError::ValidationError("Zone name is required".to_string()));
+        }
+        if req.states.is_empty() {
+            return Err(AppError::ValidationError(
+                "A zone must cover at least one state".to_string(),
+            ));
+        }
+        if !RATE_TYPES.contains(&req.rate_type.as_str()) {
+            return Err(AppError::ValidationError(format!(
+                "Rate type must be one of: {}",
+                RATE_TYPES.join(", ")
+            )));
+        }
+        if req.fee < 0.0 || req.per_kg.is_some_and(|p| p < 0.0) {
+            return Err(AppError::ValidationError("Fees cannot be negative".to_string()));
+        }
+        if req.rate_type == "weight_based" && req.per_kg.is_none() {
+            return Err(AppError::ValidationError(
+                "Weight based rates need a per_kg fee".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn find_zone(zones: &Collection<ShippingZone>, id: ObjectId) -> Result<ShippingZone> {
+        zones
+            .find_one(doc! { "_id": id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Shipping zone not found".to_string()))
+    }
+
+    fn parse_id(id: &str) -> Result<ObjectId> {
+        ObjectId::from_str(id)
+            .map_err(|_| AppError::ValidationError("Invalid shipping zone ID".to_string()))
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use super::{exact_size, ByteChunks, ObjectStorage, PresignedUpload};
+use crate::config::app::StorageConfig;
+use crate::utils::error::{AppError, Result};
+
+/// Keeps files on the local disk, served by the API under /uploads.
+/// Presigned uploads are received by the API under /api/upload/direct.
+/// Meant for development and tests.
+pub struct LocalStorage {
+    dir: PathBuf,
+    public_url: String,
+    upload_url: String,
+    signing_key: String,
+}
+
+impl LocalStorage {
+    pub fn new(dir: &str, public_url: &str, upload_url: &str, signing_key: &str) -> Self {
+        LocalStorage {
+            dir: PathBuf::from(dir),
+            public_url: public_url.trim_end_matches('/').to_string(),
+            upload_url: upload_url.trim_end_matches('/').to_string(),
+            signing_key: signing_key.to_string(),
+        }
+    }
+
+    pub fn from_config(config: &StorageConfig) -> Self {
+        Self::new(
+            &config.local_dir,
+            &config.local_public_url,
+            &config.local_upload_url,
+            &config.local_signing_key,
+        )
+    }
+
+    // HMAC over everything a presigned upload is allowed to do
+    fn signature(
+        &self,
+        key: &str,
+        content_type: &str,
+        size: u64,
+        expires: i64,
+    ) -> Result<Hmac<Sha256>> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.signing_key.as_bytes())
+            .map_err(|_| AppError::InternalError)?;
+        mac.update(format!("{}\n{}\n{}\n{}", key, content_type, size, expires).as_bytes());
+
+        Ok(mac)
+    }
+
+    // Path of a key inside the storage dir, refusing keys that would escape it
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        let key = Path::new(key);
+        if key.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(AppError::ValidationError("Invalid file key".to_string()));
+        }
+
+        Ok(self.dir.join(key))
+    }
+
+    async fn create_parent(path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                tracing::error!("Could not create {}: {:?}", parent.display(), e);
+                AppError::InternalError
+            })?;
+        }
+        Ok(())
+    }
+
+    async fn write_chunks(path: &Path, mut chunks: ByteChunks) -> Result<()> {
+        let write_error = |e: std::io::Error| {
+            tracing::error!("Could not write {}: {:?}", path.display(), e);
+            AppError::InternalError
+        };
+
+        let mut file = tokio::fs::File::create(path).await.map_err(write_error)?;
+        while let Some(chunk) = chunks.next().await {
+            file.write_all(&chunk?).await.map_err(write_error)?;
+        }
+        file.flush().await.map_err(write_error)
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for LocalStorage {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn url_for(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>, _content_type: &str) -> Result<String> {
+        let path = self.path_for(key)?;
+        Self::create_parent(&path).await?;
+
+        tokio::fs::write(&path, data).await.map_err(|e| {
+            tracing::error!("Could not write {}: {:?}", path.display(), e);
+            AppError::InternalError
+        })?;
+
+        tracing::info!("✅ Image stored at {}", path.display());
+
+        Ok(self.url_for(key))
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        chunks: ByteChunks,
+        size: u64,
+        _content_type: &str,
+    ) -> Result<String> {
+        let path = self.path_for(key)?;
+        Self::create_parent(&path).await?;
+
+        // Written beside the file and renamed once complete, so a broken upload leaves nothing
+        let partial = path.with_extension(format!("{}.part", Uuid::new_v4()));
+        let mut stored = Self::write_chunks(&partial, exact_size(chunks, size)).await;
+        if stored.is_ok() {
+            stored = tokio::fs::rename(&partial, &path).await.map_err(|e| {
+                tracing::error!("Could not move {}: {:?}", partial.display(), e);
+                AppError::InternalError
+            });
+        }
+        if stored.is_err() {
+            let _ = tokio::fs::remove_file(&partial).await;
+        }
+        stored?;
+
+        tracing::info!("✅ Image stored at {}", path.display());
+
+        Ok(self.url_for(key))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let path = self.path_for(key)?;
+
+        tokio::fs::read(&path).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                return AppError::NotFound("File not found".to_string());
+            }
+            tracing::error!("Could not read {}: {:?}", path.display(), e);
+            AppError::InternalError
+        })
+    }
+
+    async fn size(&self, key: &str) -> Result<Option<u64>> {
+        let path = self.path_for(key)?;
+
+        match tokio::fs::metadata(&path).await {
+            Ok(metadata) => Ok(Some(metadata.len())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => {
+                tracing::error!("Could not stat {}: {:?}", path.display(), e);
+                Err(AppError::InternalError)
+            }
+        }
+    }
+
+    async fn delete(&self, url: &str) -> Result<()> {
+        let key = url
+            .strip_prefix(&self.public_url)
+            .and_then(|k| k.strip_prefix('/'))
+            .ok_or_else(|| AppError::ValidationError("Invalid file URL".to_string()))?;
+        let path = self.path_for(key)?;
+
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => {}
+            // Already gone, nothing to do
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                tracing::error!("Could not delete {}: {:?}", path.display(), e);
+                return Err(AppError::InternalError);
+            }
+        }
+
+        tracing::info!("🗑️  Image deleted: {}", url);
+
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await.map_err(|e| {
+            tracing::error!("Storage directory {} unusable: {:?}", self.dir.display(), e);
+            AppError::InternalError
+        })
+    }
+
+    async fn presign_put(
+        &self,
+        key: &str,
+        content_type: &str,
+        size: u64,
+        expires_in: Duration,
+    ) -> Result<PresignedUpload> {
+        self.path_for(key)?;
+
+        let expires_at = Utc::now() + expires_in;
+        let expires = expires_at.timestamp();
+        let signature = hex::encode(
+            self.signature(key, content_type, size, expires)?
+                .finalize()
+                .into_bytes(),
+        );
+
+        let mut headers = BTreeMap::new();
+        headers.insert("content-type".to_string(), content_type.to_string());
+
+        Ok(PresignedUpload {
+            method: "PUT".to_string(),
+            url: format!(
+                "{}/{}?content_type={}&size={}&expires={}&signature={}",
+                self.upload_url, key, content_type, size, expires, signature
+            ),
+            headers,
+            expires_at,
+        })
+    }
+
+    fn verify_presigned(
+        &self,
+        key: &str,
+        content_type: &str,
+        size: u64,
+        expires: i64,
+        signature: &str,
+    ) -> Result<()> {
+        let invalid = || AppError::Forbidden("Invalid or expired upload URL".to_string());
+
+        let signature = hex::decode(signature).map_err(|_| invalid())?;
+        self.signature(key, content_type, size, expires)?
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        let expired = DateTime::from_timestamp(expires, 0).is_none_or(|at| at < Utc::now());
+        if expired {
+            return Err(invalid());
+        }
+
+        Ok(())
+    }
+
+    fn public_dir(&self) -> Option<&Path> {
+        Some(&self.dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+
+    fn storage() -> (LocalStorage, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("local-storage-{}", Uuid::new_v4()));
+        let storage = LocalStorage::new(
+            dir.to_str().unwrap(),
+            "http://localhost/uploads",
+            "http://localhost/api/upload/direct",
+            "signing-key",
+        );
+        (storage, dir)
+    }
+
+    fn chunks(parts: &[&'static [u8]]) -> ByteChunks {
+        let parts: Vec<Result<Bytes>> = parts.iter().map(|p| Ok(Bytes::from_static(p))).collect();
+        Box::pin(futures_util::stream::iter(parts))
+    }
+
+    #[tokio::test]
+    async fn put_stream_stores_the_chunks() {
+        let (storage, dir) = storage();
+
+        let url = storage
+            .put_stream("raw/a.png", chunks(&[b"abc", b"def"]), 6, "image/png")
+            .await
+            .unwrap();
+
+        assert_eq!(url, "http://localhost/uploads/raw/a.png");
+        assert_eq!(tokio::fs::read(dir.join("raw/a.png")).await.unwrap(), b"abcdef");
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn put_stream_stores_nothing_when_the_size_is_off() {
+        let (storage, dir) = storage();
+
+        for (parts, size) in [(&[b"abc".as_slice(), b"def"][..], 5), (&[b"abc".as_slice()][..], 6)] {
+            let err = storage
+                .put_stream("raw/a.png", chunks(parts), size, "image/png")
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::ValidationError(_)));
+        }
+
+        let mut entries = tokio::fs::read_dir(dir.join("raw")).await.unwrap();
+        assert!(entries.next_entry().await.unwrap().is_none());
+        tokio::fs::remove_dir_all(dir).await.unwrap();
+    }
+}
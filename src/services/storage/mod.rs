@@ -0,0 +1,154 @@
+pub mod local;
+pub mod s3;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::pin::Pin;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use uuid::Uuid;
+
+use self::local::LocalStorage;
+use self::s3::S3Storage;
+use crate::config::app::StorageConfig;
+use crate::utils::error::{AppError, Result};
+
+pub const STORAGE_BACKENDS: [&str; 2] = ["s3", "local"];
+/// Files of one request uploaded at the same time
+pub const UPLOAD_CONCURRENCY: usize = 3;
+
+/// A file arriving in chunks, stored as it is received
+pub type ByteChunks = Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>;
+
+/// Request the client sends to upload a file straight to storage
+#[derive(Debug, Clone, Serialize)]
+pub struct PresignedUpload {
+    pub method: String,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,  // must be sent as-is with the upload
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Where uploaded files are kept
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Public URL of the file stored under a key
+    fn url_for(&self, key: &str) -> String;
+
+    /// Store a file under the given key and return its public URL
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<String>;
+
+    /// Store exactly `size` bytes read from `chunks` under the given key without holding the
+    /// whole file in memory and return its public URL. Nothing is stored if the size is off.
+    async fn put_stream(
+        &self,
+        key: &str,
+        chunks: ByteChunks,
+        size: u64,
+        content_type: &str,
+    ) -> Result<String>;
+
+    /// Read a whole file
+    async fn get(&self, key: &str) -> Result<Vec<u8>>;
+
+    /// Size in bytes of a file, `None` if nothing is stored under the key
+    async fn size(&self, key: &str) -> Result<Option<u64>>;
+
+    /// Delete a file by the public URL `put` returned
+    async fn delete(&self, url: &str) -> Result<()>;
+
+    /// Check the backend can be used, for readiness probes
+    async fn check(&self) -> Result<()>;
+
+    /// Signed request letting a client upload exactly `size` bytes under a key without going
+    /// through the API
+    async fn presign_put(
+        &self,
+        key: &str,
+        content_type: &str,
+        size: u64,
+        expires_in: Duration,
+    ) -> Result<PresignedUpload>;
+
+    /// Check the signature of a presigned upload the API has to receive itself
+    fn verify_presigned(
+        &self,
+        _key: &str,
+        _content_type: &str,
+        _size: u64,
+        _expires: i64,
+        _signature: &str,
+    ) -> Result<()> {
+        Err(AppError::NotFound(format!(
+            "{} storage takes uploads directly",
+            self.name()
+        )))
+    }
+
+    /// Directory the API has to serve the files from, for backends without public URLs of their own
+    fn public_dir(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Upload an image under a unique name in the folder and return its public URL
+    async fn upload_image(&self, file_data: Vec<u8>, content_type: &str, folder: &str) -> Result<String> {
+        let key = format!("{}/{}.{}", folder, Uuid::new_v4(), extension_from_mime(content_type));
+        self.put(&key, file_data, content_type).await
+    }
+}
+
+/// Storage picked by the configured backend
+pub async fn storage_from_config(config: &StorageConfig) -> Result<Box<dyn ObjectStorage>> {
+    match config.backend.as_str() {
+        "s3" => Ok(Box::new(S3Storage::from_config(config).await)),
+        "local" => Ok(Box::new(LocalStorage::from_config(config))),
+        other => Err(AppError::ValidationError(format!(
+            "Unknown storage backend {}, must be one of: {}",
+            other,
+            STORAGE_BACKENDS.join(", ")
+        ))),
+    }
+}
+
+/// Pass the chunks through, failing as soon as they add up to more than `size` bytes or
+/// end short of it
+fn exact_size(chunks: ByteChunks, size: u64) -> ByteChunks {
+    let size_mismatch = move || {
+        AppError::ValidationError(format!("The file must be exactly {} bytes", size))
+    };
+
+    Box::pin(futures_util::stream::try_unfold(
+        (chunks, 0u64),
+        move |(mut chunks, received)| async move {
+            match chunks.next().await.transpose()? {
+                Some(chunk) => {
+                    let received = received + chunk.len() as u64;
+                    if received > size {
+                        return Err(size_mismatch());
+                    }
+                    Ok(Some((chunk, (chunks, received))))
+                }
+                None if received == size => Ok(None),
+                None => Err(size_mismatch()),
+            }
+        },
+    ))
+}
+
+/// Get file extension from MIME type
+fn extension_from_mime(content_type: &str) -> &str {
+    match content_type {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        _ => "jpg",  // default
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::types::{CompletedMultipartUpload, CompletedPart};
+use aws_sdk_s3::{primitives::ByteStream, Client};
+use chrono::Utc;
+use futures_util::StreamExt;
+
+use super::{exact_size, ByteChunks, ObjectStorage, PresignedUpload};
+use crate::config::app::StorageConfig;
+use crate::utils::error::{AppError, Result};
+
+/// Smallest part S3 takes in a multipart upload, except for the last one
+const PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// Keeps files in an S3 bucket
+pub struct S3Storage {
+    client: Client,
+    bucket_name: String,
+    region: String,
+}
+
+impl S3Storage {
+    pub async fn from_config(config: &StorageConfig) -> Self {
+        let aws_config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(config.aws_region.clone()))
+            .load()
+            .await;
+
+        S3Storage {
+            client: Client::new(&aws_config),
+            bucket_name: config.s3_bucket.clone(),
+            region: config.aws_region.clone(),
+        }
+    }
+
+    // Read the next part of a streamed file, empty once it has all been read
+    async fn next_part(chunks: &mut ByteChunks) -> Result<Vec<u8>> {
+        let mut part = Vec::new();
+        while part.len() < PART_SIZE {
+            let Some(chunk) = chunks.next().await else {
+                break;
+            };
+            part.extend_from_slice(&chunk?);
+        }
+        Ok(part)
+    }
+
+    // Upload `first` and the rest of the stream as the parts of a multipart upload
+    async fn upload_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        first: Vec<u8>,
+        chunks: &mut ByteChunks,
+    ) -> Result<Vec<CompletedPart>> {
+        let mut parts = Vec::new();
+        let mut part = first;
+
+        while !part.is_empty() {
+            let part_number = parts.len() as i32 + 1;
+            let uploaded = self
+                .client
+                .upload_part()
+                .bucket(&self.bucket_name)
+                .key(key)
+                .upload_id(upload_id)
+                .part_number(part_number)
+                .body(ByteStream::from(part))
+                .send()
+                .await
+                .map_err(|e| {
+                    tracing::error!("S3 part upload error: {:?}", e);
+                    AppError::InternalError
+                })?;
+
+            parts.push(
+                CompletedPart::builder()
+                    .part_number(part_number)
+                    .set_e_tag(uploaded.e_tag)
+                    .build(),
+            );
+            part = Self::next_part(chunks).await?;
+        }
+
+        Ok(parts)
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for S3Storage {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    fn url_for(&self, key: &str) -> String {
+        format!(
+            "https://{}.s3.{}.amazonaws.com/{}",
+            self.bucket_name, self.region, key
+        )
+    }
+
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<String> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .body(ByteStream::from(data))
+            .content_type(content_type)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("S3 upload error: {:?}", e);
+                AppError::InternalError
+            })?;
+
+        let public_url = self.url_for(key);
+
+        tracing::info!("✅ Image uploaded to S3: {}", public_url);
+
+        Ok(public_url)
+    }
+
+    async fn put_stream(
+        &self,
+        key: &str,
+        chunks: ByteChunks,
+        size: u64,
+        content_type: &str,
+    ) -> Result<String> {
+        let mut chunks = exact_size(chunks, size);
+
+        // A file that fits in one part goes up in a single request
+        let first = Self::next_part(&mut chunks).await?;
+        if first.len() < PART_SIZE {
+            return self.put(key, first, content_type).await;
+        }
+
+        let upload = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .content_type(content_type)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("S3 multipart upload error: {:?}", e);
+                AppError::InternalError
+            })?;
+        let upload_id = upload.upload_id.ok_or(AppError::InternalError)?;
+
+        let completed = match self.upload_parts(key, &upload_id, first, &mut chunks).await {
+            Ok(parts) => self
+                .client
+                .complete_multipart_upload()
+                .bucket(&self.bucket_name)
+                .key(key)
+                .upload_id(&upload_id)
+                .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+                .send()
+                .await
+                .map(|_| ())
+                .map_err(|e| {
+                    tracing::error!("S3 multipart upload error: {:?}", e);
+                    AppError::InternalError
+                }),
+            Err(e) => Err(e),
+        };
+
+        // S3 keeps the parts of an unfinished upload around until it is aborted
+        if let Err(e) = completed {
+            if let Err(abort) = self
+                .client
+                .abort_multipart_upload()
+                .bucket(&self.bucket_name)
+                .key(key)
+                .upload_id(&upload_id)
+                .send()
+                .await
+            {
+                tracing::error!("S3 multipart abort error: {:?}", abort);
+            }
+            return Err(e);
+        }
+
+        let public_url = self.url_for(key);
+
+        tracing::info!("✅ Image uploaded to S3: {}", public_url);
+
+        Ok(public_url)
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.as_service_error().is_some_and(|e| e.is_no_such_key()) {
+                    return AppError::NotFound("File not found".to_string());
+                }
+                tracing::error!("S3 download error: {:?}", e);
+                AppError::InternalError
+            })?;
+
+        let data = object.body.collect().await.map_err(|e| {
+            tracing::error!("S3 download error: {:?}", e);
+            AppError::InternalError
+        })?;
+
+        Ok(data.into_bytes().to_vec())
+    }
+
+    async fn size(&self, key: &str) -> Result<Option<u64>> {
+        match self
+            .client
+            .head_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(head) => Ok(Some(head.content_length().unwrap_or(0).max(0) as u64)),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(None),
+            Err(e) => {
+                tracing::error!("S3 head error: {:?}", e);
+                Err(AppError::InternalError)
+            }
+        }
+    }
+
+    async fn delete(&self, url: &str) -> Result<()> {
+        // Extract key from URL
+        let key = url
+            .split(&self.bucket_name)
+            .nth(1)
+            .and_then(|s| s.split('/').skip(1).collect::<Vec<_>>().join("/").into())
+            .ok_or_else(|| AppError::ValidationError("Invalid S3 URL".to_string()))?;
+
+        self.client
+            .delete_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("S3 delete error: {:?}", e);
+                AppError::InternalError
+            })?;
+
+        tracing::info!("🗑️  Image deleted from S3: {}", url);
+
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<()> {
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket_name)
+            .send()
+            .await
+            .map_err(|e| {
+                tracing::error!("S3 bucket {} unreachable: {:?}", self.bucket_name, e);
+                AppError::InternalError
+            })?;
+
+        Ok(())
+    }
+
+    async fn presign_put(
+        &self,
+        key: &str,
+        content_type: &str,
+        size: u64,
+        expires_in: Duration,
+    ) -> Result<PresignedUpload> {
+        let config = PresigningConfig::expires_in(expires_in)
+            .map_err(|e| AppError::S3Error(format!("Invalid presign expiry: {}", e)))?;
+
+        // Content type and length are part of the signature, S3 refuses anything else
+        let request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(key)
+            .content_type(content_type)
+            .content_length(size as i64)
+            .presigned(config)
+            .await
+            .map_err(|e| {
+                tracing::error!("S3 presign error: {:?}", e);
+                AppError::InternalError
+            })?;
+
+        let headers: BTreeMap<String, String> = request
+            .headers()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        Ok(PresignedUpload {
+            method: request.method().to_string(),
+            url: request.uri().to_string(),
+            headers,
+            expires_at: Utc::now() + expires_in,
+        })
+    }
+}
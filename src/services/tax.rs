@@ -0,0 +1,322 @@
+use crate::models::address::SavedAddress;
+use crate::models::cart::CartResponse;
+use crate::models::product::Product;
+use crate::models::tax::{ItemTax, TaxLine, TaxRule, TaxRuleRequest, TaxRuleResponse};
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// Amount of one product line that tax is charged on
+pub struct TaxableLine {
+    pub product_id: String,
+    pub amount: f64,  // after discounts
+}
+
+pub struct TaxBreakdown {
+    pub items: Vec<Option<ItemTax>>,  // same order as the taxable lines
+    pub lines: Vec<TaxLine>,
+    pub exclusive_total: f64,  // tax to add on top of the prices
+    pub total: f64,
+}
+
+pub struct TaxService;
+
+impl TaxService {
+    // Create a tax rule (admin)
+    pub async fn create_rule(
+        rules: &Collection<TaxRule>,
+        req: TaxRuleRequest,
+    ) -> Result<TaxRuleResponse> {
+        Self::validate(&req)?;
+
+        let now = Utc::now();
+        let rule = TaxRule {
+            id: None,
+            name: req.name.trim().to_string(),
+            rate: req.rate,
+            categories: req.categories.filter(|c| !c.is_empty()),
+            regions: req.regions.filter(|r| !r.is_empty()),
+            inclusive: req.inclusive,
+            exempt: req.exempt.unwrap_or(false),
+            active: req.active.unwrap_or(true),
+            created_at: now,
+            updated_at: now,
+        };
+
+        let result = rules.insert_one(&rule).await?;
+        let inserted_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| AppError::InternalError)?;
+
+        Ok(Self::find_rule(rules, inserted_id).await?.to_response())
+    }
+
+    // List tax rules (admin)
+    pub async fn get_rules(rules: &Collection<TaxRule>) -> Result<Vec<TaxRuleResponse>> {
+        let mut cursor = rules.find(doc! {}).sort(doc! { "created_at": 1 }).await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?.to_response());
+        }
+
+        Ok(results)
+    }
+
+    // Replace the settings of a tax rule (admin)
+    pub async fn update_rule(
+        rules: &Collection<TaxRule>,
+        id: &str,
+        req: TaxRuleRequest,
+    ) -> Result<TaxRuleResponse> {
+        let object_id = Self::parse_id(id)?;
+        Self::validate(&req)?;
+
+        let result = rules
+            .update_one(
+                doc! { "_id": object_id },
+                doc! {
+                    "$set": {
+                        "name": req.name.trim(),
+                        "rate": req.rate,
+                        "categories": req.categories.filter(|c| !c.is_empty()),
+                        "regions": req.regions.filter(|r| !r.is_empty()),
+                        "inclusive": req.inclusive,
+                        "exempt": req.exempt.unwrap_or(false),
+                        "active": req.active.unwrap_or(true),
+                        "updated_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                    }
+                },
+            )
+            .await?;
+
+        if result.matched_count == 0 {
+            return Err(AppError::NotFound("Tax rule not found".to_string()));
+        }
+
+        Ok(Self::find_rule(rules, object_id).await?.to_response())
+    }
+
+    // Delete a tax rule (admin)
+    pub async fn delete_rule(rules: &Collection<TaxRule>, id: &str) -> Result<()> {
+        let object_id = Self::parse_id(id)?;
+
+        let result = rules.delete_one(doc! { "_id": object_id }).await?;
+        if result.deleted_count == 0 {
+            return Err(AppError::NotFound("Tax rule not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Tax on each line for delivery to `region`. Without a region only rules
+    // that apply everywhere are used.
+    pub async fn calculate(
+        rules: &Collection<TaxRule>,
+        products: &Collection<Product>,
+        lines: &[TaxableLine],
+        region: Option<&str>,
+    ) -> Result<TaxBreakdown> {
+        let mut active = Vec::new();
+        let mut cursor = rules.find(doc! { "active": true }).sort(doc! { "created_at": 1 }).await?;
+        while let Some(result) = cursor.next().await {
+            active.push(result?);
+        }
+
+        let ids: Vec<ObjectId> = lines
+            .iter()
+            .filter_map(|l| ObjectId::from_str(&l.product_id).ok())
+            .collect();
+        let mut categories: HashMap<String, String> = HashMap::new();
+        let mut cursor = products.find(doc! { "_id": { "$in": ids } }).await?;
+        while let Some(result) = cursor.next().await {
+            let product = result?;
+            if let Some(id) = product.id {
+                categories.insert(id.to_hex(), product.category);
+            }
+        }
+
+        let mut breakdown = TaxBreakdown {
+            items: Vec::new(),
+            lines: Vec::new(),
+            exclusive_total: 0.0,
+            total: 0.0,
+        };
+
+        for line in lines {
+            let category = categories.get(&line.product_id).map(String::as_str);
+            let rule = Self::rule_for(&active, category, region).filter(|r| !r.exempt);
+
+            let Some(rule) = rule else {
+                breakdown.items.push(None);
+                continue;
+            };
+
+            let amount = if rule.inclusive {
+                line.amount * rule.rate / (100.0 + rule.rate)
+            } else {
+                line.amount * rule.rate / 100.0
+            };
+            let amount = (amount * 100.0).round() / 100.0;
+
+            breakdown.total += amount;
+            if !rule.inclusive {
+                breakdown.exclusive_total += amount;
+            }
+
+            match breakdown
+                .lines
+                .iter_mut()
+                .find(|l| l.rule == rule.name && l.rate == rule.rate && l.inclusive == rule.inclusive)
+            {
+                Some(tax_line) => {
+                    tax_line.taxable_amount += line.amount;
+                    tax_line.amount += amount;
+                }
+                None => breakdown.lines.push(TaxLine {
+                    rule: rule.name.clone(),
+                    rate: rule.rate,
+                    inclusive: rule.inclusive,
+                    taxable_amount: line.amount,
+                    amount,
+                }),
+            }
+
+            breakdown.items.push(Some(ItemTax {
+                rule: rule.name.clone(),
+                rate: rule.rate,
+                inclusive: rule.inclusive,
+                taxable_amount: line.amount,
+                amount,
+            }));
+        }
+
+        // Sums of rounded amounts can pick up float noise
+        for tax_line in breakdown.lines.iter_mut() {
+            tax_line.taxable_amount = (tax_line.taxable_amount * 100.0).round() / 100.0;
+            tax_line.amount = (tax_line.amount * 100.0).round() / 100.0;
+        }
+        breakdown.exclusive_total = (breakdown.exclusive_total * 100.0).round() / 100.0;
+        breakdown.total = (breakdown.total * 100.0).round() / 100.0;
+
+        Ok(breakdown)
+    }
+
+    // Spread an order-level discount over lines in proportion to their amount
+    pub fn allocate_discount(amounts: &[f64], discount: f64) -> Vec<f64> {
+        let total: f64 = amounts.iter().sum();
+        if total <= 0.0 || discount <= 0.0 {
+            return amounts.to_vec();
+        }
+
+        amounts
+            .iter()
+            .map(|amount| (amount - discount * amount / total).max(0.0))
+            .collect()
+    }
+
+    // Add tax to a cart, estimated for the customer's default address
+    pub async fn price_cart(
+        rules: &Collection<TaxRule>,
+        products: &Collection<Product>,
+        addresses: &Collection<SavedAddress>,
+        user_id: &str,
+        cart: &mut CartResponse,
+    ) -> Result<()> {
+        let region = addresses
+            .find_one(doc! { "user_id": user_id, "is_default": true })
+            .await?
+            .map(|saved| saved.address.state);
+
+        let discount = cart.discount.as_ref().map(|d| d.amount).unwrap_or(0.0);
+        let amounts: Vec<f64> = cart.items.iter().map(|i| i.line_total).collect();
+        let lines: Vec<TaxableLine> = cart
+            .items
+            .iter()
+            .zip(Self::allocate_discount(&amounts, discount))
+            .map(|(item, amount)| TaxableLine {
+                product_id: item.product_id.clone(),
+                amount,
+            })
+            .collect();
+
+        let breakdown = Self::calculate(rules, products, &lines, region.as_deref()).await?;
+
+        cart.tax_amount = breakdown.total;
+        cart.tax_lines = breakdown.lines;
+        cart.total += breakdown.exclusive_total;
+
+        Ok(())
+    }
+
+    // The most specific matching rule wins, a category match counts more than a region match
+    fn rule_for<'a>(
+        rules: &'a [TaxRule],
+        category: Option<&str>,
+        region: Option<&str>,
+    ) -> Option<&'a TaxRule> {
+        let mut best: Option<(&TaxRule, u8)> = None;
+
+        for rule in rules {
+            let category_match = match (&rule.categories, category) {
+                (None, _) => Some(0),
+                (Some(cats), Some(c)) if cats.iter().any(|x| x.eq_ignore_ascii_case(c)) => Some(2),
+                _ => None,
+            };
+            let region_match = match (&rule.regions, region) {
+                (None, _) => Some(0),
+                (Some(regions), Some(r)) if regions.iter().any(|x| x.eq_ignore_ascii_case(r.trim())) => {
+                    Some(1)
+                }
+                _ => None,
+            };
+
+            let (Some(c), Some(r)) = (category_match, region_match) else {
+                continue;
+            };
+            let score = c + r;
+
+            // Exemptions win ties
+            let better = match best {
+                None => true,
+                Some((current, best_score)) => {
+                    score > best_score || (score == best_score && rule.exempt && !current.exempt)
+                }
+            };
+            if better {
+                best = Some((rule, score));
+            }
+        }
+
+        best.map(|(rule, _)| rule)
+    }
+
+    fn validate(req: &TaxRuleRequest) -> Result<()> {
+        if req.name.trim().is_empty() {
+            return Err(AppError::ValidationError("Tax rule name is required".to_string()));
+        }
+        if !(0.0..=100.0).contains(&req.rate) {
+            return Err(AppError::ValidationError(
+                "Tax rate must be between 0 and 100".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn find_rule(rules: &Collection<TaxRule>, id: ObjectId) -> Result<TaxRule> {
+        rules
+            .find_one(doc! { "_id": id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Tax rule not found".to_string()))
+    }
+
+    fn parse_id(id: &str) -> Result<ObjectId> {
+        ObjectId::from_str(id).map_err(|_| AppError::ValidationError("Invalid tax rule ID".to_string()))
+    }
+}
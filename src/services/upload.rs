@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::doc;
+use mongodb::Collection;
+use uuid::Uuid;
+
+use crate::models::media::Media;
+use crate::models::upload::{
+    ConfirmUploadRequest, ConfirmUploadResponse, PendingUpload, PresignUploadRequest,
+    PresignUploadResponse,
+};
+use crate::services::image::{ImageService, UploadedImage};
+use crate::services::media::MediaService;
+use crate::services::storage::ObjectStorage;
+use crate::utils::error::{AppError, Result};
+
+/// Same limit as uploads through the API
+const MAX_UPLOAD_SIZE: u64 = 5 * 1024 * 1024;
+const PRESIGN_EXPIRY: Duration = Duration::from_secs(15 * 60);
+/// Unconfirmed uploads are thrown away this long after their URL expired
+const UNCONFIRMED_TTL_HOURS: i64 = 24;
+const IMAGE_TYPES: [&str; 4] = ["image/jpeg", "image/png", "image/gif", "image/webp"];
+
+pub struct UploadService;
+
+impl UploadService {
+    // Hand out a presigned URL for one image and remember who may confirm it
+    pub async fn presign(
+        uploads: &Collection<PendingUpload>,
+        storage: &dyn ObjectStorage,
+        user_id: &str,
+        req: PresignUploadRequest,
+    ) -> Result<PresignUploadResponse> {
+        if !IMAGE_TYPES.contains(&req.content_type.as_str()) {
+            return Err(AppError::ValidationError(format!(
+                "Content type must be one of: {}",
+                IMAGE_TYPES.join(", ")
+            )));
+        }
+
+        if req.size == 0 || req.size > MAX_UPLOAD_SIZE {
+            return Err(AppError::ValidationError(
+                "File size must be less than 5MB".to_string(),
+            ));
+        }
+
+        let key = format!("incoming/{}", Uuid::new_v4());
+        let upload = storage
+            .presign_put(&key, &req.content_type, req.size, PRESIGN_EXPIRY)
+            .await?;
+
+        let pending = PendingUpload {
+            id: None,
+            key: key.clone(),
+            user_id: user_id.to_string(),
+            content_type: req.content_type,
+            size: req.size as i64,
+            status: "pending".to_string(),
+            url: None,
+            expires_at: upload.expires_at,
+            created_at: Utc::now(),
+            confirmed_at: None,
+            raw_purged: false,
+        };
+        uploads.insert_one(&pending).await?;
+
+        Ok(PresignUploadResponse { key, upload })
+    }
+
+    // Validate an uploaded file, turn it into image variants and drop the raw upload
+    pub async fn confirm(
+        uploads: &Collection<PendingUpload>,
+        media: &Collection<Media>,
+        storage: &dyn ObjectStorage,
+        user_id: &str,
+        req: ConfirmUploadRequest,
+    ) -> Result<ConfirmUploadResponse> {
+        let filter = doc! { "key": &req.key, "user_id": user_id };
+
+        // Claim the upload so concurrent confirms don't process it twice
+        let pending = uploads
+            .find_one_and_update(
+                doc! { "key": &req.key, "user_id": user_id, "status": "pending" },
+                doc! { "$set": { "status": "processing" } },
+            )
+            .await?
+            .ok_or_else(|| AppError::NotFound("Upload not found or already confirmed".to_string()))?;
+
+        match Self::process(storage, &pending).await {
+            Ok(image) => {
+                if let Err(e) =
+                    MediaService::record(media, user_id, &image.url, image.files(), None).await
+                {
+                    // Nothing points at the variants yet, drop them and let the client retry
+                    for file in image.files() {
+                        if let Err(e) = storage.delete(&file).await {
+                            tracing::error!("Could not delete unrecorded {}: {:?}", file, e);
+                        }
+                    }
+                    uploads
+                        .update_one(filter, doc! { "$set": { "status": "pending" } })
+                        .await?;
+                    return Err(e);
+                }
+
+                // The image is in the library now, what is left can't undo that. The sweeper
+                // deletes the raw file again once its upload URL has expired.
+                if let Err(e) = storage.delete(&storage.url_for(&pending.key)).await {
+                    tracing::error!("Could not delete raw upload {}: {:?}", pending.key, e);
+                }
+                let confirmed = uploads
+                    .update_one(
+                        filter,
+                        doc! { "$set": {
+                            "status": "confirmed",
+                            "url": &image.url,
+                            "confirmed_at": Utc::now(),
+                        } },
+                    )
+                    .await;
+                if let Err(e) = confirmed {
+                    tracing::error!("Could not mark upload {} confirmed: {:?}", pending.key, e);
+                }
+
+                Ok(ConfirmUploadResponse { key: pending.key, image })
+            }
+            Err(AppError::ValidationError(message)) => {
+                // The file will never be valid, throw it away (the sweeper retries the delete)
+                if let Err(e) = storage.delete(&storage.url_for(&pending.key)).await {
+                    tracing::error!("Could not delete rejected upload {}: {:?}", pending.key, e);
+                }
+                uploads
+                    .update_one(filter, doc! { "$set": { "status": "rejected" } })
+                    .await?;
+
+                Err(AppError::ValidationError(message))
+            }
+            Err(e) => {
+                // Not uploaded yet or a storage hiccup, the client can confirm again
+                uploads
+                    .update_one(filter, doc! { "$set": { "status": "pending" } })
+                    .await?;
+
+                Err(e)
+            }
+        }
+    }
+
+    // Checks run on the uploaded bytes, nothing the client declared is trusted
+    async fn process(
+        storage: &dyn ObjectStorage,
+        pending: &PendingUpload,
+    ) -> Result<UploadedImage> {
+        let size = storage
+            .size(&pending.key)
+            .await?
+            .ok_or_else(|| AppError::Conflict("The file has not been uploaded yet".to_string()))?;
+
+        if size > MAX_UPLOAD_SIZE || size != pending.size as u64 {
+            return Err(AppError::ValidationError(
+                "Uploaded file doesn't match the declared size".to_string(),
+            ));
+        }
+
+        let data = storage.get(&pending.key).await?;
+        let content_type = ImageService::sniff_content_type(&data)?;
+        if content_type != pending.content_type {
+            return Err(AppError::ValidationError(format!(
+                "Uploaded file is {}, not {}",
+                content_type, pending.content_type
+            )));
+        }
+
+        ImageService::upload_variants(storage, data, "products").await
+    }
+
+    // Delete raw files of uploads that were never confirmed, returns how many were dropped
+    pub async fn expire_unconfirmed(
+        uploads: &Collection<PendingUpload>,
+        storage: &dyn ObjectStorage,
+    ) -> Result<u64> {
+        let cutoff = Utc::now() - chrono::Duration::hours(UNCONFIRMED_TTL_HOURS);
+        let mut cursor = uploads
+            .find(doc! {
+                "status": "pending",
+                "expires_at": { "$lt": mongodb::bson::DateTime::from_chrono(cutoff) },
+            })
+            .await?;
+
+        let mut expired = 0;
+        while let Some(result) = cursor.next().await {
+            let pending = result?;
+
+            // Most were never uploaded, deleting a missing file is fine
+            storage.delete(&storage.url_for(&pending.key)).await?;
+            uploads
+                .update_one(
+                    doc! { "_id": pending.id, "status": "pending" },
+                    doc! { "$set": { "status": "expired" } },
+                )
+                .await?;
+            expired += 1;
+        }
+
+        Ok(expired)
+    }
+
+    // The presigned URL of a processed upload stays usable until it expires, so the raw
+    // file is deleted once more after that. Returns how many were cleaned up.
+    pub async fn purge_processed(
+        uploads: &Collection<PendingUpload>,
+        storage: &dyn ObjectStorage,
+    ) -> Result<u64> {
+        let mut cursor = uploads
+            .find(doc! {
+                "status": { "$in": ["confirmed", "rejected"] },
+                "raw_purged": { "$ne": true },
+                "expires_at": { "$lt": mongodb::bson::DateTime::from_chrono(Utc::now()) },
+            })
+            .await?;
+
+        let mut purged = 0;
+        while let Some(result) = cursor.next().await {
+            let upload = result?;
+
+            storage.delete(&storage.url_for(&upload.key)).await?;
+            uploads
+                .update_one(doc! { "_id": upload.id }, doc! { "$set": { "raw_purged": true } })
+                .await?;
+            purged += 1;
+        }
+
+        Ok(purged)
+    }
+}
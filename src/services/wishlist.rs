@@ -0,0 +1,435 @@
+use crate::models::cart::{AddToCartRequest, CartItem};
+use crate::models::product::{Product, ProductResponse};
+use crate::models::promotion::Promotion;
+use crate::models::wishlist::{
+    MoveToCartLine, Wishlist, WishlistCount, WishlistItem, WishlistItemResponse,
+    WishlistRequest, WishlistResponse,
+};
+use crate::services::cart::CartService;
+use crate::services::promotion::PromotionService;
+use crate::utils::error::{AppError, Result};
+use chrono::Utc;
+use futures_util::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::Collection;
+use std::collections::HashMap;
+use std::str::FromStr;
+use uuid::Uuid;
+
+const MAX_WISHLISTS: u64 = 20;
+const MAX_WISHLIST_ITEMS: usize = 100;
+const DEFAULT_COUNT_LIMIT: i64 = 50;
+
+pub struct WishlistService;
+
+impl WishlistService {
+    // Create an empty wishlist
+    pub async fn create_wishlist(
+        wishlists: &Collection<Wishlist>,
+        user_id: &str,
+        req: WishlistRequest,
+    ) -> Result<Wishlist> {
+        let name = Self::validate_name(&req.name)?;
+
+        let existing = wishlists.count_documents(doc! { "user_id": user_id }).await?;
+        if existing >= MAX_WISHLISTS {
+            return Err(AppError::ValidationError(format!(
+                "Maximum {} wishlists allowed",
+                MAX_WISHLISTS
+            )));
+        }
+
+        let now = Utc::now();
+        let wishlist = Wishlist {
+            id: None,
+            user_id: user_id.to_string(),
+            name,
+            items: Vec::new(),
+            share_token: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let result = wishlists.insert_one(&wishlist).await?;
+        let inserted_id = result
+            .inserted_id
+            .as_object_id()
+            .ok_or_else(|| AppError::InternalError)?;
+
+        Self::find_wishlist(wishlists, doc! { "_id": inserted_id, "user_id": user_id }).await
+    }
+
+    // List the customer's wishlists, newest first
+    pub async fn get_wishlists(
+        wishlists: &Collection<Wishlist>,
+        user_id: &str,
+    ) -> Result<Vec<Wishlist>> {
+        let mut cursor = wishlists
+            .find(doc! { "user_id": user_id })
+            .sort(doc! { "created_at": -1 })
+            .await?;
+
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?);
+        }
+
+        Ok(results)
+    }
+
+    // Get one of the customer's wishlists
+    pub async fn get_wishlist(
+        wishlists: &Collection<Wishlist>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<Wishlist> {
+        let object_id = Self::parse_id(id)?;
+        Self::find_wishlist(wishlists, doc! { "_id": object_id, "user_id": user_id }).await
+    }
+
+    // Get a wishlist through its share link
+    pub async fn get_shared_wishlist(
+        wishlists: &Collection<Wishlist>,
+        token: &str,
+    ) -> Result<Wishlist> {
+        Self::find_wishlist(wishlists, doc! { "share_token": token }).await
+    }
+
+    // Rename a wishlist
+    pub async fn rename_wishlist(
+        wishlists: &Collection<Wishlist>,
+        id: &str,
+        user_id: &str,
+        req: WishlistRequest,
+    ) -> Result<Wishlist> {
+        let object_id = Self::parse_id(id)?;
+        let name = Self::validate_name(&req.name)?;
+
+        Self::update(
+            wishlists,
+            object_id,
+            user_id,
+            doc! { "$set": { "name": name } },
+        )
+        .await
+    }
+
+    // Delete a wishlist
+    pub async fn delete_wishlist(
+        wishlists: &Collection<Wishlist>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<()> {
+        let object_id = Self::parse_id(id)?;
+
+        let result = wishlists
+            .delete_one(doc! { "_id": object_id, "user_id": user_id })
+            .await?;
+        if result.deleted_count == 0 {
+            return Err(AppError::NotFound("Wishlist not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Save a product to a wishlist, saving it twice does nothing
+    pub async fn add_item(
+        wishlists: &Collection<Wishlist>,
+        products: &Collection<Product>,
+        id: &str,
+        user_id: &str,
+        product_id: &str,
+    ) -> Result<Wishlist> {
+        let object_id = Self::parse_id(id)?;
+        let wishlist =
+            Self::find_wishlist(wishlists, doc! { "_id": object_id, "user_id": user_id }).await?;
+
+        let product_object_id = ObjectId::from_str(product_id)
+            .map_err(|_| AppError::ValidationError("Invalid product ID".to_string()))?;
+        products
+            .find_one(doc! { "_id": product_object_id })
+            .await?
+            .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+
+        if wishlist.items.iter().any(|i| i.product_id == product_id) {
+            return Ok(wishlist);
+        }
+        if wishlist.items.len() >= MAX_WISHLIST_ITEMS {
+            return Err(AppError::ValidationError(format!(
+                "A wishlist can hold at most {} products",
+                MAX_WISHLIST_ITEMS
+            )));
+        }
+
+        let item = WishlistItem {
+            product_id: product_id.to_string(),
+            added_at: Utc::now(),
+        };
+        Self::update(
+            wishlists,
+            object_id,
+            user_id,
+            doc! { "$push": { "items": mongodb::bson::to_bson(&item)? } },
+        )
+        .await
+    }
+
+    // Take a product off a wishlist
+    pub async fn remove_item(
+        wishlists: &Collection<Wishlist>,
+        id: &str,
+        user_id: &str,
+        product_id: &str,
+    ) -> Result<Wishlist> {
+        let object_id = Self::parse_id(id)?;
+
+        let result = wishlists
+            .update_one(
+                doc! { "_id": object_id, "user_id": user_id, "items.product_id": product_id },
+                doc! {
+                    "$pull": { "items": { "product_id": product_id } },
+                    "$set": {
+                        "updated_at": mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis()),
+                    },
+                },
+            )
+            .await?;
+        if result.matched_count == 0 {
+            return Err(AppError::NotFound("Product not in wishlist".to_string()));
+        }
+
+        Self::find_wishlist(wishlists, doc! { "_id": object_id, "user_id": user_id }).await
+    }
+
+    // Turn on the public share link of a wishlist, the link stays the same once created
+    pub async fn share(
+        wishlists: &Collection<Wishlist>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<Wishlist> {
+        let object_id = Self::parse_id(id)?;
+        let wishlist =
+            Self::find_wishlist(wishlists, doc! { "_id": object_id, "user_id": user_id }).await?;
+        if wishlist.share_token.is_some() {
+            return Ok(wishlist);
+        }
+
+        Self::update(
+            wishlists,
+            object_id,
+            user_id,
+            doc! { "$set": { "share_token": Uuid::new_v4().simple().to_string() } },
+        )
+        .await
+    }
+
+    // Turn off the public share link, old links stop working
+    pub async fn unshare(
+        wishlists: &Collection<Wishlist>,
+        id: &str,
+        user_id: &str,
+    ) -> Result<Wishlist> {
+        let object_id = Self::parse_id(id)?;
+        Self::update(
+            wishlists,
+            object_id,
+            user_id,
+            doc! { "$set": { "share_token": null } },
+        )
+        .await
+    }
+
+    // Put wishlist products in the cart, one of each. Products that made it are taken off
+    // the list, the rest stay with the reason they didn't.
+    pub async fn move_to_cart(
+        wishlists: &Collection<Wishlist>,
+        cart: &Collection<CartItem>,
+        products: &Collection<Product>,
+        active: &[Promotion],
+        id: &str,
+        user_id: &str,
+        product_ids: Option<Vec<String>>,
+    ) -> Result<Vec<MoveToCartLine>> {
+        let object_id = Self::parse_id(id)?;
+        let wishlist =
+            Self::find_wishlist(wishlists, doc! { "_id": object_id, "user_id": user_id }).await?;
+
+        let selected: Vec<String> = match product_ids {
+            Some(ids) => {
+                if let Some(missing) =
+                    ids.iter().find(|id| !wishlist.items.iter().any(|i| &i.product_id == *id))
+                {
+                    return Err(AppError::ValidationError(format!(
+                        "Product {} is not in this wishlist",
+                        missing
+                    )));
+                }
+                ids
+            }
+            None => wishlist.items.iter().map(|i| i.product_id.clone()).collect(),
+        };
+
+        let mut lines = Vec::new();
+        for product_id in selected {
+            let req = AddToCartRequest {
+                product_id: product_id.clone(),
+                quantity: 1,
+            };
+            let line = match CartService::add_to_cart(cart, products, active, user_id, req).await {
+                Ok(_) => MoveToCartLine {
+                    product_id,
+                    status: "moved".to_string(),
+                    message: None,
+                },
+                Err(AppError::ValidationError(message)) => MoveToCartLine {
+                    product_id,
+                    status: "out_of_stock".to_string(),
+                    message: Some(message),
+                },
+                Err(AppError::NotFound(message)) => MoveToCartLine {
+                    product_id,
+                    status: "unavailable".to_string(),
+                    message: Some(message),
+                },
+                Err(e) => return Err(e),
+            };
+            lines.push(line);
+        }
+
+        let moved: Vec<&str> = lines
+            .iter()
+            .filter(|l| l.status == "moved")
+            .map(|l| l.product_id.as_str())
+            .collect();
+        if !moved.is_empty() {
+            Self::update(
+                wishlists,
+                object_id,
+                user_id,
+                doc! { "$pull": { "items": { "product_id": { "$in": moved } } } },
+            )
+            .await?;
+        }
+
+        Ok(lines)
+    }
+
+    // How many wishlists hold each product, most wished first (admin)
+    pub async fn product_counts(
+        wishlists: &Collection<Wishlist>,
+        product_id: Option<&str>,
+        limit: Option<i64>,
+    ) -> Result<Vec<WishlistCount>> {
+        let mut pipeline = vec![doc! { "$unwind": "$items" }];
+        if let Some(product_id) = product_id {
+            pipeline.push(doc! { "$match": { "items.product_id": product_id } });
+        }
+        pipeline.extend([
+            doc! { "$group": { "_id": "$items.product_id", "wishlist_count": { "$sum": 1 } } },
+            doc! { "$sort": { "wishlist_count": -1, "_id": 1 } },
+            doc! { "$limit": limit.unwrap_or(DEFAULT_COUNT_LIMIT).clamp(1, 500) },
+        ]);
+
+        let mut cursor = wishlists.aggregate(pipeline).with_type::<WishlistCount>().await?;
+        let mut results = Vec::new();
+        while let Some(result) = cursor.next().await {
+            results.push(result?);
+        }
+
+        Ok(results)
+    }
+
+    // Responses with the current product details, priced at promotion prices
+    pub async fn to_responses(
+        products: &Collection<Product>,
+        promotions: &Collection<Promotion>,
+        wishlists: Vec<Wishlist>,
+    ) -> Result<Vec<WishlistResponse>> {
+        let ids: Vec<ObjectId> = wishlists
+            .iter()
+            .flat_map(|w| w.items.iter())
+            .filter_map(|i| ObjectId::from_str(&i.product_id).ok())
+            .collect();
+
+        let mut found = Vec::new();
+        let mut cursor = products.find(doc! { "_id": { "$in": ids } }).await?;
+        while let Some(result) = cursor.next().await {
+            found.push(result?.to_response());
+        }
+        PromotionService::price_products(promotions, &mut found).await?;
+        let current: HashMap<String, ProductResponse> =
+            found.into_iter().map(|p| (p.id.clone(), p)).collect();
+
+        Ok(wishlists
+            .into_iter()
+            .map(|wishlist| WishlistResponse {
+                id: wishlist.id.unwrap().to_hex(),
+                name: wishlist.name,
+                item_count: wishlist.items.len(),
+                items: wishlist
+                    .items
+                    .into_iter()
+                    .map(|item| WishlistItemResponse {
+                        product: current.get(&item.product_id).cloned(),
+                        product_id: item.product_id,
+                        added_at: item.added_at,
+                    })
+                    .collect(),
+                share_token: wishlist.share_token,
+                created_at: wishlist.created_at,
+                updated_at: wishlist.updated_at,
+            })
+            .collect())
+    }
+
+    async fn update(
+        wishlists: &Collection<Wishlist>,
+        id: ObjectId,
+        user_id: &str,
+        mut update: mongodb::bson::Document,
+    ) -> Result<Wishlist> {
+        let now = mongodb::bson::DateTime::from_millis(Utc::now().timestamp_millis());
+        match update.get_document_mut("$set") {
+            Ok(set) => {
+                set.insert("updated_at", now);
+            }
+            Err(_) => {
+                update.insert("$set", doc! { "updated_at": now });
+            }
+        }
+
+        let result = wishlists
+            .update_one(doc! { "_id": id, "user_id": user_id }, update)
+            .await?;
+        if result.matched_count == 0 {
+            return Err(AppError::NotFound("Wishlist not found".to_string()));
+        }
+
+        Self::find_wishlist(wishlists, doc! { "_id": id, "user_id": user_id }).await
+    }
+
+    fn validate_name(name: &str) -> Result<String> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > 100 {
+            return Err(AppError::ValidationError(
+                "Wishlist name must be 1 to 100 characters".to_string(),
+            ));
+        }
+
+        Ok(name.to_string())
+    }
+
+    async fn find_wishlist(
+        wishlists: &Collection<Wishlist>,
+        query: mongodb::bson::Document,
+    ) -> Result<Wishlist> {
+        wishlists
+            .find_one(query)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Wishlist not found".to_string()))
+    }
+
+    fn parse_id(id: &str) -> Result<ObjectId> {
+        ObjectId::from_str(id).map_err(|_| AppError::ValidationError("Invalid wishlist ID".to_string()))
+    }
+}
@@ -18,6 +18,9 @@ pub enum AppError {
     #[error("Authentication failed: {0}")]
     AuthError(String),
     
+    #[error("Access denied: {0}")]
+    Forbidden(String),
+    
     #[error("Resource not found: {0}")]
     NotFound(String),
     
@@ -26,6 +29,9 @@ pub enum AppError {
     
     #[error("Payment error: {0}")]
     PaymentError(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
     
     #[error("Internal server error")]
     InternalError,
@@ -45,9 +51,11 @@ impl IntoResponse for AppError {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Serialization error".to_string())
             }
             AppError::AuthError(ref msg) => (StatusCode::UNAUTHORIZED, msg.clone()),
+            AppError::Forbidden(ref msg) => (StatusCode::FORBIDDEN, msg.clone()),
             AppError::NotFound(ref msg) => (StatusCode::NOT_FOUND, msg.clone()),
             AppError::ValidationError(ref msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::PaymentError(ref msg) => (StatusCode::PAYMENT_REQUIRED, msg.clone()),
+            AppError::Conflict(ref msg) => (StatusCode::CONFLICT, msg.clone()),
             AppError::InternalError => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal error".to_string())
             }